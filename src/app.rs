@@ -1,7 +1,108 @@
-use crate::data::{DataSource, TableData};
-use anyhow::Result;
+use crate::clipboard::Copied;
+use crate::column_stats::{ColumnStats, Stats, Totals};
+use crate::data::source::DataSourceType;
+use crate::data::source::check_filter;
+use crate::data::subset::Partial;
+use crate::data::format::type_label;
+use crate::data::{BackgroundLoad, CellFormat, DataSource, TableData, ENCODINGS};
+use crate::filter::{fuzzy_chars, fuzzy_match, quote_value, FilterOptions};
+use crate::live_filter::{FilterJob, LiveFilter};
+use crate::prompt::Prompt;
+use crate::theme::Theme;
+use crate::value_counts::ValueCounts;
+use crate::describe::Describe;
+use crate::resample::Resample;
+use crate::sort::{restore_order, sort_frame, SortOptions, SortOrder};
 use polars::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A value as one tab-separated field: tabs and line breaks become spaces
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Blank space the table leaves between columns
+pub const COLUMN_SPACING: u16 = 1;
+
+/// How often rows of a file still loading are filtered and sorted into the view
+const LOADING_REFRESH: Duration = Duration::from_millis(500);
+
+/// Pages kept built, one per pane
+const CACHED_PAGES: usize = 2;
+
+/// Most rows of a database table or lazily read Parquet file fetched to summarize it,
+/// as fetching all of them is what reading it page by page avoids
+pub const SUMMARY_ROWS: usize = 100_000;
+
+/// Most filtered rows of a database table or lazily read Parquet file copied or piped
+/// at once, as they are held in memory; `:export` writes any number a chunk at a time
+pub const PAGED_VIEW_ROWS: usize = 100_000;
+
+/// What a built page of the table depends on; it is built again when any of them changes
+#[derive(Debug, Clone, PartialEq)]
+struct PageKey {
+    scroll_offset: usize,
+    page_size: usize,
+    column_offset: usize,
+    table_width: u16,
+    /// `App::rows_version` when the page was built
+    rows_version: u64,
+    columns: Vec<String>,
+    format: CellFormat,
+}
+
+/// What the totals of the focused column depend on; they are worked out again when
+/// any of them changes
+#[derive(Debug, Clone, PartialEq)]
+struct TotalsKey {
+    rows_version: u64,
+    column: String,
+    /// Picked rows the totals are of, or none for all filtered rows
+    rows: Vec<usize>,
+    format: CellFormat,
+}
+
+/// An applied filter running in the background
+struct PendingFilter {
+    /// Filter being typed when it was applied, put back in the prompt if it fails
+    draft: String,
+    /// Scroll position to keep when the rows are refreshed rather than filtered anew
+    keep_scroll: Option<usize>,
+}
+
+/// Position of a table pane: the rows, columns and cell it shows
+#[derive(Debug, Clone)]
+pub struct Pane {
+    pub scroll_offset: usize,
+    pub column_offset: usize,
+    pub page_size: usize,
+    pub cursor_row: usize,
+    pub cursor_column: usize,
+    pub column_widths: Vec<u16>,
+    pub table_width: u16,
+}
+
+/// Second pane over the same rows, shown below or above the focused one
+#[derive(Debug, Clone)]
+pub struct Split {
+    /// Position of the pane without focus
+    pub other: Pane,
+    /// Whether the focused pane is the bottom one
+    pub focus_bottom: bool,
+}
+
+/// Where to open the rows, given on the command line
+#[derive(Debug, Clone, Copy)]
+pub enum RowJump {
+    /// The nth row shown, counting from 1 (`--goto-row`)
+    Row(usize),
+    /// The last rows (`--tail` without a count)
+    End,
+}
 
 /// Application state
 pub struct App {
@@ -9,77 +110,351 @@ pub struct App {
     data_source: DataSource,
     /// Filtered dataframe
     filtered_df: DataFrame,
+    /// Bumped whenever the rows shown change: filtered, sorted, reloaded or, for paged
+    /// sources, filtered and sorted on the server
+    rows_version: u64,
+    /// Pages built lately, most recent first, so they are not formatted again every frame
+    page_cache: RefCell<Vec<(PageKey, Rc<TableData>)>>,
     /// Current scroll position (row offset)
     pub scroll_offset: usize,
     /// Current column offset for horizontal scrolling
     pub column_offset: usize,
     /// Number of rows to display per page
     pub page_size: usize,
-    /// Search/filter pattern
+    /// Applied filters, each narrowing the result of the ones before it
+    pub filters: Vec<String>,
+    /// Filter being typed, pushed onto `filters` when applied
     pub filter_pattern: String,
     /// Cursor position in filter input (index in string)
     pub filter_cursor: usize,
     /// Whether we're in filter input mode
     pub filter_mode: bool,
-    /// Column to filter (None = search all columns)
-    pub filter_column: Option<String>,
+    /// Whether we're typing a `:` command
+    pub command_mode: bool,
+    /// Text of the `:` command prompt
+    pub command: Prompt,
+    /// SQL query whose result is shown instead of the source data
+    pub sql_query: Option<String>,
+    /// Result of `sql_query`, which the filter then narrows down
+    query_df: Option<DataFrame>,
+    /// Columns whose values rows must share with another filtered row to be shown
+    /// (`:dupes`), all of them when empty; `None` shows every filtered row
+    pub duplicates: Option<Vec<String>>,
+    /// Whether we're navigating individual cells
+    pub cell_mode: bool,
+    /// Row of the focused cell (index into the filtered rows)
+    pub cursor_row: usize,
+    /// Column of the focused cell (index into the visible columns)
+    pub cursor_column: usize,
+    /// Focused row as (column, value) pairs while its record view is open
+    pub record: Option<Vec<(String, String)>>,
+    /// Full value of the focused cell as (column, value) while its popup is open
+    pub value_popup: Option<(String, String)>,
+    /// Statistics of the focused column over the filtered rows, as (label, value) lines
+    pub column_profile: Option<(String, Vec<(String, String)>)>,
+    /// Distinct values of the focused column and their counts, while the frequency
+    /// screen is open
+    pub value_counts: Option<ValueCounts>,
+    /// Summary of the numeric columns while the describe screen is open
+    pub describe: Option<Describe>,
+    /// Filtered rows bucketed by time while the resample screen is open
+    pub resample: Option<Resample>,
+    /// Whether the help overlay is open
+    pub help_open: bool,
+    /// First line shown in the record, value or help popup
+    pub popup_scroll: usize,
+    /// Furthest the popup can scroll, measured when it is drawn
+    pub popup_max_scroll: Cell<usize>,
+    /// Highlighted entry in the column-name completion popup
+    pub completion_index: usize,
+    /// Case-insensitive and fuzzy matching settings for filters
+    pub filter_options: FilterOptions,
+    /// Background filtering of the pattern while it is typed
+    live_filter: LiveFilter,
+    /// Background run of the applied filters
+    filter_job: FilterJob,
+    pending_filter: Option<PendingFilter>,
+    /// Row to open at, taken once the file is loaded and the filter has run
+    pending_jump: Option<RowJump>,
+    /// Filters behind the rows shown, restored when newer ones fail or are cancelled
+    shown_filters: Vec<String>,
+    /// Rows the typed filter matched in the latest live run (None while pending or invalid)
+    pub match_preview: Option<usize>,
+    /// Columns the filtered rows are sorted by, in priority order
+    pub sort: Vec<(String, SortOrder)>,
+    /// Natural ordering and null placement for sorting
+    pub sort_options: SortOptions,
+    /// How cell values are displayed
+    pub cell_format: CellFormat,
+    /// Position of each sorted row in the unsorted filter result, so the file order can
+    /// be restored without filtering again (None while unsorted)
+    sort_positions: Option<IdxCa>,
     /// File path being viewed
     pub file_path: PathBuf,
     /// Error message to display
     pub error_message: Option<String>,
+    /// Note on what the last command did, shown until the next key
+    pub message: Option<String>,
+    /// Shell command typed as `:pipe <cmd>`, which the main loop runs on the view
+    pub pipe_command: Option<String>,
     /// Whether to quit the application
     pub should_quit: bool,
     /// Whether to show line numbers
     pub show_line_numbers: bool,
+    /// Colors the UI is drawn with
+    pub theme: Theme,
+    /// Whether every other row is shaded and the focused row highlighted
+    pub shade_rows: bool,
+    /// Whether long cells wrap onto several lines instead of being cut off
+    pub wrap_cells: bool,
+    /// Whether numeric cells are colored by where they fall between their column's extremes
+    pub heatmap: bool,
+    /// (min, max) of each numeric column of the filtered rows, computed when a filter is
+    /// applied while the heatmap is on (None while it is off)
+    value_ranges: Option<HashMap<String, (f64, f64)>>,
     /// Whether we're in column selection mode
     pub column_selection_mode: bool,
     /// Cursor position in column selection list
     pub column_selection_cursor: usize,
+    /// Fuzzy search narrowing the column selection list (empty = all columns)
+    pub column_search: Prompt,
+    /// Whether we're typing the column search
+    pub column_search_mode: bool,
+    /// Missing and distinct values of the filtered rows' columns, counted in the
+    /// background while the column selection is open
+    column_stats: ColumnStats,
+    /// Column last shown or hidden with Space, where a range toggle starts
+    last_toggled_column: Option<String>,
+    /// First entry shown in the column selection list, kept while it is drawn so the
+    /// list only scrolls when the cursor leaves it
+    pub column_list_offset: Cell<usize>,
     /// Selected columns (None = all columns, Some = only selected ones)
     pub selected_columns: Option<Vec<String>>,
+    /// Display order set in column selection (empty = the source order)
+    pub column_order: Vec<String>,
+    /// Columns kept on the left while scrolling horizontally, in the order they were pinned
+    pub pinned_columns: Vec<String>,
+    /// Width of each visible column on the current page, measured before drawing
+    pub column_widths: Vec<u16>,
+    /// Room the table has for data columns, measured before drawing
+    pub table_width: u16,
+    /// Second pane over the table, if split
+    pub split: Option<Split>,
+    /// Rows picked with Space and V, by position in the rows shown; export, copy and
+    /// pipe work on these instead of all rows when there are any
+    selected_rows: BTreeSet<usize>,
+    /// Row last picked or dropped with Space, where a range toggle starts
+    last_toggled_row: Option<usize>,
+    /// `rows_version` the rows were picked in; they are dropped once the rows change
+    selection_version: u64,
+    /// Totals of the focused column last shown, so they are not summed again every frame
+    totals_cache: RefCell<Option<(TotalsKey, Option<Totals>)>>,
+    /// Whether `Ctrl-w` was pressed and the next key picks a pane command
+    pub pane_prefix: bool,
+    /// Whether the focused cell was just copied with `y`, so a following `c` copies its
+    /// whole column instead
+    pub yank_prefix: bool,
+    /// Whether the schema overview (`i`) is open
+    pub schema_mode: bool,
+    /// Column under the cursor in the schema overview, by position in `all_columns`
+    pub schema_cursor: usize,
+    /// First present value of each column, shown in the schema overview
+    schema_examples: HashMap<String, String>,
+    /// First entry shown in the schema overview, kept while it is drawn so the list only
+    /// scrolls when the cursor leaves it
+    pub schema_list_offset: Cell<usize>,
+    /// Whether we're in table (sheet) selection mode
+    pub table_selection_mode: bool,
+    /// Cursor position in table selection list
+    pub table_selection_cursor: usize,
+    /// Time of the last automatic reload (watch mode), shown in the status bar
+    pub reloaded_at: Option<String>,
+    /// How long loading the data took, for the status bar
+    pub load_time: Option<Duration>,
+    /// Rest of a CSV file still loading while its first rows are viewed
+    loading: Option<BackgroundLoad>,
+    /// When rows still loading were last taken into the filtered rows
+    loading_refreshed: Instant,
+    /// Whether rows were added to the source since then
+    loading_behind: bool,
+    /// How long the latest applied filter took
+    pub filter_time: Option<Duration>,
+    /// Whether we're in encoding selection mode
+    pub encoding_selection_mode: bool,
+    /// Cursor position in encoding selection list
+    pub encoding_selection_cursor: usize,
 }
 
 impl App {
-    /// Create a new App instance
-    pub fn new(file_path: PathBuf) -> Result<Self> {
-        let data_source = DataSource::load(&file_path)?;
+    /// Create an App for an already loaded data source
+    pub fn from_source(file_path: PathBuf, data_source: DataSource) -> Self {
         let filtered_df = data_source.dataframe().clone();
+        // Ask which sheet to view when the source holds more than one
+        let table_selection_mode = data_source.asks_for_table();
 
-        Ok(Self {
+        Self {
             data_source,
             filtered_df,
+            rows_version: 0,
+            page_cache: RefCell::new(Vec::new()),
             scroll_offset: 0,
             column_offset: 0,
             page_size: 20,
+            filters: Vec::new(),
             filter_pattern: String::new(),
             filter_cursor: 0,
             filter_mode: false,
-            filter_column: None,
+            command_mode: false,
+            command: Prompt::default(),
+            sql_query: None,
+            query_df: None,
+            duplicates: None,
+            cell_mode: false,
+            cursor_row: 0,
+            cursor_column: 0,
+            record: None,
+            value_popup: None,
+            column_profile: None,
+            value_counts: None,
+            describe: None,
+            resample: None,
+            help_open: false,
+            popup_scroll: 0,
+            popup_max_scroll: Cell::new(0),
+            completion_index: 0,
+            filter_options: FilterOptions::default(),
+            live_filter: LiveFilter::new(),
+            filter_job: FilterJob::new(),
+            pending_filter: None,
+            pending_jump: None,
+            shown_filters: Vec::new(),
+            match_preview: None,
+            sort: Vec::new(),
+            sort_options: SortOptions::default(),
+            cell_format: CellFormat::default(),
+            sort_positions: None,
             file_path,
             error_message: None,
+            message: None,
+            pipe_command: None,
             should_quit: false,
             show_line_numbers: true,
+            theme: Theme::default(),
+            shade_rows: true,
+            wrap_cells: false,
+            heatmap: false,
+            value_ranges: None,
             column_selection_mode: false,
             column_selection_cursor: 0,
+            column_search: Prompt::default(),
+            column_search_mode: false,
+            column_stats: ColumnStats::new(),
+            last_toggled_column: None,
+            column_list_offset: Cell::new(0),
             selected_columns: None, // None means all columns visible
-        })
+            column_order: Vec::new(),
+            pinned_columns: Vec::new(),
+            column_widths: Vec::new(),
+            table_width: 0,
+            split: None,
+            pane_prefix: false,
+            yank_prefix: false,
+            selected_rows: BTreeSet::new(),
+            last_toggled_row: None,
+            selection_version: 0,
+            totals_cache: RefCell::new(None),
+            schema_mode: false,
+            schema_cursor: 0,
+            schema_examples: HashMap::new(),
+            schema_list_offset: Cell::new(0),
+            table_selection_mode,
+            table_selection_cursor: 0,
+            reloaded_at: None,
+            load_time: None,
+            loading: None,
+            loading_refreshed: Instant::now(),
+            loading_behind: false,
+            filter_time: None,
+            encoding_selection_mode: false,
+            encoding_selection_cursor: 0,
+        }
     }
 
-    /// Get current page of data for display
-    pub fn current_page(&self) -> TableData {
-        let mut table_data = TableData::from_dataframe(&self.filtered_df, self.scroll_offset, self.page_size);
-
-        // Apply column filtering if selected_columns is set
-        if let Some(ref selected_cols) = self.selected_columns {
-            table_data.filter_columns(selected_cols);
+    /// Get current page of data for display, built again only when the rows, the
+    /// position or the columns shown changed
+    pub fn current_page(&self) -> Rc<TableData> {
+        let key = PageKey {
+            scroll_offset: self.scroll_offset,
+            page_size: self.page_size,
+            column_offset: self.column_offset,
+            table_width: self.table_width,
+            rows_version: self.rows_version,
+            columns: self.visible_columns(),
+            format: self.cell_format.clone(),
+        };
+        if let Some((_, page)) = self.page_cache.borrow().iter().find(|(cached, _)| *cached == key) {
+            return Rc::clone(page);
         }
 
-        table_data
+        let page = match self.build_page(&key.columns) {
+            Ok(page) => Rc::new(page),
+            // Failed fetches are not kept, so they are retried on the next draw
+            Err(e) => {
+                return Rc::new(TableData {
+                    headers: vec!["Error".to_string()],
+                    types: vec![String::new()],
+                    rows: vec![vec![Some(format!("Failed to fetch rows: {}", e))]],
+                    formatted: vec![true],
+                    total_rows: 0,
+                })
+            }
+        };
+        let mut cache = self.page_cache.borrow_mut();
+        cache.insert(0, (key, Rc::clone(&page)));
+        cache.truncate(CACHED_PAGES);
+        page
+    }
+
+    /// Build the page of the visible `columns`, formatting only the cells of the ones
+    /// that can be on screen: the pinned ones, and the others from `column_offset` on
+    /// until even their headers alone would overflow the table width
+    fn build_page(&self, columns: &[String]) -> anyhow::Result<TableData> {
+        let (rows, offset, total_rows) = if self.data_source.is_paged() {
+            // Paged sources only ever hand us the rows on screen
+            let page = self.data_source.page(self.scroll_offset, self.page_size)?;
+            (page, 0, self.data_source.len())
+        } else {
+            (self.filtered_df.clone(), self.scroll_offset, self.filtered_df.height())
+        };
+        let rows = rows.select(columns.iter().map(|c| c.as_str()))?;
+
+        let pinned = self.pinned_count();
+        let mut used = 0;
+        let last = (pinned + self.column_offset..columns.len())
+            .take_while(|&i| {
+                let fits = used <= self.table_width;
+                used += crate::text::width(&self.header_label(&columns[i])) as u16 + COLUMN_SPACING;
+                fits
+            })
+            .last()
+            .unwrap_or(0);
+        let on_screen = |i: usize| i < pinned || (pinned + self.column_offset..=last).contains(&i);
+
+        let mut table_data = TableData::from_dataframe(&rows, offset, self.page_size, &self.cell_format, on_screen);
+        table_data.total_rows = total_rows;
+        Ok(table_data)
+    }
+
+    /// Show `rows` rows per page, e.g. after the terminal was resized
+    pub fn set_page_size(&mut self, rows: usize) {
+        self.page_size = rows.max(1);
+        let max_offset = self.total_rows().saturating_sub(self.page_size);
+        self.scroll_offset = self.scroll_offset.min(max_offset);
     }
 
     /// Scroll down by one page
     pub fn scroll_down(&mut self) {
-        let max_offset = self.filtered_df.height().saturating_sub(self.page_size);
+        let max_offset = self.total_rows().saturating_sub(self.page_size);
         self.scroll_offset = (self.scroll_offset + self.page_size).min(max_offset);
     }
 
@@ -90,7 +465,7 @@ impl App {
 
     /// Scroll down by one row
     pub fn scroll_down_one(&mut self) {
-        let max_offset = self.filtered_df.height().saturating_sub(self.page_size);
+        let max_offset = self.total_rows().saturating_sub(self.page_size);
         self.scroll_offset = (self.scroll_offset + 1).min(max_offset);
     }
 
@@ -106,12 +481,12 @@ impl App {
 
     /// Scroll to bottom
     pub fn scroll_to_bottom(&mut self) {
-        self.scroll_offset = self.filtered_df.height().saturating_sub(self.page_size);
+        self.scroll_offset = self.total_rows().saturating_sub(self.page_size);
     }
 
     /// Scroll right
     pub fn scroll_right(&mut self) {
-        let max_columns = self.data_source.columns().len();
+        let max_columns = self.visible_columns().len() - self.pinned_count();
         if self.column_offset < max_columns.saturating_sub(1) {
             self.column_offset += 1;
         }
@@ -122,202 +497,2028 @@ impl App {
         self.column_offset = self.column_offset.saturating_sub(1);
     }
 
-    /// Enter filter mode
-    pub fn enter_filter_mode(&mut self) {
-        self.filter_mode = true;
-        self.filter_cursor = self.filter_pattern.len(); // Move cursor to end
-        self.error_message = None;
+    /// Enter cell navigation mode, focusing the top-left cell on screen
+    pub fn enter_cell_mode(&mut self) {
+        if self.total_rows() == 0 || self.visible_columns().is_empty() {
+            return;
+        }
+        self.cell_mode = true;
+        self.cursor_row = self.scroll_offset;
+        self.cursor_column = self.screen_columns().first().copied().unwrap_or(0);
     }
 
-    /// Exit filter mode
-    pub fn exit_filter_mode(&mut self) {
-        self.filter_mode = false;
+    /// Exit cell navigation mode
+    pub fn exit_cell_mode(&mut self) {
+        self.cell_mode = false;
     }
 
-    /// Add character to filter pattern at cursor position
-    pub fn push_filter_char(&mut self, c: char) {
-        self.filter_pattern.insert(self.filter_cursor, c);
-        self.filter_cursor += 1;
+    /// Focus a clicked cell, entering cell mode. Clicks on the row number keep the
+    /// focused column, or focus the first one on screen.
+    pub fn click_cell(&mut self, row: usize, column: Option<usize>) {
+        if !self.cell_mode {
+            self.enter_cell_mode();
+            if !self.cell_mode {
+                return;
+            }
+        }
+        self.focus_cell(row, column.unwrap_or(self.cursor_column));
     }
 
-    /// Remove character before cursor (Backspace)
-    pub fn pop_filter_char(&mut self) {
-        if self.filter_cursor > 0 {
-            self.filter_cursor -= 1;
-            self.filter_pattern.remove(self.filter_cursor);
+    /// Focus the given cell (clamped to the data), scrolling to keep it on screen
+    fn focus_cell(&mut self, row: usize, column: usize) {
+        self.cursor_row = row.min(self.total_rows().saturating_sub(1));
+        self.cursor_column = column.min(self.visible_columns().len().saturating_sub(1));
+        if self.cursor_row < self.scroll_offset {
+            self.scroll_offset = self.cursor_row;
+        } else if self.cursor_row >= self.scroll_offset + self.page_size {
+            self.scroll_offset = self.cursor_row + 1 - self.page_size;
+        }
+        // Pinned columns are always on screen, the others scroll
+        let pinned = self.pinned_count();
+        if let Some(scrolled) = self.cursor_column.checked_sub(pinned) {
+            if scrolled < self.column_offset {
+                self.column_offset = scrolled;
+            }
+            // The first scrolled column is always on screen, so this ends
+            while !self.screen_columns().contains(&self.cursor_column) {
+                self.column_offset += 1;
+            }
         }
     }
 
-    /// Remove character at cursor (Delete key)
-    pub fn delete_filter_char(&mut self) {
-        if self.filter_cursor < self.filter_pattern.len() {
-            self.filter_pattern.remove(self.filter_cursor);
+    /// Move the focused cell down
+    pub fn cursor_down(&mut self) {
+        self.focus_cell(self.cursor_row + 1, self.cursor_column);
+    }
+
+    /// Move the focused cell up
+    pub fn cursor_up(&mut self) {
+        self.focus_cell(self.cursor_row.saturating_sub(1), self.cursor_column);
+    }
+
+    /// Move the focused cell right
+    pub fn cursor_right(&mut self) {
+        self.focus_cell(self.cursor_row, self.cursor_column + 1);
+    }
+
+    /// Move the focused cell left
+    pub fn cursor_left(&mut self) {
+        self.focus_cell(self.cursor_row, self.cursor_column.saturating_sub(1));
+    }
+
+    /// Move the focused cell a page down, keeping its place on screen
+    pub fn cursor_page_down(&mut self) {
+        self.scroll_down();
+        self.focus_cell(self.cursor_row + self.page_size, self.cursor_column);
+    }
+
+    /// Move the focused cell a page up, keeping its place on screen
+    pub fn cursor_page_up(&mut self) {
+        self.scroll_up();
+        self.focus_cell(self.cursor_row.saturating_sub(self.page_size), self.cursor_column);
+    }
+
+    /// Focus the first row, staying in the same column
+    pub fn cursor_first_row(&mut self) {
+        self.focus_cell(0, self.cursor_column);
+    }
+
+    /// Focus the last row, staying in the same column
+    pub fn cursor_last_row(&mut self) {
+        self.focus_cell(self.total_rows().saturating_sub(1), self.cursor_column);
+    }
+
+    /// Focus the first column, staying in the same row
+    pub fn cursor_first_column(&mut self) {
+        self.focus_cell(self.cursor_row, 0);
+    }
+
+    /// Focus the last column, staying in the same row
+    pub fn cursor_last_column(&mut self) {
+        self.focus_cell(self.cursor_row, usize::MAX);
+    }
+
+    /// Keep the focused cell on screen after paging, scrolling or filtering
+    pub fn clamp_cursor(&mut self) {
+        if !self.cell_mode {
+            return;
+        }
+        let rows = self.total_rows();
+        let columns = self.visible_columns().len();
+        if rows == 0 || columns == 0 {
+            self.cell_mode = false;
+            return;
+        }
+
+        let last_row = (self.scroll_offset + self.page_size).min(rows) - 1;
+        self.cursor_row = self.cursor_row.clamp(self.scroll_offset.min(last_row), last_row);
+        let pinned = self.pinned_count();
+        if self.cursor_column >= pinned {
+            let screen = self.screen_columns();
+            let last_column = screen.last().copied().unwrap_or(0);
+            let first_column = screen.get(pinned).copied().unwrap_or(last_column);
+            self.cursor_column = self.cursor_column.clamp(first_column, last_column);
         }
     }
 
-    /// Move cursor left in filter input
-    pub fn filter_cursor_left(&mut self) {
-        if self.filter_cursor > 0 {
-            self.filter_cursor -= 1;
+    /// Columns of the data being viewed: the SQL query result, or the source
+    fn view_columns(&self) -> Vec<String> {
+        match &self.query_df {
+            Some(df) => df.get_column_names().iter().map(|c| c.to_string()).collect(),
+            None => self.data_source.columns(),
         }
     }
 
-    /// Move cursor right in filter input
-    pub fn filter_cursor_right(&mut self) {
-        if self.filter_cursor < self.filter_pattern.len() {
-            self.filter_cursor += 1;
+    /// Columns in display order (the selected ones, or all), pinned ones first
+    pub fn visible_columns(&self) -> Vec<String> {
+        let shown: Vec<String> = self
+            .all_columns()
+            .into_iter()
+            .filter(|c| self.is_column_selected(c))
+            .collect();
+        let mut ordered: Vec<String> = self
+            .pinned_columns
+            .iter()
+            .filter(|c| shown.contains(c))
+            .cloned()
+            .collect();
+        ordered.extend(shown.into_iter().filter(|c| !self.pinned_columns.contains(c)));
+        ordered
+    }
+
+    /// Number of pinned columns among the visible ones
+    pub fn pinned_count(&self) -> usize {
+        self.visible_columns()
+            .iter()
+            .take_while(|c| self.pinned_columns.contains(c))
+            .count()
+    }
+
+    /// Positions in `visible_columns` of the columns on screen: the pinned ones, then as
+    /// many of the others from `column_offset` on as fit the table width
+    pub fn screen_columns(&self) -> Vec<usize> {
+        let columns = self.visible_columns().len();
+        let pinned = self.pinned_count();
+        let width = |i: usize| self.column_widths.get(i).copied().unwrap_or(0) + COLUMN_SPACING;
+
+        let mut screen: Vec<usize> = (0..pinned).collect();
+        let mut used: u16 = screen.iter().map(|&i| width(i)).sum();
+        for i in pinned + self.column_offset..columns {
+            // The first scrolled column is shown even when it does not fit
+            if used + width(i) > self.table_width && screen.len() > pinned {
+                break;
+            }
+            used += width(i);
+            screen.push(i);
         }
+        screen
     }
 
-    /// Move cursor to start of filter input
-    pub fn filter_cursor_home(&mut self) {
-        self.filter_cursor = 0;
+    /// Whether the focused cell is in a scrolled column right of the screen
+    pub fn cursor_right_of_screen(&self) -> bool {
+        self.cell_mode
+            && self.cursor_column >= self.pinned_count() + self.column_offset
+            && self.screen_columns().last().is_some_and(|&last| self.cursor_column > last)
     }
 
-    /// Move cursor to end of filter input
-    pub fn filter_cursor_end(&mut self) {
-        self.filter_cursor = self.filter_pattern.len();
+    /// Column name as shown in the header, with its sort marker
+    pub fn header_label(&self, column: &str) -> String {
+        match self.sort_indicator(column) {
+            Some(indicator) => format!("{} {}", column, indicator),
+            None => column.to_string(),
+        }
     }
 
-    /// Clear filter pattern
-    pub fn clear_filter(&mut self) {
-        self.filter_pattern.clear();
-        self.filter_cursor = 0;
-        self.apply_filter();
+    /// Pin the column, or unpin it if it already is
+    pub fn toggle_pinned_column(&mut self, column: &str) {
+        if let Some(i) = self.pinned_columns.iter().position(|c| c == column) {
+            self.pinned_columns.remove(i);
+        } else {
+            self.pinned_columns.push(column.to_string());
+        }
+        self.column_offset = 0;
     }
 
-    /// Apply the current filter
-    pub fn apply_filter(&mut self) {
-        // Use new advanced filter expression parser
-        match self.data_source.filter(&self.filter_pattern) {
-            Ok(df) => {
-                self.filtered_df = df;
-                self.scroll_offset = 0; // Reset scroll when filter changes
-                self.error_message = None;
-            }
-            Err(e) => {
-                self.error_message = Some(format!("Filter error: {}", e));
+    /// Pin or unpin the focused column, keeping it focused
+    pub fn toggle_pin_at_cursor(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        self.toggle_pinned_column(&column);
+        let position = self.visible_columns().iter().position(|c| *c == column).unwrap_or(0);
+        self.focus_cell(self.cursor_row, position);
+    }
+
+    /// Hide the focused column, as if deselected in column selection, and focus the
+    /// column that takes its place. The last visible column stays.
+    pub fn hide_column_at_cursor(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        if self.visible_columns().len() <= 1 {
+            return;
+        }
+        let all_columns = self.all_columns();
+        let selected = self.selected_columns.get_or_insert(all_columns);
+        selected.retain(|c| *c != column);
+        self.focus_cell(self.cursor_row, self.cursor_column);
+    }
+
+    /// Pin or unpin the column at the cursor of the column selection
+    pub fn toggle_pin_at_column_cursor(&mut self) {
+        if let Some(column) = self.listed_columns().get(self.column_selection_cursor).cloned() {
+            self.toggle_pinned_column(&column);
+        }
+    }
+
+    /// Name of the focused column in cell mode
+    pub fn cursor_column_name(&self) -> Option<String> {
+        self.visible_columns().get(self.cursor_column).cloned()
+    }
+
+    /// Value of the focused cell
+    fn cursor_value(&self, column: &str) -> anyhow::Result<AnyValue<'static>> {
+        let value = if self.data_source.is_paged() {
+            let page = self.data_source.page(self.cursor_row, 1)?;
+            page.column(column)?.get(0)?.into_static()
+        } else {
+            self.filtered_df.column(column)?.get(self.cursor_row)?.into_static()
+        };
+        Ok(value)
+    }
+
+    /// Copy the focused cell's full value to the clipboard
+    pub fn copy_cell(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        let copied = self.cursor_value(&column).map(|value| self.cell_format.full_text(&value));
+        self.copy(copied, "cell".to_string());
+    }
+
+    /// Copy the focused row, or the picked rows when there are any, to the clipboard as
+    /// tab-separated values: a line with the visible column names, then one per row
+    pub fn copy_row(&mut self) {
+        let columns = self.visible_columns();
+        let picked = self.selected_rows().len();
+        let rows = if picked > 0 {
+            self.view_frame(false)
+        } else if self.data_source.is_paged() {
+            self.data_source.page(self.cursor_row, 1)
+        } else {
+            Ok(self.filtered_df.slice(self.cursor_row as i64, 1))
+        };
+        let copied = rows.and_then(|rows| {
+            let mut lines = vec![columns.iter().map(|column| tsv_field(column)).collect::<Vec<_>>().join("\t")];
+            for i in 0..rows.height() {
+                let values = columns
+                    .iter()
+                    .map(|column| Ok(tsv_field(&self.cell_format.full_text(&rows.column(column)?.get(i)?))))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                lines.push(values.join("\t"));
             }
+            Ok(lines.join("\n"))
+        });
+        let what = if picked > 0 { format!("{} picked rows", picked) } else { format!("row {}", self.cursor_row + 1) };
+        self.copy(copied, what);
+    }
+
+    /// Copy every filtered value of the focused column, or those of the picked rows, to
+    /// the clipboard, one per line
+    pub fn copy_column(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        let mut rows = 0;
+        let copied = self.view_frame(false).and_then(|df| {
+            rows = df.height();
+            let values = df.column(&column)?.as_materialized_series().clone();
+            Ok(values
+                .iter()
+                .map(|value| tsv_field(&self.cell_format.full_text(&value)))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        });
+        self.copy(copied, format!("{} values of {}", rows, column));
+    }
+
+    /// Rows picked with Space and V, ascending; none once the rows shown changed
+    pub fn selected_rows(&self) -> Vec<usize> {
+        if self.selection_version != self.rows_version {
+            return Vec::new();
         }
+        self.selected_rows.iter().copied().collect()
     }
 
-    /// Parse filter pattern to extract column name and value (deprecated, kept for reference)
-    /// The new filter system handles this automatically
-    #[allow(dead_code)]
-    fn parse_filter_pattern(&self, pattern: &str) -> (Option<String>, String) {
-        // Try colon separator first
-        if let Some(colon_pos) = pattern.find(':') {
-            let col = pattern[..colon_pos].trim().to_string();
-            let val = pattern[colon_pos + 1..].trim().to_string();
+    /// Whether the row at `row` of the rows shown is picked
+    pub fn is_row_selected(&self, row: usize) -> bool {
+        self.selection_version == self.rows_version && self.selected_rows.contains(&row)
+    }
 
-            // Verify column exists
-            if self.data_source.columns().contains(&col) {
-                return (Some(col), val);
+    /// Sum, mean and count of the focused column over the picked rows, or all filtered
+    /// rows when none are, for the status bar; `None` unless the column is numeric, and
+    /// for paged sources without picked rows, whose rows are not all at hand
+    pub fn cursor_column_totals(&self) -> Option<Totals> {
+        let column = self.cursor_column_name()?;
+        let key = TotalsKey {
+            rows_version: self.rows_version,
+            column,
+            rows: self.selected_rows(),
+            format: self.cell_format.clone(),
+        };
+        if let Some((cached, totals)) = &*self.totals_cache.borrow() {
+            if *cached == key {
+                return totals.clone();
             }
         }
 
-        // Try equals separator
-        if let Some(eq_pos) = pattern.find('=') {
-            let col = pattern[..eq_pos].trim().to_string();
-            let val = pattern[eq_pos + 1..].trim().to_string();
+        let values = if !key.rows.is_empty() {
+            self.view_frame(false).ok().and_then(|rows| rows.column(&key.column).ok().cloned())
+        } else if self.data_source.is_paged() {
+            None
+        } else {
+            self.filtered_df.column(&key.column).ok().cloned()
+        };
+        let totals = values
+            .and_then(|values| crate::column_stats::totals(values.as_materialized_series(), &self.cell_format).ok())
+            .flatten();
+        *self.totals_cache.borrow_mut() = Some((key, totals.clone()));
+        totals
+    }
 
-            // Verify column exists
-            if self.data_source.columns().contains(&col) {
-                return (Some(col), val);
+    /// Pick or drop the focused row (Space in cell mode)
+    pub fn toggle_row_at_cursor(&mut self) {
+        self.forget_stale_selection();
+        let row = self.cursor_row;
+        if !self.selected_rows.remove(&row) {
+            self.selected_rows.insert(row);
+        }
+        self.last_toggled_row = Some(row);
+    }
+
+    /// Pick or drop every row from the one last toggled to the focused one, as the last
+    /// toggled one now is; without one, toggle the focused row
+    pub fn toggle_row_range_at_cursor(&mut self) {
+        self.forget_stale_selection();
+        let Some(start) = self.last_toggled_row else {
+            self.toggle_row_at_cursor();
+            return;
+        };
+        let pick = self.selected_rows.contains(&start);
+        for row in start.min(self.cursor_row)..=start.max(self.cursor_row) {
+            if pick {
+                self.selected_rows.insert(row);
+            } else {
+                self.selected_rows.remove(&row);
             }
         }
+        self.last_toggled_row = Some(self.cursor_row);
+    }
 
-        // No column specified or column not found, search all columns
-        (None, pattern.to_string())
+    /// Drop the picked rows, returning whether there were any
+    pub fn clear_row_selection(&mut self) -> bool {
+        let had_rows = !self.selected_rows().is_empty();
+        self.selected_rows.clear();
+        self.last_toggled_row = None;
+        had_rows
     }
 
-    /// Get total number of rows (after filtering)
-    pub fn total_rows(&self) -> usize {
-        self.filtered_df.height()
+    /// Drop rows picked among rows that are no longer shown
+    fn forget_stale_selection(&mut self) {
+        if self.selection_version != self.rows_version {
+            self.selected_rows.clear();
+            self.last_toggled_row = None;
+            self.selection_version = self.rows_version;
+        }
     }
 
-    /// Get total number of rows (before filtering)
-    pub fn original_total_rows(&self) -> usize {
-        self.data_source.len()
+    /// Copy the rows on screen, or with `page_only` unset all filtered rows, with the
+    /// visible columns to the clipboard as a Markdown table; the picked rows when there
+    /// are any
+    pub fn copy_markdown(&mut self, page_only: bool) {
+        let table = self.view_frame(page_only);
+        let what = match &table {
+            Ok(df) => format!("{} rows as a Markdown table", df.height()),
+            Err(_) => String::new(),
+        };
+        self.copy(table.map(|df| crate::export::markdown(&df)), what);
     }
 
-    /// Quit the application
-    pub fn quit(&mut self) {
-        self.should_quit = true;
+    /// Put copied text on the clipboard, noting `what` was copied or why it was not
+    fn copy(&mut self, text: anyhow::Result<String>, what: String) {
+        match text.and_then(|text| crate::clipboard::copy(&text)) {
+            Ok(Copied::System) => self.message = Some(format!("Copied {}", what)),
+            Ok(Copied::Terminal) => self.message = Some(format!("Copied {} through the terminal", what)),
+            Err(e) => self.error_message = Some(format!("Failed to copy: {:#}", e)),
+        }
     }
 
-    /// Toggle line numbers display
-    pub fn toggle_line_numbers(&mut self) {
-        self.show_line_numbers = !self.show_line_numbers;
+    /// Show the focused row transposed, one line per column with its full value
+    pub fn open_record_view(&mut self) {
+        let row = if self.data_source.is_paged() {
+            self.data_source.page(self.cursor_row, 1)
+        } else {
+            Ok(self.filtered_df.slice(self.cursor_row as i64, 1))
+        };
+        let record = row.and_then(|row| {
+            self.visible_columns()
+                .into_iter()
+                .map(|column| {
+                    let value = self.cell_format.full_text(&row.column(&column)?.get(0)?);
+                    Ok((column, value))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        });
+        match record {
+            Ok(record) => {
+                self.record = Some(record);
+                self.popup_scroll = 0;
+            }
+            Err(e) => self.error_message = Some(format!("Failed to read row: {}", e)),
+        }
     }
 
-    /// Enter column selection mode
-    pub fn enter_column_selection_mode(&mut self) {
-        self.column_selection_mode = true;
-        self.column_selection_cursor = 0;
+    /// Show the complete value of the focused cell, which the table may truncate
+    pub fn open_value_popup(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        match self.cursor_value(&column) {
+            Ok(value) => {
+                self.value_popup = Some((column, self.cell_format.full_text(&value)));
+                self.popup_scroll = 0;
+            }
+            Err(e) => self.error_message = Some(format!("Failed to read cell: {}", e)),
+        }
+    }
 
-        // Initialize selected_columns if not set (start with all columns selected)
-        if self.selected_columns.is_none() {
-            self.selected_columns = Some(self.data_source.columns());
+    /// Show statistics of the focused column's filtered values: count, nulls, range and
+    /// quantiles of numbers, or distinct and most frequent values
+    pub fn open_column_profile(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        let profile = self.summary_rows().and_then(|(rows, total)| {
+            let mut lines = crate::column_stats::profile(&rows, &column, &self.cell_format)?;
+            if let Some(total) = total {
+                let sample = format!("first {} of {} rows", crate::ui::format_count(rows.height()), crate::ui::format_count(total));
+                lines.insert(0, ("sample".to_string(), sample));
+            }
+            Ok(lines)
+        });
+        match profile {
+            Ok(lines) => {
+                self.column_profile = Some((column, lines));
+                self.popup_scroll = 0;
+            }
+            Err(e) => self.error_message = Some(format!("Failed to compute statistics: {:#}", e)),
         }
     }
 
-    /// Exit column selection mode
-    pub fn exit_column_selection_mode(&mut self) {
-        self.column_selection_mode = false;
+    /// The filtered rows to summarize: for paged sources only the first `SUMMARY_ROWS`,
+    /// with the number of rows they are the first of when that is more
+    fn summary_rows(&self) -> anyhow::Result<(DataFrame, Option<usize>)> {
+        if !self.data_source.is_paged() {
+            return Ok((self.filtered_df.clone(), None));
+        }
+        let total = self.data_source.len();
+        let rows = self.data_source.page(0, total.min(SUMMARY_ROWS))?;
+        Ok((rows, (total > SUMMARY_ROWS).then_some(total)))
     }
 
-    /// Move cursor up in column selection
-    pub fn column_selection_up(&mut self) {
-        if self.column_selection_cursor > 0 {
-            self.column_selection_cursor -= 1;
+    /// Open the frequency screen: the distinct values of the focused column among the
+    /// filtered rows, most frequent first
+    pub fn open_value_counts(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        let counts = if self.data_source.is_paged() {
+            ValueCounts::of_paged(&self.data_source, &column)
+        } else {
+            ValueCounts::of(&self.filtered_df, &column)
+        };
+        match counts {
+            Ok(counts) => self.value_counts = Some(counts),
+            Err(e) => self.error_message = Some(format!("Failed to count values: {:#}", e)),
         }
     }
 
-    /// Move cursor down in column selection
-    pub fn column_selection_down(&mut self) {
-        let max_cursor = self.data_source.columns().len().saturating_sub(1);
-        if self.column_selection_cursor < max_cursor {
-            self.column_selection_cursor += 1;
+    /// Open the describe screen: count, mean, spread and quantiles of every numeric
+    /// column over the filtered rows
+    pub fn open_describe(&mut self) {
+        let describe = self.summary_rows().and_then(|(rows, total)| {
+            let mut describe = Describe::of(&rows, &self.all_columns(), &self.cell_format)?;
+            describe.sampled_from = total;
+            Ok(describe)
+        });
+        match describe {
+            Ok(describe) => self.describe = Some(describe),
+            Err(e) => self.error_message = Some(format!("Failed to describe columns: {:#}", e)),
         }
     }
 
-    /// Toggle column visibility at cursor position
-    pub fn toggle_column_at_cursor(&mut self) {
-        let all_columns = self.data_source.columns();
-        if self.column_selection_cursor >= all_columns.len() {
+    /// Open the resample screen: the filtered rows in time buckets of the focused date or
+    /// datetime column, counted, with the numeric columns aggregated
+    pub fn open_resample(&mut self) {
+        let Some(column) = self.cursor_column_name() else {
             return;
+        };
+        let resample = self.summary_rows().and_then(|(rows, total)| {
+            let mut resample = Resample::of(&rows, &column, &self.visible_columns())?;
+            resample.sampled_from = total;
+            Ok(resample)
+        });
+        match resample {
+            Ok(resample) => self.resample = Some(resample),
+            Err(e) => self.error_message = Some(format!("Failed to resample: {:#}", e)),
         }
+    }
 
-        let column_name = &all_columns[self.column_selection_cursor];
+    /// Close the frequency screen, keeping or with `exclude` dropping the rows with the
+    /// value under its cursor
+    pub fn filter_by_value_count(&mut self, exclude: bool) {
+        let Some(counts) = self.value_counts.take() else {
+            return;
+        };
+        match counts.get(counts.cursor) {
+            Ok((value, _)) => self.filter_by_value(&counts.column, value, exclude),
+            Err(e) => self.error_message = Some(format!("Failed to read value: {}", e)),
+        }
+    }
 
-        if let Some(ref mut selected) = self.selected_columns {
-            if let Some(pos) = selected.iter().position(|c| c == column_name) {
-                // Column is selected, remove it
-                selected.remove(pos);
-            } else {
-                // Column is not selected, add it
-                selected.push(column_name.clone());
+    /// Show the list of keys and filter syntax
+    pub fn open_help(&mut self) {
+        self.help_open = true;
+        self.popup_scroll = 0;
+    }
+
+    /// Whether the record view, the value popup, the column statistics or the help is open
+    pub fn popup_open(&self) -> bool {
+        self.record.is_some() || self.value_popup.is_some() || self.column_profile.is_some() || self.help_open
+    }
+
+    /// Close the open popup
+    pub fn close_popup(&mut self) {
+        self.record = None;
+        self.value_popup = None;
+        self.column_profile = None;
+        self.help_open = false;
+    }
+
+    /// Scroll the open popup down by `lines`, stopping once its end is shown
+    pub fn popup_scroll_down(&mut self, lines: usize) {
+        self.popup_scroll = self.popup_scroll.saturating_add(lines).min(self.popup_max_scroll.get());
+    }
+
+    /// Scroll the open popup up by `lines`
+    pub fn popup_scroll_up(&mut self, lines: usize) {
+        self.popup_scroll = self.popup_scroll.saturating_sub(lines);
+    }
+
+    /// Narrow the filter to rows whose focused column equals the focused cell's value,
+    /// or with `exclude`, to rows where it differs ("filter by example")
+    pub fn filter_by_cell(&mut self, exclude: bool) {
+        let Some(column) = self.cursor_column_name() else {
+            return;
+        };
+        match self.cursor_value(&column) {
+            Ok(value) => self.filter_by_value(&column, value, exclude),
+            Err(e) => self.error_message = Some(format!("Failed to read cell: {}", e)),
+        }
+    }
+
+    /// Add a condition keeping, or with `exclude` dropping, the rows where `column` is `value`
+    fn filter_by_value(&mut self, column: &str, value: AnyValue, exclude: bool) {
+        let condition = match value {
+            AnyValue::Null if exclude => format!("{} IS NOT NULL", column),
+            AnyValue::Null => format!("{} IS NULL", column),
+            value => {
+                let op = if exclude { "!=" } else { "=" };
+                format!("{} {} {}", column, op, quote_value(&value.str_value()))
             }
+        };
+
+        // A rejected condition is dropped again, or every later filter would fail with it
+        self.filters.push(condition);
+        if !self.apply_filter() {
+            self.filters.pop();
         }
     }
 
-    /// Reset column selection to show all columns
-    pub fn reset_column_selection(&mut self) {
-        self.selected_columns = None;
-        self.column_offset = 0;
+    /// Enter filter mode
+    pub fn enter_filter_mode(&mut self) {
+        self.filter_mode = true;
+        self.filter_cursor = self.filter_pattern.len(); // Move cursor to end
+        self.error_message = None;
+        self.match_preview = None;
     }
 
-    /// Check if a column is currently selected/visible
-    pub fn is_column_selected(&self, column_name: &str) -> bool {
-        match &self.selected_columns {
-            None => true, // All columns visible
-            Some(selected) => selected.contains(&column_name.to_string()),
+    /// Exit filter mode, dropping the filter being typed
+    pub fn exit_filter_mode(&mut self) {
+        self.filter_mode = false;
+        if !self.filter_pattern.is_empty() {
+            self.filter_pattern.clear();
+            self.filter_cursor = 0;
+            // The live filter may have shown the draft already
+            self.apply_filter();
         }
     }
 
-    /// Get all available columns
-    pub fn all_columns(&self) -> Vec<String> {
-        self.data_source.columns()
+    /// Apply the typed filter on top of the existing ones and leave filter mode.
+    /// An invalid filter stays in the input so it can be fixed.
+    pub fn push_filter(&mut self) {
+        if self.filter_pattern.trim().is_empty() {
+            self.exit_filter_mode();
+            return;
+        }
+        if self.apply_filter() {
+            self.filters.push(self.filter_pattern.trim().to_string());
+            self.filter_pattern.clear();
+            self.filter_cursor = 0;
+            self.filter_mode = false;
+        }
+    }
+
+    /// Open with the view given on the command line: `filter` applied as if typed at the
+    /// `/` prompt, left there to fix if it is invalid, only `columns` shown, in their
+    /// order, and scrolled to `jump` once the rows are there
+    pub fn open_view(&mut self, filter: Option<&str>, columns: Option<&[String]>, jump: Option<RowJump>) {
+        self.pending_jump = jump;
+        if let Some(columns) = columns {
+            let known = self.all_columns();
+            match columns.iter().find(|column| !known.contains(column)) {
+                Some(missing) => {
+                    self.error_message = Some(format!("Unknown column '{}', expected one of {}", missing, known.join(", ")));
+                    return;
+                }
+                None => {
+                    self.selected_columns = Some(columns.to_vec());
+                    self.column_order = columns.to_vec();
+                }
+            }
+        }
+        if let Some(filter) = filter {
+            self.filter_pattern = filter.to_string();
+            self.filter_cursor = self.filter_pattern.len();
+            self.push_filter();
+        }
     }
+
+    /// Remove the most recently applied filter (`u`)
+    pub fn pop_filter(&mut self) {
+        if self.filters.pop().is_some() {
+            self.apply_filter();
+        }
+    }
+
+    /// The applied filters and the one being typed, combined into one expression
+    pub fn filter_expression(&self) -> String {
+        let draft = self.filter_pattern.trim();
+        let mut parts: Vec<&str> = self.filters.iter().map(String::as_str).collect();
+        if !draft.is_empty() {
+            parts.push(draft);
+        }
+        match parts.as_slice() {
+            [single] => single.to_string(),
+            // Group each filter so an OR in one cannot reach into the others
+            _ => parts.iter().map(|p| format!("({})", p)).collect::<Vec<_>>().join(" AND "),
+        }
+    }
+
+    /// Add character to filter pattern at cursor position
+    pub fn push_filter_char(&mut self, c: char) {
+        self.filter_pattern.insert(self.filter_cursor, c);
+        self.filter_cursor += 1;
+        self.filter_edited();
+    }
+
+    /// Remove character before cursor (Backspace)
+    pub fn pop_filter_char(&mut self) {
+        if self.filter_cursor > 0 {
+            self.filter_cursor -= 1;
+            self.filter_pattern.remove(self.filter_cursor);
+            self.filter_edited();
+        }
+    }
+
+    /// Remove character at cursor (Delete key)
+    pub fn delete_filter_char(&mut self) {
+        if self.filter_cursor < self.filter_pattern.len() {
+            self.filter_pattern.remove(self.filter_cursor);
+            self.filter_edited();
+        }
+    }
+
+    /// Move cursor left in filter input
+    pub fn filter_cursor_left(&mut self) {
+        if self.filter_cursor > 0 {
+            self.filter_cursor -= 1;
+        }
+    }
+
+    /// Move cursor right in filter input
+    pub fn filter_cursor_right(&mut self) {
+        if self.filter_cursor < self.filter_pattern.len() {
+            self.filter_cursor += 1;
+        }
+    }
+
+    /// Move cursor to start of filter input
+    pub fn filter_cursor_home(&mut self) {
+        self.filter_cursor = 0;
+    }
+
+    /// Move cursor to end of filter input
+    pub fn filter_cursor_end(&mut self) {
+        self.filter_cursor = self.filter_pattern.len();
+    }
+
+    /// Clear all filters
+    pub fn clear_filter(&mut self) {
+        self.filters.clear();
+        self.filter_pattern.clear();
+        self.filter_cursor = 0;
+        self.apply_filter();
+    }
+
+    /// Drop the picked rows, then show all rows again after `:dupes`, then clear the
+    /// filters, or the SQL query once no filter is left
+    pub fn clear_view(&mut self) {
+        if self.clear_row_selection() || self.clear_duplicates() {
+            return;
+        }
+        if self.filters.is_empty() && self.sql_query.is_some() {
+            self.clear_query();
+        } else {
+            self.clear_filter();
+        }
+    }
+
+    /// Enter the `:` command prompt
+    pub fn enter_command_mode(&mut self) {
+        self.command_mode = true;
+        self.command.clear();
+        self.error_message = None;
+    }
+
+    /// Leave the `:` command prompt without running anything
+    pub fn exit_command_mode(&mut self) {
+        self.command_mode = false;
+    }
+
+    /// Open the `:` prompt with an export of the view to a file named after the source
+    pub fn start_export(&mut self) {
+        self.enter_command_mode();
+        let stem = self.file_path.file_stem().map_or("export".into(), |stem| stem.to_string_lossy());
+        for c in format!("export {}-filtered.csv", stem).chars() {
+            self.command.insert(c);
+        }
+    }
+
+    /// Run the command typed at the `:` prompt: `export <file>` writes the view to a
+    /// file, `copy` (or `copy all`) copies the page (or all rows) as a Markdown table,
+    /// `pipe <cmd>` sends the view to a shell command as CSV, `dupes [columns]` shows
+    /// only duplicate rows, and anything else is a SQL query against the loaded data
+    /// (see `DataSource::query`)
+    pub fn run_command(&mut self) {
+        self.command_mode = false;
+        let command = self.command.text.trim().to_string();
+        if command == "export" {
+            self.error_message = Some("export needs a file name, e.g. export out.csv".to_string());
+        } else if let Some(path) = command.strip_prefix("export ") {
+            self.export(path.trim());
+        } else if command == "pipe" {
+            self.error_message = Some("pipe needs a command, e.g. pipe xsv stats".to_string());
+        } else if let Some(shell_command) = command.strip_prefix("pipe ") {
+            self.pipe_command = Some(shell_command.trim().to_string());
+        } else if command == "dupes" {
+            self.show_duplicates(Vec::new());
+        } else if let Some(columns) = command.strip_prefix("dupes ") {
+            self.show_duplicates(columns.split(',').map(|column| column.trim().to_string()).collect());
+        } else if command == "copy" || command == "copy all" {
+            self.copy_markdown(command == "copy");
+        } else if !command.is_empty() {
+            self.run_query(&command);
+        }
+    }
+
+    /// Show only the filtered rows that have the same values in `columns`, or in every
+    /// column when it is empty, as another filtered row
+    pub fn show_duplicates(&mut self, columns: Vec<String>) {
+        if self.data_source.is_paged() {
+            self.error_message = Some("Duplicates are found in loaded files only".to_string());
+            return;
+        }
+        let known = self.all_columns();
+        if let Some(missing) = columns.iter().find(|column| !known.contains(column)) {
+            self.error_message = Some(format!("Unknown column '{}', expected one of {}", missing, known.join(", ")));
+            return;
+        }
+        self.duplicates = Some(columns);
+        self.apply_filter();
+    }
+
+    /// Show every filtered row again after `:dupes`, returning whether only duplicates
+    /// were shown
+    pub fn clear_duplicates(&mut self) -> bool {
+        if self.duplicates.take().is_none() {
+            return false;
+        }
+        self.apply_filter();
+        true
+    }
+
+    /// Write the rows shown, in their sort order, and the visible columns, in their
+    /// display order, to `path`
+    pub fn export(&mut self, path: &str) {
+        let path_ref = std::path::Path::new(path);
+        // Paged sources are written a chunk at a time, as they may not fit in memory
+        let written = if self.data_source.is_paged() && self.selected_rows().is_empty() {
+            let columns = self.visible_columns();
+            let rows = self.total_rows();
+            crate::export::write_chunked(path_ref, rows, |offset, limit| {
+                Ok(self.data_source.page(offset, limit)?.select(&columns)?)
+            })
+            .map(|()| rows)
+        } else {
+            self.view_frame(false).and_then(|mut df| {
+                crate::export::write(&mut df, path_ref)?;
+                Ok(df.height())
+            })
+        };
+        match written {
+            Ok(rows) => self.message = Some(format!("Wrote {} rows to {}", rows, path)),
+            Err(e) => self.error_message = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Rows shown and the visible columns as CSV, with the number of rows, for `:pipe`
+    pub fn pipe_input(&self) -> anyhow::Result<(Vec<u8>, usize)> {
+        let mut df = self.view_frame(false)?;
+        Ok((crate::export::csv(&mut df)?, df.height()))
+    }
+
+    /// The filtered rows with the visible columns: the picked rows when there are any,
+    /// otherwise all of them, or only those of the page on screen with `page_only`.
+    /// Paged sources hand over each picked row on its own, and at most
+    /// `PAGED_VIEW_ROWS` rows in all.
+    fn view_frame(&self, page_only: bool) -> anyhow::Result<DataFrame> {
+        let selected = self.selected_rows();
+        if !selected.is_empty() && self.data_source.is_paged() {
+            let columns = self.visible_columns();
+            let mut rows: Option<DataFrame> = None;
+            for &row in &selected {
+                let picked = self.data_source.page(row, 1)?.select(&columns)?;
+                match rows.as_mut() {
+                    Some(rows) => {
+                        rows.vstack_mut(&picked)?;
+                    }
+                    None => rows = Some(picked),
+                }
+            }
+            return Ok(rows.unwrap_or_default());
+        }
+        if let (Some(&first), Some(&last)) = (selected.first(), selected.last()) {
+            let span = self.filtered_df.slice(first as i64, last + 1 - first);
+            let positions: Vec<IdxSize> = selected.iter().map(|&row| (row - first) as IdxSize).collect();
+            let rows = span.take(&IdxCa::from_vec("".into(), positions))?;
+            return Ok(rows.select(self.visible_columns())?);
+        }
+
+        let (offset, limit) = if page_only {
+            (self.scroll_offset, self.page_size)
+        } else {
+            (0, self.total_rows())
+        };
+        if self.data_source.is_paged() && limit > PAGED_VIEW_ROWS {
+            anyhow::bail!(
+                "{} rows are too many to hold at once; filter to {} or fewer, or use :export",
+                crate::ui::format_count(limit),
+                crate::ui::format_count(PAGED_VIEW_ROWS)
+            );
+        }
+        let rows = if self.data_source.is_paged() {
+            self.data_source.page(offset, limit)?
+        } else {
+            self.filtered_df.slice(offset as i64, limit)
+        };
+        Ok(rows.select(self.visible_columns())?)
+    }
+
+    /// Show the result of a SQL query. The `/` filter is cleared, since it may refer to
+    /// columns the query does not return; filters typed afterwards narrow the result.
+    pub fn run_query(&mut self, sql: &str) {
+        match self.data_source.query(sql) {
+            Ok(df) => {
+                self.query_df = Some(df);
+                self.sql_query = Some(sql.to_string());
+                self.selected_columns = None;
+                self.column_order.clear();
+                self.sort.clear();
+                self.duplicates = None;
+                self.column_offset = 0;
+                self.clear_filter();
+            }
+            Err(e) => {
+                self.error_message = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    /// Go back to viewing the source data
+    pub fn clear_query(&mut self) {
+        self.sql_query = None;
+        self.query_df = None;
+        self.selected_columns = None;
+        self.column_order.clear();
+        self.sort.clear();
+        self.duplicates = None;
+        self.column_offset = 0;
+        self.apply_filter();
+    }
+
+    /// Toggle case-insensitive string matching and re-run the active filter
+    pub fn toggle_ignore_case(&mut self) {
+        self.filter_options.ignore_case = !self.filter_options.ignore_case;
+        if !self.filters.is_empty() {
+            self.apply_filter();
+        }
+    }
+
+    /// Toggle fuzzy global search while typing a filter
+    pub fn toggle_fuzzy_search(&mut self) {
+        self.filter_options.fuzzy = !self.filter_options.fuzzy;
+        self.filter_edited();
+    }
+
+    /// Toggle whether global search skips non-string columns while typing a filter
+    pub fn toggle_strings_only(&mut self) {
+        self.filter_options.strings_only = !self.filter_options.strings_only;
+        self.filter_edited();
+    }
+
+    /// Re-run the filter shortly after the pattern was edited.
+    /// Paged sources filter on the server, so they wait for Enter instead.
+    fn filter_edited(&mut self) {
+        self.completion_index = 0;
+        self.match_preview = None;
+        if !self.data_source.is_paged() {
+            self.live_filter.schedule();
+        }
+    }
+
+    /// Start of the word before the filter cursor, if the cursor is where a column
+    /// name goes: at the start, or after `(`, AND, OR or NOT
+    fn completion_start(&self) -> Option<usize> {
+        let before = &self.filter_pattern[..self.filter_cursor];
+        let is_separator = |c: char| c.is_whitespace() || c == '(';
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| is_separator(*c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        if start == before.len() {
+            return None;
+        }
+
+        let context = before[..start].trim_end();
+        let last_word = context.rsplit(is_separator).next().unwrap_or("");
+        let at_column = last_word.is_empty()
+            || ["AND", "OR", "NOT"].iter().any(|k| last_word.eq_ignore_ascii_case(k));
+        at_column.then_some(start)
+    }
+
+    /// Column names matching the word being typed in the filter, names starting with it
+    /// first, then names containing it (both case-insensitive)
+    pub fn filter_completions(&self) -> Vec<String> {
+        let Some(start) = self.completion_start() else {
+            return Vec::new();
+        };
+        let word = &self.filter_pattern[start..self.filter_cursor];
+        let lower = word.to_lowercase();
+
+        let columns = self.view_columns();
+        let (mut matches, contained): (Vec<String>, Vec<String>) = columns
+            .into_iter()
+            .filter(|c| c.to_lowercase().contains(&lower))
+            .partition(|c| c.to_lowercase().starts_with(&lower));
+        matches.extend(contained);
+
+        // Nothing to offer once the name is complete
+        if matches.len() == 1 && matches[0] == word {
+            matches.clear();
+        }
+        matches
+    }
+
+    /// Highlight the previous completion
+    pub fn completion_up(&mut self) {
+        self.completion_index = self.completion_index.saturating_sub(1);
+    }
+
+    /// Highlight the next completion
+    pub fn completion_down(&mut self) {
+        if self.completion_index + 1 < self.filter_completions().len() {
+            self.completion_index += 1;
+        }
+    }
+
+    /// Replace the word before the cursor with the highlighted column name
+    pub fn complete_filter_column(&mut self) {
+        let completions = self.filter_completions();
+        let (Some(start), Some(column)) = (self.completion_start(), completions.get(self.completion_index)) else {
+            return;
+        };
+        self.filter_pattern.replace_range(start..self.filter_cursor, column);
+        self.filter_cursor = start + column.len();
+        self.filter_edited();
+    }
+
+    /// Whether a live filter is waiting to run or still running
+    pub fn live_filter_pending(&self) -> bool {
+        self.live_filter.is_pending()
+    }
+
+    /// Start a due live filter and show the result of a finished one.
+    /// Errors are expected while an expression is half typed, so they are not shown;
+    /// pressing Enter applies the filter and reports them.
+    pub fn update_live_filter(&mut self) {
+        // Filters run on the SQL query result when there is one
+        let base = self.query_df.as_ref().unwrap_or(self.data_source.dataframe());
+        let pattern = self.filter_expression();
+        self.live_filter.start_if_due(base, &pattern, self.filter_options);
+        match self.live_filter.take_result() {
+            Some(Ok(df)) => {
+                self.match_preview = Some(df.height());
+                self.error_message = None;
+                self.set_filtered(df);
+                self.scroll_offset = 0;
+            }
+            Some(Err(_)) => self.match_preview = None,
+            None => {}
+        }
+    }
+
+    /// Apply the filters, returning whether they were valid
+    pub fn apply_filter(&mut self) -> bool {
+        self.run_filter(None)
+    }
+
+    /// Apply the filters, scrolling to the top, or with `keep_scroll` back to that row
+    /// where the new rows allow it. Frames in memory are filtered in the background
+    /// (see `update_filter`) once the filter proved valid for their columns.
+    fn run_filter(&mut self, keep_scroll: Option<usize>) -> bool {
+        self.live_filter.cancel();
+        self.filter_job.cancel();
+        self.pending_filter = None;
+        let pattern = self.filter_expression();
+        let started = Instant::now();
+        if self.data_source.is_paged() {
+            // Paged sources run the filter server-side
+            return match self.data_source.filter_paged(&pattern, self.filter_options) {
+                Ok(()) => {
+                    self.rows_version += 1;
+                    self.filter_time = Some(started.elapsed());
+                    self.shown_filters = self.filters.clone();
+                    self.restore_scroll(keep_scroll);
+                    self.error_message = None;
+                    true
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("Filter error: {}", e));
+                    false
+                }
+            };
+        }
+
+        // Filters run on the SQL query result when there is one
+        let base = self.query_df.as_ref().unwrap_or(self.data_source.dataframe());
+        if pattern.is_empty() {
+            let df = base.clone();
+            self.filter_time = None;
+            self.error_message = None;
+            self.shown_filters.clear();
+            self.set_filtered(df);
+            self.restore_scroll(keep_scroll);
+            return true;
+        }
+        if let Err(e) = check_filter(&base.schema(), &pattern, self.filter_options) {
+            self.error_message = Some(format!("Filter error: {}", e));
+            return false;
+        }
+        self.filter_job.start(base, &pattern, self.filter_options);
+        self.pending_filter = Some(PendingFilter {
+            draft: self.filter_pattern.clone(),
+            keep_scroll,
+        });
+        self.error_message = None;
+        true
+    }
+
+    /// Show the rows of the applied filter once the background run finished, or the
+    /// error it failed with
+    pub fn update_filter(&mut self) {
+        let elapsed = self.filter_job.running_for();
+        let Some(result) = self.filter_job.take_result() else {
+            return;
+        };
+        let Some(pending) = self.pending_filter.take() else {
+            return;
+        };
+        match result {
+            Ok(df) => {
+                self.filter_time = elapsed;
+                self.shown_filters = self.filters.clone();
+                self.set_filtered(df);
+                self.restore_scroll(pending.keep_scroll);
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Filter error: {}", e));
+                self.restore_filters(pending);
+            }
+        }
+    }
+
+    /// Scroll to the row asked for on the command line, once the file has loaded and the
+    /// filter has run; a row past the end focuses the last one
+    pub fn update_jump(&mut self) {
+        if self.is_loading() || self.filter_job.running_for().is_some() {
+            return;
+        }
+        match self.pending_jump.take() {
+            Some(RowJump::Row(row)) => {
+                self.enter_cell_mode();
+                self.focus_cell(row.saturating_sub(1), self.cursor_column);
+                if row > self.total_rows() {
+                    self.message = Some(format!("Row {} is past the end, showing the last row", crate::ui::format_count(row)));
+                }
+            }
+            Some(RowJump::End) => self.scroll_to_bottom(),
+            None => {}
+        }
+    }
+
+    /// How long the applied filter has been running in the background
+    pub fn filter_running_for(&self) -> Option<std::time::Duration> {
+        self.filter_job.running_for()
+    }
+
+    /// Stop the applied filter running in the background and go back to the filters
+    /// of the rows shown (Esc)
+    pub fn cancel_filter(&mut self) {
+        self.filter_job.cancel();
+        if let Some(pending) = self.pending_filter.take() {
+            self.error_message = Some("Filter cancelled".to_string());
+            self.restore_filters(pending);
+        }
+    }
+
+    /// Go back to the filters of the rows shown after a failed or cancelled filter,
+    /// with the filter that was being typed back in the prompt
+    fn restore_filters(&mut self, pending: PendingFilter) {
+        self.filters = self.shown_filters.clone();
+        if !pending.draft.is_empty() {
+            self.filter_cursor = pending.draft.len();
+            self.filter_pattern = pending.draft;
+            self.filter_mode = true;
+        }
+    }
+
+    fn restore_scroll(&mut self, keep_scroll: Option<usize>) {
+        let max_offset = self.total_rows().saturating_sub(self.page_size);
+        self.scroll_offset = keep_scroll.unwrap_or(0).min(max_offset);
+    }
+
+    /// Sort by the focused column alone: ascending, then descending, then unsorted again
+    pub fn cycle_sort(&mut self) {
+        if let Some(column) = self.cursor_column_name() {
+            self.cycle_sort_by(column);
+        }
+    }
+
+    /// Cycle the sort of the `index`th visible column, as for a click on its header
+    pub fn cycle_sort_column(&mut self, index: usize) {
+        if let Some(column) = self.visible_columns().get(index).cloned() {
+            self.cycle_sort_by(column);
+        }
+    }
+
+    fn cycle_sort_by(&mut self, column: String) {
+        self.sort = match self.sort.as_slice() {
+            [(sorted, SortOrder::Ascending)] if *sorted == column => vec![(column, SortOrder::Descending)],
+            [(sorted, SortOrder::Descending)] if *sorted == column => Vec::new(),
+            _ => vec![(column, SortOrder::Ascending)],
+        };
+        self.apply_sort();
+    }
+
+    /// Add the column at the cursor of the column selection to the sort, after the
+    /// columns already in it; pressing again makes it descending, then removes it
+    pub fn cycle_sort_at_column_cursor(&mut self) {
+        let Some(column) = self.listed_columns().get(self.column_selection_cursor).cloned() else {
+            return;
+        };
+        match self.sort.iter().position(|(sorted, _)| *sorted == column) {
+            None => self.sort.push((column, SortOrder::Ascending)),
+            Some(i) if self.sort[i].1 == SortOrder::Ascending => self.sort[i].1 = SortOrder::Descending,
+            Some(i) => {
+                self.sort.remove(i);
+            }
+        }
+        self.apply_sort();
+    }
+
+    /// ▲/▼ marker for a sorted column, numbered by priority when sorting by several
+    pub fn sort_indicator(&self, column: &str) -> Option<String> {
+        let i = self.sort.iter().position(|(sorted, _)| sorted == column)?;
+        let indicator = self.sort[i].1.indicator();
+        Some(if self.sort.len() > 1 {
+            format!("{}{}", indicator, i + 1)
+        } else {
+            indicator.to_string()
+        })
+    }
+
+    /// Switch between natural and strict lexical order for sorted string columns
+    pub fn toggle_natural_sort(&mut self) {
+        self.sort_options.natural = !self.sort_options.natural;
+        if !self.sort.is_empty() && !self.data_source.is_paged() {
+            self.apply_sort();
+        }
+    }
+
+    /// Switch between sorting missing values first and last
+    pub fn toggle_nulls_last(&mut self) {
+        self.sort_options.nulls_last = !self.sort_options.nulls_last;
+        if !self.sort.is_empty() {
+            self.apply_sort();
+        }
+    }
+
+    /// Drop the sort and show the filtered rows in file order again
+    pub fn restore_file_order(&mut self) {
+        if !self.sort.is_empty() {
+            self.sort.clear();
+            self.apply_sort();
+        }
+    }
+
+    /// Show the rows in the order of `sort`
+    fn apply_sort(&mut self) {
+        if self.data_source.is_paged() {
+            // Paged sources sort on the server
+            match self.data_source.sort_paged(&self.sort, self.sort_options.nulls_last) {
+                Ok(()) => {
+                    self.rows_version += 1;
+                    self.scroll_offset = 0;
+                }
+                Err(e) => {
+                    self.error_message = Some(format!("{:#}", e));
+                    self.sort.clear();
+                }
+            }
+        } else {
+            // The filtered rows are put back in file order and sorted again
+            match self.unsorted_rows() {
+                Ok(df) => {
+                    self.filtered_df = self.sorted(df);
+                    self.rows_version += 1;
+                    self.scroll_offset = 0;
+                }
+                // Filtering again also yields the file order
+                Err(_) => {
+                    self.apply_filter();
+                }
+            }
+        }
+    }
+
+    /// The filtered rows in file order
+    fn unsorted_rows(&mut self) -> anyhow::Result<DataFrame> {
+        match self.sort_positions.take() {
+            Some(positions) => restore_order(&self.filtered_df, &positions),
+            None => Ok(self.filtered_df.clone()),
+        }
+    }
+
+    /// Show freshly filtered rows, sorted, and measure them for the heatmap
+    fn set_filtered(&mut self, df: DataFrame) {
+        let df = self.only_duplicates(df);
+        self.filtered_df = self.sorted(df);
+        self.rows_version += 1;
+        self.value_ranges = self.heatmap.then(|| value_ranges(&self.filtered_df));
+    }
+
+    /// Keep the freshly filtered rows that are duplicates by `duplicates`. A failed search
+    /// is reported and dropped.
+    fn only_duplicates(&mut self, df: DataFrame) -> DataFrame {
+        let Some(columns) = &self.duplicates else {
+            return df;
+        };
+        let key = if columns.is_empty() { Ok(df.clone()) } else { df.select(columns) };
+        match key.and_then(|key| key.is_duplicated()).and_then(|mask| df.filter(&mask)) {
+            Ok(duplicates) => duplicates,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to find duplicates: {}", e));
+                self.duplicates = None;
+                df
+            }
+        }
+    }
+
+    /// Sort freshly filtered rows by the active sort. A failed sort is reported and dropped.
+    fn sorted(&mut self, df: DataFrame) -> DataFrame {
+        self.sort_positions = None;
+        if self.sort.is_empty() {
+            return df;
+        }
+        match sort_frame(&df, &self.sort, self.sort_options) {
+            Ok((sorted, positions)) => {
+                self.sort_positions = Some(positions);
+                sorted
+            }
+            Err(e) => {
+                self.error_message = Some(format!("{:#}", e));
+                self.sort.clear();
+                df
+            }
+        }
+    }
+
+    /// Parse filter pattern to extract column name and value (deprecated, kept for reference)
+    /// The new filter system handles this automatically
+    #[allow(dead_code)]
+    fn parse_filter_pattern(&self, pattern: &str) -> (Option<String>, String) {
+        // Try colon separator first
+        if let Some(colon_pos) = pattern.find(':') {
+            let col = pattern[..colon_pos].trim().to_string();
+            let val = pattern[colon_pos + 1..].trim().to_string();
+
+            // Verify column exists
+            if self.data_source.columns().contains(&col) {
+                return (Some(col), val);
+            }
+        }
+
+        // Try equals separator
+        if let Some(eq_pos) = pattern.find('=') {
+            let col = pattern[..eq_pos].trim().to_string();
+            let val = pattern[eq_pos + 1..].trim().to_string();
+
+            // Verify column exists
+            if self.data_source.columns().contains(&col) {
+                return (Some(col), val);
+            }
+        }
+
+        // No column specified or column not found, search all columns
+        (None, pattern.to_string())
+    }
+
+    /// Get total number of rows (after filtering)
+    pub fn total_rows(&self) -> usize {
+        if self.data_source.is_paged() {
+            self.data_source.len()
+        } else {
+            self.filtered_df.height()
+        }
+    }
+
+    /// Get total number of rows (before filtering)
+    pub fn original_total_rows(&self) -> usize {
+        self.data_source.len()
+    }
+
+    /// Whether the source is a database that filters server-side
+    pub fn is_paged(&self) -> bool {
+        self.data_source.is_paged()
+    }
+
+    /// Rows the filters are applied to: the SQL query result, or the source data
+    pub fn unfiltered_rows(&self) -> usize {
+        match &self.query_df {
+            Some(df) => df.height(),
+            None => self.original_total_rows(),
+        }
+    }
+
+    /// Quit the application
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    /// Toggle line numbers display
+    pub fn toggle_line_numbers(&mut self) {
+        self.show_line_numbers = !self.show_line_numbers;
+    }
+
+    /// Toggle the shading of alternate rows and of the focused row
+    pub fn toggle_row_shading(&mut self) {
+        self.shade_rows = !self.shade_rows;
+    }
+
+    /// Split the table into two panes over the same rows, starting at the same
+    /// position, or go back to one pane
+    pub fn toggle_split(&mut self) {
+        self.split = match self.split {
+            Some(_) => None,
+            None => Some(Split {
+                other: Pane {
+                    scroll_offset: self.scroll_offset,
+                    column_offset: self.column_offset,
+                    page_size: self.page_size,
+                    cursor_row: self.cursor_row,
+                    cursor_column: self.cursor_column,
+                    column_widths: self.column_widths.clone(),
+                    table_width: self.table_width,
+                },
+                focus_bottom: false,
+            }),
+        };
+    }
+
+    /// Go back to one pane, keeping the focused one
+    pub fn close_split(&mut self) {
+        self.split = None;
+    }
+
+    /// Move the focus to the other pane: its position becomes the app's, which is
+    /// what scrolling and drawing work on
+    pub fn switch_pane(&mut self) {
+        let Some(split) = &mut self.split else {
+            return;
+        };
+        let other = &mut split.other;
+        std::mem::swap(&mut self.scroll_offset, &mut other.scroll_offset);
+        std::mem::swap(&mut self.column_offset, &mut other.column_offset);
+        std::mem::swap(&mut self.page_size, &mut other.page_size);
+        std::mem::swap(&mut self.cursor_row, &mut other.cursor_row);
+        std::mem::swap(&mut self.cursor_column, &mut other.cursor_column);
+        std::mem::swap(&mut self.column_widths, &mut other.column_widths);
+        std::mem::swap(&mut self.table_width, &mut other.table_width);
+        split.focus_bottom = !split.focus_bottom;
+    }
+
+    /// Toggle wrapping long cells onto several lines
+    pub fn toggle_wrap_cells(&mut self) {
+        self.wrap_cells = !self.wrap_cells;
+    }
+
+    /// Toggle coloring numeric cells on a scale from their column's minimum to its maximum
+    pub fn toggle_heatmap(&mut self) {
+        if self.data_source.is_paged() {
+            self.error_message = Some("The heatmap needs all rows in memory, which tables read page by page are not".to_string());
+            return;
+        }
+        self.heatmap = !self.heatmap;
+        self.value_ranges = self.heatmap.then(|| value_ranges(&self.filtered_df));
+    }
+
+    /// Where the value of `column` in `row` falls between the column's minimum (0.0) and
+    /// maximum (1.0), or `None` for missing values, non-numeric columns and constant ones
+    pub fn heat_level(&self, column: &str, row: usize) -> Option<f64> {
+        let &(min, max) = self.value_ranges.as_ref()?.get(column)?;
+        if max <= min {
+            return None;
+        }
+        let value = self.filtered_df.column(column).ok()?.get(row).ok()?.extract::<f64>()?;
+        Some(((value - min) / (max - min)).clamp(0.0, 1.0))
+    }
+
+    /// Enter column selection mode
+    pub fn enter_column_selection_mode(&mut self) {
+        self.column_selection_mode = true;
+        self.column_selection_cursor = 0;
+        self.column_search.clear();
+        self.column_search_mode = false;
+        self.last_toggled_column = None;
+        self.column_list_offset.set(0);
+        // Paged sources only hold the rows on screen, which say little about a column
+        if !self.data_source.is_paged() {
+            self.column_stats.start(&self.filtered_df);
+        }
+
+        // Initialize selected_columns if not set (start with all columns selected)
+        if self.selected_columns.is_none() {
+            self.selected_columns = Some(self.all_columns());
+        }
+    }
+
+    /// Exit column selection mode
+    pub fn exit_column_selection_mode(&mut self) {
+        self.column_selection_mode = false;
+    }
+
+    /// Move cursor up in column selection
+    pub fn column_selection_up(&mut self) {
+        if self.column_selection_cursor > 0 {
+            self.column_selection_cursor -= 1;
+        }
+    }
+
+    /// Move cursor down in column selection
+    pub fn column_selection_down(&mut self) {
+        let max_cursor = self.listed_columns().len().saturating_sub(1);
+        if self.column_selection_cursor < max_cursor {
+            self.column_selection_cursor += 1;
+        }
+    }
+
+    /// Toggle column visibility at cursor position
+    pub fn toggle_column_at_cursor(&mut self) {
+        let listed = self.listed_columns();
+        if self.column_selection_cursor >= listed.len() {
+            return;
+        }
+
+        let column_name = &listed[self.column_selection_cursor];
+        self.last_toggled_column = Some(column_name.clone());
+        let shown = self.is_column_selected(column_name);
+        self.set_column_selected(column_name, !shown);
+    }
+
+    /// Show or hide every listed column from the one last toggled to the one at the
+    /// cursor, as the last toggled one now is; without one, toggle the cursor's column
+    pub fn toggle_column_range_at_cursor(&mut self) {
+        let listed = self.listed_columns();
+        let start = self
+            .last_toggled_column
+            .as_ref()
+            .and_then(|last| listed.iter().position(|c| c == last));
+        let (Some(start), Some(_)) = (start, listed.get(self.column_selection_cursor)) else {
+            self.toggle_column_at_cursor();
+            return;
+        };
+        let show = self.is_column_selected(&listed[start]);
+        let (from, to) = (start.min(self.column_selection_cursor), start.max(self.column_selection_cursor));
+        for column in &listed[from..=to] {
+            self.set_column_selected(column, show);
+        }
+        self.last_toggled_column = Some(listed[self.column_selection_cursor].clone());
+    }
+
+    /// Hide all listed columns (all of them, unless a search narrows the list)
+    pub fn select_no_columns(&mut self) {
+        for column in self.listed_columns() {
+            self.set_column_selected(&column, false);
+        }
+    }
+
+    /// Show the hidden listed columns and hide the shown ones
+    pub fn invert_column_selection(&mut self) {
+        for column in self.listed_columns() {
+            let shown = self.is_column_selected(&column);
+            self.set_column_selected(&column, !shown);
+        }
+    }
+
+    fn set_column_selected(&mut self, column: &str, show: bool) {
+        let all_columns = self.all_columns();
+        let selected = self.selected_columns.get_or_insert(all_columns);
+        let position = selected.iter().position(|c| c == column);
+        match (show, position) {
+            (true, None) => selected.push(column.to_string()),
+            (false, Some(i)) => {
+                selected.remove(i);
+            }
+            _ => {}
+        }
+    }
+
+    /// Move the column at the cursor one place earlier in the display order, or with
+    /// `down` one place later, keeping the cursor on it. While searching, it swaps places
+    /// with the listed column above or below it.
+    pub fn move_column_at_cursor(&mut self, down: bool) {
+        let listed = self.listed_columns();
+        let from = self.column_selection_cursor;
+        let to = if down { from + 1 } else { from.wrapping_sub(1) };
+        if from >= listed.len() || to >= listed.len() {
+            return;
+        }
+        let mut columns = self.all_columns();
+        let position = |name: &String| columns.iter().position(|c| c == name);
+        if let (Some(a), Some(b)) = (position(&listed[from]), position(&listed[to])) {
+            columns.swap(a, b);
+        }
+        self.column_order = columns;
+        self.column_selection_cursor = to;
+    }
+
+    /// Start typing a fuzzy search of the column selection list
+    pub fn start_column_search(&mut self) {
+        self.column_search_mode = true;
+    }
+
+    /// Stop typing the column search, keeping the list narrowed to its matches
+    pub fn finish_column_search(&mut self) {
+        self.column_search_mode = false;
+    }
+
+    /// Stop typing the column search and list all columns again
+    pub fn clear_column_search(&mut self) {
+        self.column_search_mode = false;
+        self.column_search.clear();
+        self.column_selection_cursor = 0;
+    }
+
+    /// Put the cursor back on the first match after the search text changed
+    pub fn column_search_edited(&mut self) {
+        self.column_selection_cursor = 0;
+    }
+
+    /// Take in the column counts finished in the background
+    pub fn update_column_stats(&mut self) {
+        self.column_stats.update();
+    }
+
+    /// Whether the column selection or the schema overview is waiting for column counts
+    pub fn column_stats_pending(&self) -> bool {
+        (self.column_selection_mode || self.schema_mode) && self.column_stats.is_pending()
+    }
+
+    /// Open the schema overview, counting the missing and distinct values of the
+    /// filtered rows' columns in the background
+    pub fn enter_schema_mode(&mut self) {
+        self.schema_mode = true;
+        self.schema_cursor = 0;
+        self.schema_list_offset.set(0);
+        // Paged sources only hold the rows on screen, which say little about a column,
+        // but they still give an example
+        let rows = if self.data_source.is_paged() {
+            self.data_source.page(self.scroll_offset, self.page_size.max(1)).unwrap_or_default()
+        } else {
+            self.column_stats.start(&self.filtered_df);
+            self.filtered_df.clone()
+        };
+        self.schema_examples = rows
+            .get_columns()
+            .iter()
+            .filter_map(|column| {
+                let value = column.as_materialized_series().iter().find(|value| !value.is_null())?;
+                let text = self.cell_format.format(&value)?.replace(['\n', '\r'], " ");
+                Some((column.name().to_string(), text))
+            })
+            .collect();
+    }
+
+    /// Close the schema overview
+    pub fn exit_schema_mode(&mut self) {
+        self.schema_mode = false;
+    }
+
+    /// Move the schema overview's cursor by `delta` columns, stopping at either end
+    pub fn schema_move(&mut self, delta: isize) {
+        let last = self.all_columns().len().saturating_sub(1);
+        self.schema_cursor = self.schema_cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// First present value of `column` among the rows, for the schema overview
+    pub fn schema_example(&self, column: &str) -> Option<&str> {
+        self.schema_examples.get(column).map(String::as_str)
+    }
+
+    /// Type given to `column` with `--dtype` instead of inferring it
+    pub fn forced_type(&self, column: &str) -> Option<&DataType> {
+        let dtypes = &self.data_source.load_options().dtypes;
+        dtypes.iter().find(|(name, _)| name == column).map(|(_, dtype)| dtype)
+    }
+
+    /// Close the schema overview and focus the column under its cursor in the table
+    pub fn go_to_schema_column(&mut self) {
+        self.schema_mode = false;
+        let Some(column) = self.all_columns().get(self.schema_cursor).cloned() else {
+            return;
+        };
+        let Some(index) = self.visible_columns().iter().position(|c| *c == column) else {
+            self.error_message = Some(format!("Column '{}' is hidden (c shows it again)", column));
+            return;
+        };
+        if !self.cell_mode {
+            self.enter_cell_mode();
+        }
+        self.focus_cell(self.cursor_row, index);
+    }
+
+    /// Missing and distinct values of `column` in the filtered rows, once counted
+    pub fn column_stats(&self, column: &str) -> Option<Stats> {
+        self.column_stats.get(column)
+    }
+
+    /// Columns of the column selection list: all of them, in display order, or those
+    /// fuzzily matching the column search (see `filter::fuzzy_match`)
+    pub fn listed_columns(&self) -> Vec<String> {
+        let term = fuzzy_chars(&self.column_search.text);
+        self.all_columns()
+            .into_iter()
+            .filter(|column| fuzzy_match(column, &term))
+            .collect()
+    }
+
+    /// Reset column selection to show all columns
+    pub fn reset_column_selection(&mut self) {
+        self.selected_columns = None;
+        self.column_offset = 0;
+    }
+
+    /// Check if a column is currently selected/visible
+    pub fn is_column_selected(&self, column_name: &str) -> bool {
+        match &self.selected_columns {
+            None => true, // All columns visible
+            Some(selected) => selected.contains(&column_name.to_string()),
+        }
+    }
+
+    /// Get all available columns, in display order
+    pub fn all_columns(&self) -> Vec<String> {
+        let columns = self.view_columns();
+        if self.column_order.is_empty() {
+            return columns;
+        }
+        let mut ordered: Vec<String> = self
+            .column_order
+            .iter()
+            .filter(|c| columns.contains(c))
+            .cloned()
+            .collect();
+        ordered.extend(columns.into_iter().filter(|c| !self.column_order.contains(c)));
+        ordered
+    }
+
+    /// Short data type name of each column, like `f64` or `datetime`
+    pub fn column_types(&self) -> HashMap<String, String> {
+        // Paged sources know the types from when the table was opened
+        let schema = match self.data_source.paged_schema() {
+            Some(schema) => schema,
+            None => Arc::new(self.filtered_df.schema()),
+        };
+        schema
+            .iter()
+            .map(|(name, dtype)| (name.to_string(), type_label(dtype)))
+            .collect()
+    }
+
+    /// Get all named tables (sheets or database tables) in the data source
+    pub fn all_tables(&self) -> &[String] {
+        self.data_source.tables()
+    }
+
+    /// Get the currently loaded table (sheet or database table)
+    pub fn current_table(&self) -> Option<&str> {
+        self.data_source.current_table()
+    }
+
+    /// What tables are called for the current source ("Sheet" or "Table")
+    pub fn table_label(&self) -> &'static str {
+        self.data_source.source_type().table_label()
+    }
+
+    /// Enter table selection mode with the cursor on the current table
+    pub fn enter_table_selection_mode(&mut self) {
+        if self.data_source.tables().is_empty() {
+            self.error_message = Some("This file has no tables to switch between".to_string());
+            return;
+        }
+
+        self.table_selection_cursor = self
+            .current_table()
+            .and_then(|current| self.all_tables().iter().position(|t| t == current))
+            .unwrap_or(0);
+        self.table_selection_mode = true;
+    }
+
+    /// Exit table selection mode without switching
+    pub fn exit_table_selection_mode(&mut self) {
+        self.table_selection_mode = false;
+    }
+
+    /// Move cursor up in table selection
+    pub fn table_selection_up(&mut self) {
+        if self.table_selection_cursor > 0 {
+            self.table_selection_cursor -= 1;
+        }
+    }
+
+    /// Move cursor down in table selection
+    pub fn table_selection_down(&mut self) {
+        let max_cursor = self.data_source.tables().len().saturating_sub(1);
+        if self.table_selection_cursor < max_cursor {
+            self.table_selection_cursor += 1;
+        }
+    }
+
+    /// Load the table at the cursor and reset the view for its columns
+    pub fn select_table_at_cursor(&mut self) {
+        let name = match self.data_source.tables().get(self.table_selection_cursor) {
+            Some(name) => name.clone(),
+            None => return,
+        };
+
+        match self.data_source.select_table(&name) {
+            Ok(()) => {
+                self.filtered_df = self.data_source.dataframe().clone();
+                self.rows_version += 1;
+                self.value_ranges = self.heatmap.then(|| value_ranges(&self.filtered_df));
+                self.sort_positions = None;
+                self.sql_query = None;
+                self.query_df = None;
+                self.filters.clear();
+                self.filter_pattern.clear();
+                self.filter_cursor = 0;
+                self.selected_columns = None;
+                self.column_order.clear();
+                self.sort.clear();
+                self.scroll_offset = 0;
+                self.duplicates = None;
+                self.column_offset = 0;
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to load '{}': {}", name, e));
+            }
+        }
+        self.table_selection_mode = false;
+    }
+
+    /// Re-read the file after it changed on disk, reapplying the active filter
+    /// and keeping the scroll position where the new data allows it
+    pub fn reload(&mut self) {
+        // The load still running reads the file as it is now
+        if self.loading.is_some() {
+            return;
+        }
+        let started = Instant::now();
+        match self.data_source.reload() {
+            Ok(()) => {
+                self.load_time = Some(started.elapsed());
+                self.refresh_rows();
+                self.reloaded_at = Some(chrono::Local::now().format("%H:%M:%S").to_string());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Reload failed: {}", e));
+            }
+        }
+    }
+
+    /// Run the SQL query and filters again after the source data changed, keeping the
+    /// scroll position where the new data allows it
+    fn refresh_rows(&mut self) {
+        let scroll_offset = self.scroll_offset;
+        // Re-run the SQL query on the new data
+        if let Some(sql) = &self.sql_query {
+            match self.data_source.query(sql) {
+                Ok(df) => self.query_df = Some(df),
+                Err(e) => {
+                    self.error_message = Some(format!("{:#}", e));
+                    self.sql_query = None;
+                    self.query_df = None;
+                }
+            }
+        }
+        self.run_filter(Some(scroll_offset));
+    }
+
+    /// Keep loading the rest of the file in the background, adding its rows to the view
+    /// as they are parsed
+    pub fn keep_loading(&mut self, rest: BackgroundLoad) {
+        self.loading = Some(rest);
+    }
+
+    /// Whether rows of the file are still being loaded
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// Rows read so far of a file still loading
+    pub fn loading_rows(&self) -> Option<usize> {
+        self.loading.as_ref().map(|loading| loading.progress().rows())
+    }
+
+    /// Take in the rows parsed since the last call, and the loaded source once the
+    /// load is done. The filtered rows catch up every `LOADING_REFRESH`, except while a
+    /// filter is being typed, so its live preview is not cut short, or still running.
+    pub fn update_loading(&mut self) {
+        let Some(loading) = &self.loading else {
+            return;
+        };
+        match loading.try_finish() {
+            Some(Ok(source)) => {
+                self.load_time = Some(loading.elapsed());
+                self.loading = None;
+                self.data_source = source;
+                self.refresh_rows();
+            }
+            Some(Err(e)) => {
+                self.loading = None;
+                self.error_message = Some(format!("Loading failed, showing the rows read so far: {:#}", e));
+            }
+            None => {
+                let rows = loading.progress().take_rows();
+                if let Err(e) = self.data_source.append_rows(&rows) {
+                    self.loading = None;
+                    self.error_message = Some(format!("{:#}", e));
+                    return;
+                }
+                self.loading_behind |= !rows.is_empty();
+                let idle = !self.filter_mode && self.filter_job.running_for().is_none();
+                if self.loading_behind && self.loading_refreshed.elapsed() >= LOADING_REFRESH && idle {
+                    self.loading_refreshed = Instant::now();
+                    self.loading_behind = false;
+                    self.refresh_rows();
+                }
+            }
+        }
+    }
+
+    /// Estimated memory taken by the loaded data, or `None` for paged sources, which
+    /// only hold the rows on screen
+    pub fn memory_size(&self) -> Option<usize> {
+        if self.data_source.is_paged() {
+            return None;
+        }
+        let query_size = self.query_df.as_ref().map_or(0, |df| df.estimated_size());
+        Some(self.data_source.dataframe().estimated_size() + query_size)
+    }
+
+    /// Whether this tab shows a comparison of two files
+    pub fn is_diff(&self) -> bool {
+        matches!(self.data_source.source_type(), DataSourceType::Diff)
+    }
+
+    /// Which rows of the file were loaded, when it did not fit in memory
+    pub fn partial(&self) -> Option<Partial> {
+        self.data_source.partial()
+    }
+
+    /// Encoding the current CSV file was decoded with
+    pub fn encoding_name(&self) -> Option<&'static str> {
+        self.data_source.encoding().map(|e| e.name())
+    }
+
+    /// Enter encoding selection mode (CSV files only)
+    pub fn enter_encoding_selection_mode(&mut self) {
+        if self.loading.is_some() {
+            self.error_message = Some("The file is still loading".to_string());
+            return;
+        }
+        let Some(current) = self.data_source.encoding() else {
+            self.error_message = Some("Encodings can only be chosen for local CSV files".to_string());
+            return;
+        };
+        self.encoding_selection_cursor = ENCODINGS.iter().position(|e| *e == current).unwrap_or(0);
+        self.encoding_selection_mode = true;
+    }
+
+    /// Exit encoding selection mode without reloading
+    pub fn exit_encoding_selection_mode(&mut self) {
+        self.encoding_selection_mode = false;
+    }
+
+    /// Move cursor up in encoding selection
+    pub fn encoding_selection_up(&mut self) {
+        if self.encoding_selection_cursor > 0 {
+            self.encoding_selection_cursor -= 1;
+        }
+    }
+
+    /// Move cursor down in encoding selection
+    pub fn encoding_selection_down(&mut self) {
+        if self.encoding_selection_cursor < ENCODINGS.len() - 1 {
+            self.encoding_selection_cursor += 1;
+        }
+    }
+
+    /// Reload the file with the encoding at the cursor and reapply the active filter
+    pub fn select_encoding_at_cursor(&mut self) {
+        let encoding = ENCODINGS[self.encoding_selection_cursor];
+        match self.data_source.reload_with_encoding(encoding) {
+            Ok(()) => {
+                // Column names may decode differently, so the column selection and
+                // any SQL query no longer apply
+                self.selected_columns = None;
+                self.column_order.clear();
+                self.sort.clear();
+                self.column_offset = 0;
+                self.sql_query = None;
+                self.query_df = None;
+                self.apply_filter();
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to reload as {}: {}", encoding.name(), e));
+            }
+        }
+        self.encoding_selection_mode = false;
+    }
+}
+
+/// (min, max) of each numeric column, skipping columns with no values
+fn value_ranges(df: &DataFrame) -> HashMap<String, (f64, f64)> {
+    df.get_columns()
+        .iter()
+        .filter(|column| column.dtype().is_numeric())
+        .filter_map(|column| {
+            let values = column.as_materialized_series().cast(&DataType::Float64).ok()?;
+            let values = values.f64().ok()?;
+            Some((column.name().to_string(), (values.min()?, values.max()?)))
+        })
+        .collect()
 }
@@ -0,0 +1,42 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// The system clipboard, kept open: on X11 the copied text is only served while it is
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// How copied text reached the clipboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Copied {
+    System,
+    /// OSC 52, asking the terminal to set its clipboard
+    Terminal,
+}
+
+/// Put `text` on the system clipboard. Over SSH, or where there is no clipboard to
+/// open, the terminal is asked to set its own (OSC 52), which most terminals support.
+pub fn copy(text: &str) -> Result<Copied> {
+    let remote = std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some();
+    if !remote && copy_to_system(text).is_ok() {
+        return Ok(Copied::System);
+    }
+    copy_to_terminal(text)?;
+    Ok(Copied::Terminal)
+}
+
+fn copy_to_system(text: &str) -> Result<(), arboard::Error> {
+    let mut clipboard = CLIPBOARD.lock().unwrap_or_else(|e| e.into_inner());
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new()?);
+    }
+    clipboard.as_mut().expect("opened above").set_text(text)
+}
+
+fn copy_to_terminal(text: &str) -> Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)
+        .and_then(|_| stdout.flush())
+        .context("Failed to send the text to the terminal")
+}
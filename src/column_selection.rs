@@ -4,9 +4,9 @@ use crate::app::App;
 pub fn render_column_selection(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
     use ratatui::{
         layout::{Constraint, Direction, Layout},
-        style::{Color, Modifier, Style},
+        style::Style,
         text::{Line, Span},
-        widgets::{Block, Borders, List, ListItem, Paragraph},
+        widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     };
 
     let chunks = Layout::default()
@@ -22,54 +22,96 @@ pub fn render_column_selection(f: &mut ratatui::Frame, app: &App, area: ratatui:
     let header_block = Block::default()
         .title(" Column Selection ")
         .borders(Borders::ALL)
-        .style(Style::default().fg(Color::Cyan));
+        .style(Style::default().fg(app.theme.frame));
 
     let header_text = vec![Line::from(vec![
-        Span::styled("↑↓", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::styled("↑↓", app.theme.key()),
         Span::raw(" navigate | "),
-        Span::styled("Space", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::styled("J/K", app.theme.key()),
+        Span::raw(" move | "),
+        Span::styled("Space", app.theme.key()),
         Span::raw(" toggle | "),
-        Span::styled("a", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-        Span::raw(" all | "),
-        Span::styled("Enter", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::styled("Shift+Space/V", app.theme.key()),
+        Span::raw(" range | "),
+        Span::styled("a/d/i", app.theme.key()),
+        Span::raw(" all/none/invert | "),
+        Span::styled("s", app.theme.key()),
+        Span::raw(" sort | "),
+        Span::styled("n", app.theme.key()),
+        Span::raw(" nulls first/last | "),
+        Span::styled("p", app.theme.key()),
+        Span::raw(" pin | "),
+        Span::styled("/", app.theme.key()),
+        Span::raw(" search | "),
+        Span::styled("Enter", app.theme.key()),
         Span::raw(" done | "),
-        Span::styled("Esc", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::styled("Esc", app.theme.key()),
         Span::raw(" cancel"),
     ])];
 
     let header = Paragraph::new(header_text).block(header_block);
     f.render_widget(header, chunks[0]);
 
-    // Column list
+    // Column list, narrowed by the search
     let all_columns = app.all_columns();
-    let items: Vec<ListItem> = all_columns
+    let listed = app.listed_columns();
+    let types = app.column_types();
+    let items: Vec<ListItem> = listed
         .iter()
         .enumerate()
         .map(|(i, col)| {
             let is_selected = app.is_column_selected(col);
             let checkbox = if is_selected { "[✓]" } else { "[ ]" };
-            let content = format!("{} {}", checkbox, col);
+            let mut content = match app.sort_indicator(col) {
+                Some(indicator) => format!("{} {} {}", checkbox, col, indicator),
+                None => format!("{} {}", checkbox, col),
+            };
+            if app.pinned_columns.contains(col) {
+                content.push_str(" (pinned)");
+            }
+            let mut dtype = types.get(col).map(|dtype| format!(" ({})", dtype)).unwrap_or_default();
+            // Counts appear as the background job gets to the column
+            if let Some(stats) = app.column_stats(col) {
+                let distinct = stats.distinct.map_or("?".to_string(), |n| n.to_string());
+                dtype.push_str(&format!(" · {} missing · {} distinct", stats.nulls, distinct));
+            } else if app.column_stats_pending() {
+                dtype.push_str(" · …");
+            }
 
             let style = if i == app.column_selection_cursor {
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD)
-                    .bg(Color::DarkGray)
+                app.theme.cursor_entry()
             } else if is_selected {
-                Style::default().fg(Color::Green)
+                Style::default().fg(app.theme.good)
             } else {
-                Style::default().fg(Color::DarkGray)
+                Style::default().fg(app.theme.muted)
             };
 
-            ListItem::new(content).style(style)
+            // Types are dimmed, except on the cursor entry whose background is muted too
+            let dtype_style = if i == app.column_selection_cursor {
+                Style::default()
+            } else {
+                Style::default().fg(app.theme.muted)
+            };
+            let line = Line::from(vec![Span::raw(content), Span::styled(dtype, dtype_style)]);
+            ListItem::new(line).style(style)
         })
         .collect();
 
+    let title = if app.column_search.text.is_empty() {
+        " Columns ".to_string()
+    } else {
+        format!(" Columns matching \"{}\" ({} of {}) ", app.column_search.text, listed.len(), all_columns.len())
+    };
     let list = List::new(items)
-        .block(Block::default().title(" Columns ").borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(Style::default().fg(app.theme.text));
 
-    f.render_widget(list, chunks[1]);
+    // The list scrolls from where it was drawn last, just enough to keep the cursor in view
+    let mut state = ListState::default()
+        .with_offset(app.column_list_offset.get())
+        .with_selected(Some(app.column_selection_cursor));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+    app.column_list_offset.set(state.offset());
 
     // Footer with stats
     let selected_count = all_columns
@@ -82,11 +124,19 @@ pub fn render_column_selection(f: &mut ratatui::Frame, app: &App, area: ratatui:
         .borders(Borders::ALL);
 
     let footer_text = format!(
-        "Selected: {} / {} columns",
+        "Selected: {} / {} columns | Nulls sort {}",
         selected_count,
-        all_columns.len()
+        all_columns.len(),
+        if app.sort_options.nulls_last { "last" } else { "first" }
     );
 
-    let footer = Paragraph::new(footer_text).block(footer_block);
+    // While the search is typed, it takes the place of the stats
+    let footer = if app.column_search_mode {
+        Paragraph::new(app.column_search.display())
+            .style(Style::default().fg(app.theme.good))
+            .block(Block::default().title(" Search (fuzzy) | Enter keep | Esc clear ").borders(Borders::ALL))
+    } else {
+        Paragraph::new(footer_text).block(footer_block)
+    };
     f.render_widget(footer, chunks[2]);
 }
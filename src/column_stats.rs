@@ -0,0 +1,177 @@
+use crate::data::CellFormat;
+use crate::ui::format_count;
+use anyhow::Result;
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+/// Missing and distinct values of a column
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub nulls: usize,
+    /// Distinct present values, `None` for types that cannot be counted
+    pub distinct: Option<usize>,
+}
+
+/// Counts the missing and distinct values of each column on a background thread, so
+/// the column selection opens at once and fills in the counts as they come
+pub struct ColumnStats {
+    /// Counts of the columns done so far
+    stats: HashMap<String, Stats>,
+    /// Columns still being counted
+    remaining: usize,
+    /// Bumped for every job, so results of superseded jobs are dropped
+    generation: u64,
+    sender: Sender<(u64, String, Stats)>,
+    results: Receiver<(u64, String, Stats)>,
+}
+
+impl ColumnStats {
+    pub fn new() -> Self {
+        let (sender, results) = channel();
+        Self {
+            stats: HashMap::new(),
+            remaining: 0,
+            generation: 0,
+            sender,
+            results,
+        }
+    }
+
+    /// Forget the counts and start counting the columns of `df`
+    pub fn start(&mut self, df: &DataFrame) {
+        self.stats.clear();
+        self.remaining = df.width();
+        self.generation += 1;
+
+        let generation = self.generation;
+        let sender = self.sender.clone();
+        let df = df.clone();
+        thread::spawn(move || {
+            for column in df.get_columns() {
+                // Polars counts missing values as one more distinct value
+                let nulls = column.null_count();
+                let distinct = column.as_materialized_series().n_unique().ok();
+                let stats = Stats {
+                    nulls,
+                    distinct: distinct.map(|n| n - usize::from(nulls > 0)),
+                };
+                // The receiver is gone if the app quit meanwhile
+                if sender.send((generation, column.name().to_string(), stats)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Whether columns are still being counted
+    pub fn is_pending(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Take in the counts that finished since the last call
+    pub fn update(&mut self) {
+        while let Ok((generation, column, stats)) = self.results.try_recv() {
+            if generation == self.generation {
+                self.remaining = self.remaining.saturating_sub(1);
+                self.stats.insert(column, stats);
+            }
+        }
+    }
+
+    /// Counts of `column`, once they are done
+    pub fn get(&self, column: &str) -> Option<Stats> {
+        self.stats.get(column).copied()
+    }
+}
+
+/// Sum, mean and count of a numeric column's values, formatted for the status bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct Totals {
+    pub sum: String,
+    pub mean: String,
+    /// Values present
+    pub count: usize,
+}
+
+/// Decimal places of the mean in the totals when `--precision` is not given
+const TOTALS_PRECISION: usize = 4;
+
+/// Totals of `values`, or `None` unless they are numbers
+pub fn totals(values: &Series, format: &CellFormat) -> Result<Option<Totals>> {
+    if !values.dtype().is_numeric() {
+        return Ok(None);
+    }
+    let sum = values.sum_reduce()?;
+    let mean = values.mean().map_or(AnyValue::Null, AnyValue::Float64);
+    Ok(Some(Totals {
+        sum: format.format(sum.value()).unwrap_or_else(|| format.null.clone()),
+        mean: format
+            .with_default_precision(TOTALS_PRECISION)
+            .format(&mean)
+            .unwrap_or_else(|| format.null.clone()),
+        count: values.len() - values.null_count(),
+    }))
+}
+
+/// Quantiles of numeric columns listed in the column statistics
+const QUANTILES: [(&str, f64); 5] = [("5%", 0.05), ("25%", 0.25), ("median", 0.5), ("75%", 0.75), ("95%", 0.95)];
+
+/// Most frequent values listed in the column statistics of non-numeric columns
+const TOP_VALUES: usize = 5;
+
+/// Statistics of `column` of `df` for the statistics popup (`S`), as (label, value)
+/// lines: count and nulls, then the range, mean, spread and quantiles of numbers, the
+/// range of dates and times, or the distinct and most frequent values of anything else
+pub fn profile(df: &DataFrame, column: &str, format: &CellFormat) -> Result<Vec<(String, String)>> {
+    let values = df.column(column)?;
+    let rows = values.len();
+    let nulls = values.null_count();
+    let share = |n: usize| format!("{} ({:.1}%)", format_count(n), n as f64 * 100.0 / rows.max(1) as f64);
+    let mut lines = vec![
+        ("count".to_string(), format_count(rows - nulls)),
+        ("nulls".to_string(), share(nulls)),
+    ];
+    let text = |value: AnyValue| format.format(&value).unwrap_or_else(|| format.null.clone());
+
+    let dtype = values.dtype();
+    if dtype.is_numeric() || dtype.is_temporal() {
+        let mut stats = vec![col(column).min().alias("min"), col(column).max().alias("max")];
+        // Means and quantiles of dates would need a unit to make sense of
+        if dtype.is_numeric() {
+            stats.push(col(column).cast(DataType::Float64).mean().alias("mean"));
+            stats.push(col(column).cast(DataType::Float64).std(1).alias("std"));
+            for (label, quantile) in QUANTILES {
+                let expr = col(column).cast(DataType::Float64).quantile(lit(quantile), QuantileMethod::Linear);
+                stats.push(expr.alias(label));
+            }
+        }
+        let stats = df.clone().lazy().select(stats).collect()?;
+        for stat in stats.get_columns() {
+            lines.push((stat.name().to_string(), text(stat.get(0)?)));
+        }
+        return Ok(lines);
+    }
+
+    // Polars counts missing values as one more distinct value
+    let distinct = values.as_materialized_series().n_unique()? - usize::from(nulls > 0);
+    lines.push(("distinct".to_string(), format_count(distinct)));
+    let top = df
+        .clone()
+        .lazy()
+        .select([col(column)])
+        .drop_nulls(None)
+        .group_by([col(column)])
+        .agg([len().alias("count")])
+        .sort(["count"], SortMultipleOptions::default().with_order_descending(true))
+        .limit(TOP_VALUES as IdxSize)
+        .collect()?;
+    for i in 0..top.height() {
+        let value = format.full_text(&top.column(column)?.get(i)?);
+        let count = top.column("count")?.get(i)?.extract::<usize>().unwrap_or(0);
+        let label = if i == 0 { "top values" } else { "" };
+        lines.push((label.to_string(), format!("{}  {}", value, share(count))));
+    }
+    Ok(lines)
+}
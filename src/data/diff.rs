@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Name of the column holding each row's diff status
+pub const STATUS_COLUMN: &str = "__diff";
+
+/// Separator between the old and new value of a changed cell
+pub const CHANGE_MARKER: &str = " → ";
+
+/// Compare two DataFrames and merge them into one view.
+///
+/// Rows are paired by position, or by the value of `key` when given. The result holds the
+/// union of both column sets plus a leading `__diff` column that is `added`, `removed`,
+/// `changed` or `same`. Cells that differ read `old → new`; columns containing such cells
+/// become text, all other columns keep their type.
+pub fn diff_frames(old: &DataFrame, new: &DataFrame, key: Option<&str>) -> Result<DataFrame> {
+    let pairs = match key {
+        Some(key) => pair_by_key(old, new, key)?,
+        None => pair_by_index(old, new),
+    };
+
+    // Union of the columns, keeping the order of the old file first
+    let mut columns: Vec<String> = old.get_column_names().iter().map(|c| c.to_string()).collect();
+    for name in new.get_column_names() {
+        if !columns.iter().any(|c| c == name.as_str()) {
+            columns.push(name.to_string());
+        }
+    }
+
+    let cell = |df: &DataFrame, name: &str, row: Option<usize>| -> Option<AnyValue<'static>> {
+        let column = df.column(name).ok()?;
+        column.get(row?).ok().map(|v| v.into_static())
+    };
+
+    let mut changed_rows = vec![false; pairs.len()];
+    let mut output = Vec::with_capacity(columns.len() + 1);
+    for name in &columns {
+        let values: Vec<(Option<AnyValue>, Option<AnyValue>)> = pairs
+            .iter()
+            .map(|&(i, j)| (cell(old, name, i), cell(new, name, j)))
+            .collect();
+
+        let differs = |pair: &(Option<AnyValue>, Option<AnyValue>)| match pair {
+            (Some(a), Some(b)) => a.str_value() != b.str_value(),
+            _ => false,
+        };
+        let mut column_changed = false;
+        for (row, pair) in values.iter().enumerate() {
+            if differs(pair) {
+                changed_rows[row] = true;
+                column_changed = true;
+            }
+        }
+
+        let series = if column_changed {
+            let text: Vec<Option<String>> = values
+                .iter()
+                .map(|pair| match pair {
+                    (Some(a), Some(b)) if differs(pair) => {
+                        Some(format!("{}{}{}", a.str_value(), CHANGE_MARKER, b.str_value()))
+                    }
+                    (_, Some(v)) | (Some(v), None) if !v.is_null() => Some(v.str_value().to_string()),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name.as_str().into(), text)
+        } else {
+            let merged: Vec<AnyValue> = values
+                .into_iter()
+                .map(|(a, b)| b.or(a).unwrap_or(AnyValue::Null))
+                .collect();
+            Series::from_any_values(name.as_str().into(), &merged, false)
+                .with_context(|| format!("Failed to merge column '{}'", name))?
+        };
+        output.push(series.into());
+    }
+
+    let status: Vec<&str> = pairs
+        .iter()
+        .zip(&changed_rows)
+        .map(|(pair, &changed)| match pair {
+            (Some(_), None) => "removed",
+            (None, Some(_)) => "added",
+            _ if changed => "changed",
+            _ => "same",
+        })
+        .collect();
+    output.insert(0, Series::new(STATUS_COLUMN.into(), status).into());
+
+    DataFrame::new(output).context("Failed to build diff")
+}
+
+/// Pair row `i` of the old frame with row `i` of the new one
+fn pair_by_index(old: &DataFrame, new: &DataFrame) -> Vec<(Option<usize>, Option<usize>)> {
+    let rows = old.height().max(new.height());
+    (0..rows)
+        .map(|i| ((i < old.height()).then_some(i), (i < new.height()).then_some(i)))
+        .collect()
+}
+
+/// Pair rows with equal key values. Rows of the old frame come first in their original
+/// order, rows only found in the new frame are appended at the end.
+fn pair_by_key(old: &DataFrame, new: &DataFrame, key: &str) -> Result<Vec<(Option<usize>, Option<usize>)>> {
+    let old_keys = old
+        .column(key)
+        .with_context(|| format!("Key column '{}' not found in the first file", key))?;
+    let new_keys = new
+        .column(key)
+        .with_context(|| format!("Key column '{}' not found in the second file", key))?;
+
+    // Duplicate keys are matched in order of appearance
+    let mut index: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for j in 0..new.height() {
+        let value = new_keys.get(j)?.str_value().to_string();
+        index.entry(value).or_default().push_back(j);
+    }
+
+    let mut matched = vec![false; new.height()];
+    let mut pairs = Vec::with_capacity(old.height().max(new.height()));
+    for i in 0..old.height() {
+        let value = old_keys.get(i)?.str_value().to_string();
+        let j = index.get_mut(&value).and_then(|rows| rows.pop_front());
+        if let Some(j) = j {
+            matched[j] = true;
+        }
+        pairs.push((Some(i), j));
+    }
+    pairs.extend((0..new.height()).filter(|&j| !matched[j]).map(|j| (None, Some(j))));
+    Ok(pairs)
+}
@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use arrow_flight::{FlightClient, FlightDescriptor};
+use futures::TryStreamExt;
+use polars::prelude::*;
+use tonic::transport::Endpoint;
+
+/// Whether a source string is an Arrow Flight endpoint
+pub fn is_flight_url(source: &str) -> bool {
+    let lower = source.to_lowercase();
+    ["grpc://", "grpc+tcp://", "grpc+tls://"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+}
+
+/// gRPC URL in the `http://` form tonic expects
+fn channel_url(url: &str) -> Result<String> {
+    let (scheme, rest) = url.split_once("://").context("Invalid Flight URL")?;
+    match scheme.to_lowercase().as_str() {
+        "grpc" | "grpc+tcp" => Ok(format!("http://{}", rest)),
+        "grpc+tls" => anyhow::bail!("TLS Flight endpoints are not supported yet"),
+        other => anyhow::bail!("Unsupported Flight scheme '{}'", other),
+    }
+}
+
+/// Fetch the dataset at `path` (slash-separated descriptor path) from a Flight server.
+/// Every endpoint of the flight is read in turn and the record batches are collected
+/// into a single DataFrame.
+pub fn fetch(url: &str, path: &str) -> Result<DataFrame> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start async runtime")?;
+
+    let (schema, batches) = runtime.block_on(async {
+        let channel = Endpoint::from_shared(channel_url(url)?)?
+            .connect()
+            .await
+            .with_context(|| format!("Failed to connect to Flight server {}", url))?;
+        let mut client = FlightClient::new(channel);
+
+        let descriptor = FlightDescriptor::new_path(path.split('/').map(String::from).collect());
+        let info = client
+            .get_flight_info(descriptor)
+            .await
+            .with_context(|| format!("Flight '{}' not found", path))?;
+
+        let mut batches = Vec::new();
+        for endpoint in &info.endpoint {
+            let ticket = endpoint.ticket.clone().context("Flight endpoint has no ticket")?;
+            let stream = client.do_get(ticket).await.context("Failed to fetch flight data")?;
+            batches.extend(stream.try_collect::<Vec<_>>().await?);
+        }
+
+        // Prefer the schema the batches carry; empty results fall back to the advertised one
+        let schema = match batches.first() {
+            Some(batch) => batch.schema().as_ref().clone(),
+            None => info.try_decode_schema().context("Invalid flight schema")?,
+        };
+        anyhow::Ok((schema, batches))
+    })?;
+
+    super::record_batch::to_dataframe(&schema, &batches).context("Failed to read flight data")
+}
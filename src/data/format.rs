@@ -0,0 +1,181 @@
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use polars::prelude::*;
+
+/// Time zone datetimes are shown in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayZone {
+    /// The zone of this computer
+    Local,
+    /// A zone from the IANA database, like `Europe/Paris` or `UTC`
+    Named(Tz),
+}
+
+impl DisplayZone {
+    /// Parse `local` or an IANA zone name
+    pub fn parse(name: &str) -> Option<DisplayZone> {
+        if name.eq_ignore_ascii_case("local") {
+            Some(DisplayZone::Local)
+        } else {
+            name.parse().ok().map(DisplayZone::Named)
+        }
+    }
+}
+
+/// Days from 0001-01-01 to the Unix epoch, where polars counts dates from
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// How cell values are turned into text for display
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellFormat {
+    /// Shown in place of missing values
+    pub null: String,
+    /// Groups the digits of integers by thousands, if set
+    pub thousands_separator: Option<char>,
+    /// Decimal places of floats (as many as needed if `None`)
+    pub float_precision: Option<usize>,
+    /// Floats at least this large, or smaller than its inverse, use scientific notation
+    pub scientific_threshold: f64,
+    /// strftime format of dates
+    pub date_format: String,
+    /// strftime format of datetimes
+    pub datetime_format: String,
+    /// strftime format of times of day
+    pub time_format: String,
+    /// Zone datetimes are converted to; `None` shows them in their column's zone.
+    /// Datetimes without a zone are taken as UTC when converted.
+    pub timezone: Option<DisplayZone>,
+}
+
+impl Default for CellFormat {
+    fn default() -> Self {
+        Self {
+            null: "∅".to_string(),
+            thousands_separator: Some(','),
+            float_precision: None,
+            scientific_threshold: 1e15,
+            date_format: "%Y-%m-%d".to_string(),
+            datetime_format: "%Y-%m-%d %H:%M:%S%.f".to_string(),
+            time_format: "%H:%M:%S%.f".to_string(),
+            timezone: None,
+        }
+    }
+}
+
+impl CellFormat {
+    /// Text of a value, or `None` if it is missing
+    pub fn format(&self, value: &AnyValue) -> Option<String> {
+        let text = match value {
+            AnyValue::Null => return None,
+            AnyValue::Int8(v) => self.integer(v.to_string()),
+            AnyValue::Int16(v) => self.integer(v.to_string()),
+            AnyValue::Int32(v) => self.integer(v.to_string()),
+            AnyValue::Int64(v) => self.integer(v.to_string()),
+            AnyValue::UInt8(v) => self.integer(v.to_string()),
+            AnyValue::UInt16(v) => self.integer(v.to_string()),
+            AnyValue::UInt32(v) => self.integer(v.to_string()),
+            AnyValue::UInt64(v) => self.integer(v.to_string()),
+            AnyValue::Float32(v) => self.float(*v as f64),
+            AnyValue::Float64(v) => self.float(*v),
+            AnyValue::Date(days) => match NaiveDate::from_num_days_from_ce_opt(*days + EPOCH_DAYS_FROM_CE) {
+                Some(date) => date.format(&self.date_format).to_string(),
+                None => format!("{}", value),
+            },
+            AnyValue::Time(nanoseconds) => {
+                let (seconds, nanoseconds) = (nanoseconds / 1_000_000_000, nanoseconds % 1_000_000_000);
+                match NaiveTime::from_num_seconds_from_midnight_opt(seconds as u32, nanoseconds as u32) {
+                    Some(time) => time.format(&self.time_format).to_string(),
+                    None => format!("{}", value),
+                }
+            }
+            AnyValue::Datetime(timestamp, unit, zone) => self.datetime(*timestamp, *unit, zone.map(|z| z.as_str()))
+                .unwrap_or_else(|| format!("{}", value)),
+            AnyValue::DatetimeOwned(timestamp, unit, zone) => self
+                .datetime(*timestamp, *unit, zone.as_deref().map(|z| z.as_str()))
+                .unwrap_or_else(|| format!("{}", value)),
+            value => format!("{}", value),
+        };
+        Some(text)
+    }
+
+    /// This format with `precision` decimal places for floats, unless `--precision` set
+    /// them already; for figures worked out from values, like means, which have
+    /// fractions even when the values are whole
+    pub fn with_default_precision(&self, precision: usize) -> Self {
+        Self {
+            float_precision: Some(self.float_precision.unwrap_or(precision)),
+            ..self.clone()
+        }
+    }
+
+    /// Text of a value for the record view and value popup: unformatted, strings
+    /// without the quotes the table shows, missing values as the placeholder
+    pub fn full_text(&self, value: &AnyValue) -> String {
+        match value {
+            AnyValue::Null => self.null.clone(),
+            value => value.str_value().to_string(),
+        }
+    }
+
+    /// Digits of an integer, grouped by the thousands separator
+    fn integer(&self, digits: String) -> String {
+        let Some(separator) = self.thousands_separator else {
+            return digits;
+        };
+        let (sign, digits) = match digits.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", digits.as_str()),
+        };
+        let mut grouped = String::from(sign);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// A datetime in the display zone, or its column's `zone`, with the datetime format
+    fn datetime(&self, timestamp: i64, unit: TimeUnit, zone: Option<&str>) -> Option<String> {
+        let instant = match unit {
+            TimeUnit::Nanoseconds => DateTime::from_timestamp_nanos(timestamp),
+            TimeUnit::Microseconds => DateTime::from_timestamp_micros(timestamp)?,
+            TimeUnit::Milliseconds => DateTime::from_timestamp_millis(timestamp)?,
+        };
+        let format = &self.datetime_format;
+        let text = match (self.timezone, zone.and_then(|zone| zone.parse::<Tz>().ok())) {
+            (Some(DisplayZone::Local), _) => instant.with_timezone(&chrono::Local).format(format).to_string(),
+            (Some(DisplayZone::Named(tz)), _) | (None, Some(tz)) => instant.with_timezone(&tz).format(format).to_string(),
+            (None, None) => instant.naive_utc().format(format).to_string(),
+        };
+        Some(text)
+    }
+
+    /// A float with the configured precision, in scientific notation if very large or small
+    fn float(&self, value: f64) -> String {
+        let magnitude = value.abs();
+        let scientific = value.is_finite()
+            && magnitude != 0.0
+            && (magnitude >= self.scientific_threshold || magnitude < 1.0 / self.scientific_threshold);
+        match (scientific, self.float_precision) {
+            (true, Some(precision)) => format!("{:.*e}", precision, value),
+            (true, None) => format!("{:e}", value),
+            (false, Some(precision)) => format!("{:.*}", precision, value),
+            // Whole numbers keep a decimal point, so they still read as floats
+            (false, None) if value.is_finite() && value.fract() == 0.0 => format!("{:.1}", value),
+            (false, None) => format!("{}", value),
+        }
+    }
+}
+
+/// Short name of a data type for column headers, like `f64`, `str` or `datetime`
+pub fn type_label(dtype: &DataType) -> String {
+    match dtype {
+        DataType::Datetime(..) => "datetime".to_string(),
+        DataType::Duration(_) => "duration".to_string(),
+        DataType::List(_) => "list".to_string(),
+        DataType::Struct(_) => "struct".to_string(),
+        dtype => dtype.to_string(),
+    }
+}
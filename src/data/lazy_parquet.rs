@@ -0,0 +1,173 @@
+use super::paged::{PagedSource, COUNT_COLUMN};
+use crate::filter::{collect, FilterExpr};
+use crate::sort::SortOrder;
+use anyhow::{bail, Context, Result};
+use polars::prelude::*;
+use std::cell::RefCell;
+use std::path::Path;
+
+/// Parquet files and datasets at least this large on disk are scanned page by page
+/// instead of loaded, so files larger than memory can be browsed
+pub const LAZY_PARQUET_BYTES: u64 = 1 << 30;
+
+/// Total size of the Parquet files at `path`: a file, or a dataset directory
+pub fn parquet_size(path: &Path) -> u64 {
+    if path.is_file() {
+        return path.metadata().map_or(0, |m| m.len());
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries.flatten().map(|entry| parquet_size(&entry.path())).sum()
+}
+
+/// A Parquet file or dataset viewed page by page: only the rows on screen are read,
+/// with filters and sorts run by Polars while it scans the files
+pub struct LazyParquetSource {
+    scan: LazyFrame,
+    schema: SchemaRef,
+    /// Pushed-down filter
+    filter: Option<FilterExpr>,
+    /// Pushed-down sort, in priority order
+    sort: Vec<(String, SortOrder)>,
+    nulls_last: bool,
+    /// Row count for the current filter
+    row_count: usize,
+    /// Last fetched page, keyed by (offset, limit)
+    cache: RefCell<Option<(usize, usize, DataFrame)>>,
+}
+
+impl LazyParquetSource {
+    /// Scan the file, or the files of a dataset directory or glob with their hive-style
+    /// `key=value` directories as columns, reading only its metadata so far
+    pub fn open(path: &Path) -> Result<Self> {
+        let is_dataset = path.is_dir() || super::is_glob(path);
+        let args = ScanArgsParquet {
+            hive_options: polars::io::HiveOptions {
+                enabled: Some(is_dataset),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut scan = LazyFrame::scan_parquet(path, args).context("Failed to open Parquet file")?;
+        let schema = scan.collect_schema().context("Failed to read Parquet schema")?;
+
+        let mut source = Self {
+            scan,
+            schema,
+            filter: None,
+            sort: Vec::new(),
+            nulls_last: false,
+            row_count: 0,
+            cache: RefCell::new(None),
+        };
+        source.row_count = source.count_rows()?;
+        Ok(source)
+    }
+
+    /// The rows matching the filter, in sort order
+    fn view(&self) -> Result<LazyFrame> {
+        let mut lf = self.scan.clone();
+        if let Some(filter) = &self.filter {
+            lf = filter.filter_lazy(lf, &self.schema, false)?;
+        }
+        if !self.sort.is_empty() {
+            let columns: Vec<Expr> = self.sort.iter().map(|(name, _)| col(name.as_str())).collect();
+            let descending: Vec<bool> = self.sort.iter().map(|(_, order)| *order == SortOrder::Descending).collect();
+            let options = SortMultipleOptions::default()
+                .with_order_descending_multi(descending)
+                .with_nulls_last(self.nulls_last)
+                .with_maintain_order(true);
+            lf = lf.sort_by_exprs(columns, options);
+        }
+        Ok(lf.with_streaming(true))
+    }
+
+    fn count_rows(&self) -> Result<usize> {
+        let counted = collect(self.view()?.select([len()]))?;
+        Ok(counted.column("len")?.get(0)?.extract::<usize>().unwrap_or(0))
+    }
+}
+
+impl PagedSource for LazyParquetSource {
+    fn columns(&self) -> Vec<String> {
+        self.schema.iter_names().map(|name| name.to_string()).collect()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.row_count
+    }
+
+    fn fetch(&self, offset: usize, limit: usize) -> Result<DataFrame> {
+        // Pages within the last one (e.g. a single row of it) are cut from it
+        if let Some((cached_offset, cached_limit, df)) = self.cache.borrow().as_ref() {
+            if offset >= *cached_offset && offset + limit <= cached_offset + cached_limit {
+                return Ok(df.slice((offset - cached_offset) as i64, limit));
+            }
+        }
+
+        let df = collect(self.view()?.slice(offset as i64, limit as IdxSize)).context("Failed to fetch rows")?;
+        *self.cache.borrow_mut() = Some((offset, limit, df.clone()));
+        Ok(df)
+    }
+
+    fn value_counts(&self, column: &str, limit: usize) -> Result<DataFrame> {
+        if !self.schema.contains(column) {
+            bail!("Column '{}' not found", column);
+        }
+        // Counting needs no sort, so only the filter is applied to the scan
+        let mut lf = self.scan.clone();
+        if let Some(filter) = &self.filter {
+            lf = filter.filter_lazy(lf, &self.schema, false)?;
+        }
+        let counts = lf
+            .group_by([col(column)])
+            .agg([len().alias(COUNT_COLUMN)])
+            .sort(
+                [COUNT_COLUMN, column],
+                SortMultipleOptions::default().with_order_descending_multi([true, false]),
+            )
+            .limit(limit as IdxSize)
+            .with_streaming(true);
+        collect(counts).context("Failed to count values")
+    }
+
+    fn set_filter(&mut self, filter: Option<&FilterExpr>) -> Result<()> {
+        let previous = std::mem::replace(&mut self.filter, filter.cloned());
+        match self.count_rows() {
+            Ok(count) => {
+                self.row_count = count;
+                *self.cache.borrow_mut() = None;
+                Ok(())
+            }
+            Err(e) => {
+                self.filter = previous;
+                Err(e)
+            }
+        }
+    }
+
+    fn set_sort(&mut self, sort: &[(String, SortOrder)], nulls_last: bool) -> Result<()> {
+        for (column, _) in sort {
+            if !self.schema.contains(column) {
+                bail!("Column '{}' not found", column);
+            }
+        }
+        self.sort = sort.to_vec();
+        self.nulls_last = nulls_last;
+        *self.cache.borrow_mut() = None;
+        Ok(())
+    }
+
+    fn tables(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    fn select_table(&mut self, _table: &str) -> Result<()> {
+        bail!("Parquet files have no tables to switch between")
+    }
+}
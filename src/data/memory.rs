@@ -0,0 +1,92 @@
+use super::source::{DataSourceType, LoadOptions};
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Rows read from the start of a file to measure the memory a row takes
+const SAMPLE_ROWS: usize = 1000;
+
+/// How much memory a file would take once loaded, measured on its first rows
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryEstimate {
+    /// Rows in the whole file
+    pub total_rows: usize,
+    /// Whether `total_rows` is counted rather than estimated from the file size
+    pub exact: bool,
+    pub bytes_per_row: f64,
+}
+
+impl MemoryEstimate {
+    /// Estimate Parquet files from their metadata and uncompressed UTF-8 CSV files from
+    /// their size, `None` for other files and CSV files that cannot be sampled
+    pub fn of(path: &Path, source_type: DataSourceType, load_options: &LoadOptions) -> Result<Option<Self>> {
+        match source_type {
+            DataSourceType::Parquet => Self::of_parquet(path).map(Some),
+            DataSourceType::Csv => Ok(Self::of_csv(path, load_options)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The memory all rows would take
+    pub fn bytes(&self) -> f64 {
+        self.total_rows as f64 * self.bytes_per_row
+    }
+
+    /// Most rows that fit in `budget` bytes, at least one
+    pub fn rows_within(&self, budget: u64) -> usize {
+        ((budget as f64 / self.bytes_per_row.max(1.0)) as usize).max(1)
+    }
+
+    fn of_parquet(path: &Path) -> Result<Self> {
+        let is_dataset = path.is_dir() || super::is_glob(path);
+        let args = ScanArgsParquet {
+            hive_options: polars::io::HiveOptions {
+                enabled: Some(is_dataset),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let scan = LazyFrame::scan_parquet(path, args).context("Failed to open Parquet file")?;
+        let counted = scan.clone().select([len()]).collect().context("Failed to read Parquet metadata")?;
+        let total_rows = counted.column("len")?.get(0)?.extract::<usize>().unwrap_or(0);
+        let sample = scan
+            .slice(0, SAMPLE_ROWS as IdxSize)
+            .collect()
+            .context("Failed to read Parquet file")?;
+        Ok(Self {
+            total_rows,
+            exact: true,
+            bytes_per_row: sample.estimated_size() as f64 / sample.height().max(1) as f64,
+        })
+    }
+
+    /// Parse the first rows, and scale their text to the file size for the row count
+    fn of_csv(path: &Path, load_options: &LoadOptions) -> Option<Self> {
+        let file_size = path.metadata().ok()?.len();
+        let sample = load_options
+            .read_options()
+            .with_n_rows(Some(SAMPLE_ROWS))
+            .try_into_reader_with_file_path(Some(path.to_path_buf()))
+            .ok()?
+            .finish()
+            .ok()?;
+        let bytes_per_row = sample.estimated_size() as f64 / sample.height().max(1) as f64;
+        if sample.height() < SAMPLE_ROWS {
+            return Some(Self { total_rows: sample.height(), exact: true, bytes_per_row });
+        }
+
+        // Bytes of the lines the sample was parsed from; quoted line breaks make this
+        // a little high, which only makes the row count a little low
+        let lines = load_options.skip_rows + usize::from(load_options.has_header) + SAMPLE_ROWS;
+        let mut reader = BufReader::new(std::fs::File::open(path).ok()?);
+        let mut line = Vec::new();
+        let mut sample_bytes = 0;
+        for _ in 0..lines {
+            line.clear();
+            sample_bytes += reader.read_until(b'\n', &mut line).ok()?;
+        }
+        let total_rows = (file_size as f64 / sample_bytes.max(1) as f64 * SAMPLE_ROWS as f64) as usize;
+        Some(Self { total_rows, exact: false, bytes_per_row })
+    }
+}
@@ -1,5 +1,20 @@
+pub mod diff;
+pub mod flight;
+pub mod format;
+pub mod lazy_parquet;
+pub mod memory;
+pub mod paged;
+pub mod mysql;
+pub mod postgres;
+pub mod progress;
+pub mod record_batch;
+pub mod remote;
 pub mod source;
+pub mod sql_table;
+pub mod subset;
 pub mod table;
 
-pub use source::{DataSource, DataSourceType};
+pub use format::CellFormat;
+pub use progress::{BackgroundLoad, FileSummary, LoadProgress};
+pub use source::{is_glob, DataSource, LoadOptions, ENCODINGS};
 pub use table::TableData;
@@ -0,0 +1,43 @@
+use crate::filter::FilterExpr;
+use crate::sort::SortOrder;
+use anyhow::Result;
+use polars::prelude::*;
+
+/// Name of the count column of `PagedSource::value_counts`, which cannot clash with
+/// the value column it is next to
+pub const COUNT_COLUMN: &str = "__count";
+
+/// A table too large to load eagerly.
+/// Rows are fetched from the backend one page at a time, and filters are pushed down
+/// to the backend instead of running over an in-memory DataFrame.
+pub trait PagedSource: Send {
+    /// Column names in table order
+    fn columns(&self) -> Vec<String>;
+
+    /// Column types of the rows `fetch` returns, known from when the table was opened
+    fn schema(&self) -> SchemaRef;
+
+    /// Number of rows matching the current filter
+    fn len(&self) -> usize;
+
+    /// Fetch up to `limit` rows starting at `offset`
+    fn fetch(&self, offset: usize, limit: usize) -> Result<DataFrame>;
+
+    /// Distinct values of `column` among the rows matching the filter, missing ones
+    /// included, with how often they occur as `COUNT_COLUMN`: the `limit` most frequent,
+    /// most frequent first, counted by the backend
+    fn value_counts(&self, column: &str, limit: usize) -> Result<DataFrame>;
+
+    /// Restrict the rows returned by `fetch` (None removes the filter)
+    fn set_filter(&mut self, filter: Option<&FilterExpr>) -> Result<()>;
+
+    /// Order the rows returned by `fetch` by columns in priority order
+    /// (empty restores the backend's order), with NULLs before or after the other values
+    fn set_sort(&mut self, sort: &[(String, SortOrder)], nulls_last: bool) -> Result<()>;
+
+    /// Names of the tables the backend can switch between
+    fn tables(&self) -> Result<Vec<String>>;
+
+    /// Switch to another table, clearing any filter
+    fn select_table(&mut self, table: &str) -> Result<()>;
+}
@@ -0,0 +1,200 @@
+use super::paged::PagedSource;
+use super::sql_table::{ColumnKind, SqlTable};
+use crate::filter::{FilterExpr, SqlDialect};
+use crate::sort::SortOrder;
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use postgres::types::Type;
+use postgres::{Client, NoTls};
+use std::cell::RefCell;
+
+/// Whether a source string is a PostgreSQL connection URL
+pub fn is_postgres_url(source: &str) -> bool {
+    let lower = source.to_lowercase();
+    lower.starts_with("postgres://") || lower.starts_with("postgresql://")
+}
+
+/// How values of a column of type `ty` are transferred and stored
+fn column_kind(ty: &Type) -> ColumnKind {
+    match *ty {
+        Type::BOOL => ColumnKind::Bool,
+        Type::INT2 | Type::INT4 | Type::INT8 => ColumnKind::Int,
+        Type::FLOAT4 | Type::FLOAT8 | Type::NUMERIC => ColumnKind::Float,
+        Type::DATE => ColumnKind::Date,
+        Type::TIMESTAMP => ColumnKind::Datetime,
+        Type::TIMESTAMPTZ => ColumnKind::DatetimeUtc,
+        Type::TIME => ColumnKind::Time,
+        _ => ColumnKind::Text,
+    }
+}
+
+/// A PostgreSQL table viewed page by page with LIMIT/OFFSET
+pub struct PostgresSource {
+    client: RefCell<Client>,
+    table: SqlTable,
+}
+
+impl PostgresSource {
+    /// Connect using a `postgres://user@host/db?table=name` URL, opening `table` instead
+    /// of the URL's when given. Returns the source and whether a table was named; without
+    /// one the first table in the database is opened.
+    pub fn connect(url: &str, table: Option<&str>) -> Result<(Self, bool)> {
+        let (conn_url, url_table) = split_table_param(url);
+        let table = table.map(str::to_string).or(url_table);
+        let client = Client::connect(&conn_url, NoTls).context("Failed to connect to PostgreSQL")?;
+
+        let mut source = Self {
+            client: RefCell::new(client),
+            table: SqlTable::new(SqlDialect::Postgres),
+        };
+
+        let explicit = table.is_some();
+        let table = match table {
+            Some(table) => table,
+            None => source
+                .tables()?
+                .into_iter()
+                .next()
+                .context("Database contains no tables")?,
+        };
+        source.select_table(&table)?;
+        Ok((source, explicit))
+    }
+
+    fn query(&self, sql: &str) -> Result<Vec<postgres::Row>> {
+        Ok(self.client.borrow_mut().query(sql, &[])?)
+    }
+}
+
+impl PagedSource for PostgresSource {
+    fn columns(&self) -> Vec<String> {
+        self.table.columns()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema()
+    }
+
+    fn len(&self) -> usize {
+        self.table.row_count()
+    }
+
+    fn fetch(&self, offset: usize, limit: usize) -> Result<DataFrame> {
+        self.table.fetch(offset, limit, |sql, columns| {
+            frame(&self.query(sql).context("Failed to fetch rows")?, columns)
+        })
+    }
+
+    fn value_counts(&self, column: &str, limit: usize) -> Result<DataFrame> {
+        let (sql, columns) = self.table.value_counts_sql(column, limit)?;
+        frame(&self.query(&sql).context("Failed to count values")?, &columns)
+    }
+
+    fn set_filter(&mut self, filter: Option<&FilterExpr>) -> Result<()> {
+        let client = &self.client;
+        self.table.set_filter(filter, |sql| count_rows(client, sql))
+    }
+
+    fn set_sort(&mut self, sort: &[(String, SortOrder)], nulls_last: bool) -> Result<()> {
+        self.table.set_sort(sort, nulls_last)
+    }
+
+    fn tables(&self) -> Result<Vec<String>> {
+        let rows = self.client.borrow_mut().query(
+            "SELECT table_schema, table_name FROM information_schema.tables \
+             WHERE table_schema NOT IN ('pg_catalog', 'information_schema') \
+             ORDER BY table_schema, table_name",
+            &[],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let schema: String = row.get(0);
+                let table: String = row.get(1);
+                if schema == "public" {
+                    table
+                } else {
+                    format!("{}.{}", schema, table)
+                }
+            })
+            .collect())
+    }
+
+    fn select_table(&mut self, table: &str) -> Result<()> {
+        let relation = self.table.relation_of(table);
+
+        // An empty result still describes the columns
+        let statement = self
+            .client
+            .borrow_mut()
+            .prepare(&SqlTable::describe_sql(&relation))
+            .with_context(|| format!("Failed to open table '{}'", table))?;
+        let columns = statement
+            .columns()
+            .iter()
+            .map(|c| (c.name().to_string(), column_kind(c.type_())))
+            .collect();
+        let client = &self.client;
+        self.table.open(relation, columns, |sql| count_rows(client, sql))
+    }
+}
+
+/// Rows counted by `sql`, a `SELECT COUNT(*)`
+fn count_rows(client: &RefCell<Client>, sql: &str) -> Result<usize> {
+    let row = client.borrow_mut().query_one(sql, &[])?;
+    Ok(row.get::<_, i64>(0) as usize)
+}
+
+/// Decode rows of `columns`, in that order, into a DataFrame
+fn frame(rows: &[postgres::Row], columns: &[(String, ColumnKind)]) -> Result<DataFrame> {
+    let columns = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (name, kind))| {
+            let name = PlSmallStr::from(name.as_str());
+            let series = match kind {
+                ColumnKind::Bool => {
+                    Series::new(name, rows.iter().map(|r| r.get::<_, Option<bool>>(i)).collect::<Vec<_>>())
+                }
+                ColumnKind::Int => {
+                    Series::new(name, rows.iter().map(|r| r.get::<_, Option<i64>>(i)).collect::<Vec<_>>())
+                }
+                ColumnKind::Float => {
+                    Series::new(name, rows.iter().map(|r| r.get::<_, Option<f64>>(i)).collect::<Vec<_>>())
+                }
+                ColumnKind::Date | ColumnKind::Datetime | ColumnKind::DatetimeUtc | ColumnKind::Time => {
+                    kind.temporal(name, rows.iter().map(|r| r.get::<_, Option<i64>>(i)).collect())?
+                }
+                ColumnKind::Text => {
+                    Series::new(name, rows.iter().map(|r| r.get::<_, Option<String>>(i)).collect::<Vec<_>>())
+                }
+            };
+            Ok(Column::from(series))
+        })
+        .collect::<Result<_>>()?;
+    DataFrame::new(columns).context("Failed to build DataFrame from rows")
+}
+
+/// Split the viewer's `table=` parameter off a connection URL
+fn split_table_param(url: &str) -> (String, Option<String>) {
+    let Some((base, query)) = url.split_once('?') else {
+        return (url.to_string(), None);
+    };
+
+    let mut table = None;
+    let mut rest = Vec::new();
+    for param in query.split('&') {
+        match param.strip_prefix("table=") {
+            Some(name) => table = Some(super::remote::percent_decode(name)),
+            None if !param.is_empty() => rest.push(param),
+            None => {}
+        }
+    }
+
+    let conn_url = if rest.is_empty() {
+        base.to_string()
+    } else {
+        format!("{}?{}", base, rest.join("&"))
+    };
+    (conn_url, table)
+}
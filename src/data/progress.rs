@@ -0,0 +1,131 @@
+use super::DataSource;
+use anyhow::Result;
+use polars::prelude::DataFrame;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How far a load has got, updated by the thread loading and read by the loading screen
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    /// Bytes of a remote file received so far
+    downloaded: AtomicU64,
+    /// Size of the remote file, 0 while unknown
+    download_size: AtomicU64,
+    /// Whether a remote file is being downloaded
+    downloading: AtomicBool,
+    /// CSV rows parsed so far
+    rows: AtomicUsize,
+    /// Whether parsed rows are handed out while the rest of the file loads
+    streaming: AtomicBool,
+    /// Parsed rows not yet taken by the viewer
+    parsed: Mutex<Vec<DataFrame>>,
+    /// Size and schema of the file, when its metadata tells them ahead of the rows
+    summary: Mutex<Option<FileSummary>>,
+}
+
+/// Row count and columns of a file, read from its metadata before the rows are loaded
+#[derive(Debug, Clone)]
+pub struct FileSummary {
+    pub rows: usize,
+    /// Column names and types
+    pub columns: Vec<(String, String)>,
+}
+
+impl LoadProgress {
+    pub fn set_download(&self, downloaded: u64, total: Option<u64>) {
+        self.downloading.store(true, Ordering::Relaxed);
+        self.downloaded.store(downloaded, Ordering::Relaxed);
+        self.download_size.store(total.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Bytes received and the total size when known, once a download started
+    pub fn download(&self) -> Option<(u64, Option<u64>)> {
+        if !self.downloading.load(Ordering::Relaxed) {
+            return None;
+        }
+        let total = self.download_size.load(Ordering::Relaxed);
+        Some((self.downloaded.load(Ordering::Relaxed), (total > 0).then_some(total)))
+    }
+
+    /// Count a batch of parsed rows, keeping it for `take_rows` when streaming
+    pub fn add_rows(&self, rows: &DataFrame) {
+        self.rows.fetch_add(rows.height(), Ordering::Relaxed);
+        if self.streaming.load(Ordering::Relaxed) {
+            self.parsed.lock().unwrap_or_else(|e| e.into_inner()).push(rows.clone());
+        }
+    }
+
+    /// Rows parsed so far, for sources read in batches
+    pub fn rows(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    /// The batches parsed since the last call, empty unless streaming
+    pub fn take_rows(&self) -> Vec<DataFrame> {
+        std::mem::take(&mut *self.parsed.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub fn set_summary(&self, summary: FileSummary) {
+        *self.summary.lock().unwrap_or_else(|e| e.into_inner()) = Some(summary);
+    }
+
+    /// Row count and columns, once read from the file's metadata
+    pub fn summary(&self) -> Option<FileSummary> {
+        self.summary.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// A load running on a worker thread
+pub struct BackgroundLoad {
+    progress: Arc<LoadProgress>,
+    result: Receiver<Result<DataSource>>,
+    started: Instant,
+}
+
+impl BackgroundLoad {
+    /// Start `load`. With `streaming`, CSV rows can be taken from the progress as they
+    /// are parsed, so the first of them can be shown before the whole file is in.
+    pub fn spawn(
+        streaming: bool,
+        load: impl FnOnce(&LoadProgress) -> Result<DataSource> + Send + 'static,
+    ) -> Self {
+        let progress = Arc::new(LoadProgress::default());
+        progress.streaming.store(streaming, Ordering::Relaxed);
+        let (sender, result) = channel();
+        let worker_progress = Arc::clone(&progress);
+        std::thread::spawn(move || {
+            // The receiver is gone if loading was cancelled meanwhile
+            let _ = sender.send(load(&worker_progress));
+        });
+        Self { progress, result, started: Instant::now() }
+    }
+
+    pub fn progress(&self) -> &LoadProgress {
+        &self.progress
+    }
+
+    /// Time since the load started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Wait up to `timeout` for the load to finish
+    pub fn wait(&self, timeout: Duration) -> Option<Result<DataSource>> {
+        match self.result.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(anyhow::anyhow!("Loading crashed"))),
+        }
+    }
+
+    /// The result, once the load finished
+    pub fn try_finish(&self) -> Option<Result<DataSource>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(anyhow::anyhow!("Loading crashed"))),
+        }
+    }
+}
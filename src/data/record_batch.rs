@@ -0,0 +1,23 @@
+use anyhow::{Context, Result};
+use arrow::datatypes::Schema;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use polars::prelude::*;
+use std::io::Cursor;
+
+/// Convert Arrow record batches (from arrow-rs based readers) into a Polars DataFrame.
+/// The batches are handed over in the Arrow IPC stream format, which both libraries speak.
+pub fn to_dataframe(schema: &Schema, batches: &[RecordBatch]) -> Result<DataFrame> {
+    let mut buffer = Vec::new();
+    {
+        let mut writer = StreamWriter::try_new(&mut buffer, schema)?;
+        for batch in batches {
+            writer.write(batch)?;
+        }
+        writer.finish()?;
+    }
+
+    IpcStreamReader::new(Cursor::new(buffer))
+        .finish()
+        .context("Failed to convert Arrow data")
+}
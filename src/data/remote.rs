@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// Whether a source string is an HTTP(S) URL
+pub fn is_remote(source: &str) -> bool {
+    let lower = source.to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Whether a source string is an object-store URI (S3, Google Cloud Storage or Azure)
+/// Those are scanned directly by Polars instead of being downloaded first.
+pub fn is_cloud(source: &str) -> bool {
+    const SCHEMES: [&str; 8] = ["s3://", "s3a://", "gs://", "gcs://", "az://", "azure://", "abfs://", "abfss://"];
+    let lower = source.to_lowercase();
+    SCHEMES.iter().any(|scheme| lower.starts_with(scheme))
+}
+
+/// A URL component with its `%XX` escapes decoded, e.g. `my%20table` as `my table`
+pub fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        let escaped = after.get(..2).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(value) if byte == b'%' => {
+                bytes.push(value);
+                rest = &after[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = after;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// File name at the end of a URL, ignoring any query string or fragment
+fn url_file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/').find(|s| !s.is_empty()).unwrap_or("download")
+}
+
+/// Download a remote file into the temp directory, reporting progress as it goes.
+/// The callback receives the bytes downloaded so far and the total size, if the server sent one.
+/// The local copy keeps the URL's file name so its format can be detected from the extension.
+pub fn download(url: &str, mut progress: impl FnMut(u64, Option<u64>)) -> Result<PathBuf> {
+    let response = ureq::get(url)
+        .call()
+        .with_context(|| format!("Failed to fetch {}", url))?;
+
+    let body = response.into_body();
+    let total = body.content_length();
+    let mut reader = body.into_reader();
+
+    let local_path = std::env::temp_dir().join(format!(
+        "rata_remote_{}_{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_micros(),
+        url_file_name(url)
+    ));
+    let mut file = std::fs::File::create(&local_path).context("Failed to create download file")?;
+
+    let result = (|| -> Result<()> {
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut downloaded = 0u64;
+        progress(downloaded, total);
+        loop {
+            let n = reader.read(&mut buffer).context("Download interrupted")?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buffer[..n]).context("Failed to write download file")?;
+            downloaded += n as u64;
+            progress(downloaded, total);
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&local_path);
+        return Err(e);
+    }
+
+    Ok(local_path)
+}
@@ -1,137 +1,1051 @@
 use anyhow::{Context, Result};
+use encoding_rs::Encoding;
 use polars::prelude::*;
-use std::path::Path;
-use crate::filter::FilterExpr;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use super::paged::PagedSource;
+use super::progress::LoadProgress;
+use super::subset::{Partial, RowSubset};
+use crate::filter::{FilterExpr, FilterOptions};
+use crate::sort::SortOrder;
 
 /// Supported data source types
 #[derive(Debug, Clone, Copy)]
 pub enum DataSourceType {
     Parquet,
+    Orc,
     Csv,
+    Json,
+    NdJson,
+    Excel,
     Sqlite,
+    Postgres,
+    MySql,
+    /// Merged comparison of two sources (`--diff`)
+    Diff,
+    /// Several sources stacked into one table (`--concat`)
+    Concat,
+    /// Result set fetched from an Arrow Flight server (`--flight`)
+    Flight,
 }
 
 impl DataSourceType {
     /// Detect data source type from file extension
+    /// A trailing compression extension is skipped, so `trades.csv.gz` is detected as CSV.
+    /// Directories are treated as partitioned Parquet datasets, `postgres://` and `mysql://` URLs as databases.
     pub fn from_path(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            return Some(DataSourceType::Parquet);
+        }
+        if super::postgres::is_postgres_url(&path.to_string_lossy()) {
+            return Some(DataSourceType::Postgres);
+        }
+        if super::mysql::is_mysql_url(&path.to_string_lossy()) {
+            return Some(DataSourceType::MySql);
+        }
+
+        let path = match Compression::from_extension(path) {
+            Compression::None => path.to_path_buf(),
+            _ => path.with_extension(""),
+        };
+
         path.extension()
             .and_then(|ext| ext.to_str())
             .and_then(|ext| match ext.to_lowercase().as_str() {
                 "parquet" => Some(DataSourceType::Parquet),
+                "orc" => Some(DataSourceType::Orc),
                 "csv" => Some(DataSourceType::Csv),
+                "json" => Some(DataSourceType::Json),
+                "ndjson" | "jsonl" => Some(DataSourceType::NdJson),
+                "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => Some(DataSourceType::Excel),
                 "db" | "sqlite" | "sqlite3" => Some(DataSourceType::Sqlite),
                 _ => None,
             })
     }
+
+    /// What the named tables of this source are called in the UI
+    pub fn table_label(&self) -> &'static str {
+        match self {
+            DataSourceType::Excel => "Sheet",
+            _ => "Table",
+        }
+    }
+}
+
+/// Column added by `--concat` naming the file each row came from
+pub const CONCAT_SOURCE_COLUMN: &str = "__source_file";
+
+/// Options controlling how files are loaded: how CSV files are parsed, and for files of
+/// any format which rows, sheet or table are opened and how they are stored
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Whether the first row holds the column names; without one they are named `column_1`, `column_2`, ...
+    pub has_header: bool,
+    /// Character used to quote fields, `None` to disable quoting
+    pub quote_char: Option<u8>,
+    /// Lines starting with this character are skipped
+    pub comment_char: Option<u8>,
+    /// Number of lines to skip before the header (or first row)
+    pub skip_rows: usize,
+    /// Column types to parse as instead of inferring them (`--dtype`)
+    pub dtypes: Vec<(String, DataType)>,
+    /// Rows read to infer the column types from, `None` for the whole file
+    pub infer_schema_rows: Option<usize>,
+    /// Text encoding of the file, `None` to detect it
+    pub encoding: Option<&'static Encoding>,
+    /// Most memory in bytes the loaded rows of a Parquet or CSV file may take; larger
+    /// files load only as many of their first rows as fit
+    pub max_memory: Option<u64>,
+    /// Rows of Parquet, CSV and other files to load instead of all of them
+    pub rows: Option<RowSubset>,
+    /// Store string columns that mostly repeat a few values as categoricals, for files
+    /// of any format
+    pub categorical: bool,
+    /// Sheet or database table to open instead of asking which (`--table`)
+    pub table: Option<String>,
+    /// SQL run by the database, whose result is opened instead of a table (`--query`)
+    pub query: Option<String>,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            quote_char: Some(b'"'),
+            comment_char: None,
+            skip_rows: 0,
+            dtypes: Vec::new(),
+            infer_schema_rows: Some(100),
+            encoding: None,
+            categorical: false,
+            max_memory: None,
+            rows: None,
+            table: None,
+            query: None,
+        }
+    }
+}
+
+impl LoadOptions {
+    /// Polars CSV reader options for these settings
+    pub(super) fn read_options(&self) -> CsvReadOptions {
+        let comment_prefix = self.comment_char.map(CommentPrefix::Single);
+        CsvReadOptions::default()
+            .with_has_header(self.has_header)
+            .with_skip_rows(self.skip_rows)
+            .with_infer_schema_length(self.infer_schema_rows)
+            .with_schema_overwrite(self.schema_overwrite())
+            .map_parse_options(|parse| {
+                parse
+                    .with_quote_char(self.quote_char)
+                    .with_comment_prefix(comment_prefix.clone())
+            })
+    }
+
+    /// The `--dtype` column types as a schema for the reader to overwrite inferred ones with
+    pub(super) fn schema_overwrite(&self) -> Option<SchemaRef> {
+        if self.dtypes.is_empty() {
+            return None;
+        }
+        let fields = self.dtypes.iter().map(|(name, dtype)| Field::new(name.into(), dtype.clone()));
+        Some(Arc::new(Schema::from_iter(fields)))
+    }
+
+    /// Fail on `--dtype` columns the file does not have, which the reader passes over
+    pub(super) fn check_dtypes(&self, df: &DataFrame) -> Result<()> {
+        let names = df.get_column_names_str();
+        match self.dtypes.iter().find(|(name, _)| !names.contains(&name.as_str())) {
+            Some((name, _)) => anyhow::bail!("--dtype names column '{}', expected one of {}", name, names.join(", ")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// String columns with at most this share of distinct values are stored as
+/// categoricals with `LoadOptions::categorical`
+const CATEGORICAL_MAX_DISTINCT: f64 = 0.5;
+
+/// Row positions added while sampling a Parquet scan
+const SAMPLE_POSITION: &str = "__sample_position";
+
+/// Cast the string columns of `df` that mostly repeat a few values to categoricals,
+/// which store each distinct value once. They sort by their text, as before.
+fn encode_categorical(mut df: DataFrame) -> Result<DataFrame> {
+    let max_distinct = (df.height() as f64 * CATEGORICAL_MAX_DISTINCT) as usize;
+    let categorical = DataType::Categorical(None, CategoricalOrdering::Lexical);
+    let repetitive: Vec<String> = df
+        .get_columns()
+        .iter()
+        .filter(|column| column.dtype() == &DataType::String)
+        .filter(|column| column.as_materialized_series().n_unique().is_ok_and(|distinct| distinct <= max_distinct))
+        .map(|column| column.name().to_string())
+        .collect();
+    for name in repetitive {
+        let cast = df.column(&name)?.cast(&categorical)?;
+        df.with_column(cast)?;
+    }
+    Ok(df)
+}
+
+/// Whether a path is a glob pattern (e.g. `data/*.parquet`) rather than a single file
+pub fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Compression wrapping a data file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Detect compression from the last file extension
+    fn from_extension(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .as_deref()
+        {
+            Some("gz") | Some("gzip") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// Detect compression from the extension, falling back to the file's magic bytes
+    fn detect(path: &Path) -> Result<Self> {
+        let by_extension = Self::from_extension(path);
+        if by_extension != Compression::None {
+            return Ok(by_extension);
+        }
+
+        let mut magic = [0u8; 4];
+        let mut file = std::fs::File::open(path).context("Failed to open file")?;
+        let n = file.read(&mut magic).context("Failed to read file")?;
+        Ok(match &magic[..n] {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x28, 0xb5, 0x2f, 0xfd] => Compression::Zstd,
+            _ => Compression::None,
+        })
+    }
+}
+
+/// Encodings offered by the encoding picker
+pub const ENCODINGS: [&Encoding; 14] = [
+    encoding_rs::UTF_8,
+    encoding_rs::GBK,
+    encoding_rs::GB18030,
+    encoding_rs::BIG5,
+    encoding_rs::SHIFT_JIS,
+    encoding_rs::EUC_JP,
+    encoding_rs::EUC_KR,
+    encoding_rs::WINDOWS_1252,
+    encoding_rs::ISO_8859_2,
+    encoding_rs::WINDOWS_1250,
+    encoding_rs::WINDOWS_1251,
+    encoding_rs::KOI8_R,
+    encoding_rs::UTF_16LE,
+    encoding_rs::UTF_16BE,
+];
+
+/// Guess the text encoding of the start of a file
+fn detect_encoding(buffer: &[u8]) -> &'static Encoding {
+    use encoding_rs::*;
+
+    if let Some((enc, _bom_len)) = Encoding::for_bom(buffer) {
+        return enc;
+    }
+
+    // No BOM, try to detect
+    // Common encodings: UTF-8, GBK (Chinese), GB18030, ISO-8859-1
+    if std::str::from_utf8(buffer).is_ok() {
+        UTF_8
+    } else {
+        // Try GBK for Chinese systems
+        let (decoded, _, had_errors) = GBK.decode(buffer);
+        if !had_errors && !decoded.is_empty() {
+            GBK
+        } else {
+            // Try GB18030
+            let (decoded, _, had_errors) = GB18030.decode(buffer);
+            if !had_errors && !decoded.is_empty() {
+                GB18030
+            } else {
+                // Fallback to WINDOWS_1252 (similar to ISO-8859-1)
+                WINDOWS_1252
+            }
+        }
+    }
+}
+
+/// Parse a CSV file a few chunks at a time, counting the rows in `progress` as they
+/// are parsed, so the loading screen can show how far it got. With `keep`, only the
+/// rows at those ascending positions are returned.
+fn read_csv_batches<R: polars::io::mmap::MmapBytesReader>(
+    mut reader: CsvReader<R>,
+    progress: &LoadProgress,
+    keep: Option<&[IdxSize]>,
+) -> PolarsResult<DataFrame> {
+    // One chunk per thread at a time, as a full read would parse them
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut df: Option<DataFrame> = None;
+    {
+        let mut batched = reader.batched_borrowed()?;
+        let mut offset = 0;
+        while let Some(chunks) = batched.next_batches(threads)? {
+            for chunk in chunks {
+                // Only the rows at `keep` are held on to, when given
+                let height = chunk.height();
+                let chunk = match keep {
+                    Some(positions) => super::subset::keep_rows(&chunk, offset, positions)?,
+                    None => chunk,
+                };
+                offset += height;
+                progress.add_rows(&chunk);
+                match df.as_mut() {
+                    Some(df) => {
+                        df.vstack_mut(&chunk)?;
+                    }
+                    None => df = Some(chunk),
+                }
+            }
+        }
+    }
+    match df {
+        Some(mut df) => {
+            df.as_single_chunk_par();
+            Ok(df)
+        }
+        // Files without rows still have their columns, which only a full read returns
+        None => reader.finish(),
+    }
+}
+
+/// Open a file for reading, transparently decompressing gzip/zstd content
+fn open_decompressed(path: &Path) -> Result<Box<dyn Read>> {
+    let file = std::fs::File::open(path).context("Failed to open file")?;
+    Ok(match Compression::detect(path)? {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        Compression::Zstd => Box::new(
+            zstd::stream::read::Decoder::new(file).context("Failed to open zstd stream")?,
+        ),
+    })
 }
 
 /// Data source abstraction for loading different file formats
 pub struct DataSource {
     df: DataFrame,
     source_type: DataSourceType,
+    /// Path the data was loaded from (needed to reload another table)
+    path: PathBuf,
+    /// Named tables inside the source (workbook sheets, database tables), empty for flat files
+    tables: Vec<String>,
+    /// Table currently loaded into `df`
+    current_table: Option<String>,
+    /// Local copy of a downloaded file, removed again when the source is dropped
+    temp_file: Option<PathBuf>,
+    /// Backend serving rows page by page; when set, `df` is empty and unused
+    paged: Option<Box<dyn PagedSource>>,
+    /// Options the source was loaded with, reused when it is reloaded
+    load_options: LoadOptions,
+    /// Encoding a local CSV file was decoded with
+    encoding: Option<&'static Encoding>,
+    /// Which rows of the file were loaded, when not all of them
+    partial: Option<Partial>,
 }
 
 impl DataSource {
-    /// Load data from a file
-    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+    /// Load data from a file according to `load_options`
+    pub fn load(path: impl AsRef<Path>, load_options: &LoadOptions) -> Result<Self> {
+        Self::load_with_progress(path, load_options, &LoadProgress::default())
+    }
+
+    /// Load a file, counting the rows parsed so far in `progress` where the format allows
+    pub fn load_with_progress(path: impl AsRef<Path>, load_options: &LoadOptions, progress: &LoadProgress) -> Result<Self> {
         let path = path.as_ref();
+        if super::remote::is_cloud(&path.to_string_lossy()) {
+            if load_options.table.is_some() || load_options.query.is_some() {
+                anyhow::bail!("--table and --query only apply to workbooks and databases");
+            }
+            return Self::load_cloud(path, load_options);
+        }
+
         let source_type = DataSourceType::from_path(path)
-            .context("Unsupported file type. Supported: .parquet, .csv, .db")?;
+            .context("Unsupported file type. Supported: .parquet, .orc, .csv, .json, .ndjson, .jsonl, .xlsx, .xls, .db")?;
+        match source_type {
+            DataSourceType::Excel if load_options.query.is_some() => {
+                anyhow::bail!("--query runs on SQLite and MySQL databases; pick a sheet with --table")
+            }
+            DataSourceType::Postgres if load_options.query.is_some() => {
+                anyhow::bail!("--query runs on SQLite and MySQL databases; open a PostgreSQL table with --table")
+            }
+            DataSourceType::Excel | DataSourceType::Sqlite | DataSourceType::Postgres | DataSourceType::MySql => {}
+            _ if load_options.table.is_some() || load_options.query.is_some() => {
+                anyhow::bail!("--table and --query only apply to workbooks and databases")
+            }
+            _ => {}
+        }
 
-        let df = match source_type {
-            DataSourceType::Parquet => Self::load_parquet(path)?,
-            DataSourceType::Csv => Self::load_csv(path)?,
-            DataSourceType::Sqlite => Self::load_sqlite(path)?,
+        if let DataSourceType::Postgres | DataSourceType::MySql = source_type {
+            if load_options.rows.is_some() {
+                anyhow::bail!("--head, --tail and --sample only apply to files");
+            }
+            return Self::load_database(path, source_type, load_options);
+        }
+        // Only a subset of the rows is small enough to load, and `--max-memory` and
+        // `--categorical` ask for the file to be loaded to keep it small
+        if matches!(source_type, DataSourceType::Parquet)
+            && load_options.rows.is_none()
+            && load_options.max_memory.is_none()
+            && !load_options.categorical
+            && super::lazy_parquet::parquet_size(path) >= super::lazy_parquet::LAZY_PARQUET_BYTES
+        {
+            return Self::scan_parquet(path);
+        }
+
+        let mut tables = Vec::new();
+        let mut current_table = None;
+        let mut encoding = None;
+
+        if is_glob(path) && !matches!(source_type, DataSourceType::Parquet) {
+            anyhow::bail!("Glob patterns are only supported for Parquet datasets");
+        }
+
+        let compressed = path.is_file() && Compression::detect(path)? != Compression::None;
+        if compressed && !matches!(source_type, DataSourceType::Csv | DataSourceType::Json | DataSourceType::NdJson) {
+            anyhow::bail!("Compressed files are only supported for CSV and JSON");
+        }
+
+        // Files that would not fit in the memory budget load only their first rows, also
+        // the estimate of the file's rows when they are not all loaded
+        let estimate = match (load_options.max_memory, load_options.rows) {
+            (None, None) => None,
+            _ if compressed => None,
+            _ => super::memory::MemoryEstimate::of(path, source_type, load_options)?,
+        };
+        let mut budget = None;
+        let subset = match (load_options.rows, load_options.max_memory, estimate) {
+            (Some(subset), _, _) => Some(subset),
+            (None, Some(max_memory), Some(estimate)) => {
+                let rows = estimate.rows_within(max_memory);
+                let over = estimate.bytes() > max_memory as f64 && rows < estimate.total_rows;
+                budget = over.then_some(max_memory);
+                over.then_some(RowSubset::Head(rows))
+            }
+            _ => None,
+        };
+        // Rows of the whole file, when counted while loading
+        let mut total_rows = None;
+
+        let mut df = match source_type {
+            DataSourceType::Parquet => {
+                let (df, total) = Self::load_parquet(path, progress, subset)?;
+                total_rows = Some(total);
+                df
+            }
+            DataSourceType::Orc => Self::load_orc(path)?,
+            DataSourceType::Csv => {
+                let (df, used, total) = Self::load_csv(path, load_options, progress, subset)?;
+                load_options.check_dtypes(&df)?;
+                encoding = Some(used);
+                total_rows = total;
+                df
+            }
+            DataSourceType::Json => Self::load_json(path, JsonFormat::Json)?,
+            DataSourceType::NdJson => Self::load_json(path, JsonFormat::JsonLines)?,
+            DataSourceType::Excel => {
+                tables = Self::excel_sheets(path)?;
+                let first = chosen_table(&tables, load_options.table.as_deref(), "Workbook contains no sheets")?;
+                let df = Self::load_excel(path, &first)?;
+                current_table = Some(first);
+                df
+            }
+            // A query's result is not one of the tables, so none is offered to switch to
+            DataSourceType::Sqlite if load_options.query.is_some() => {
+                let sql = load_options.query.as_deref().unwrap_or_default();
+                sqlite_query(&Self::open_sqlite(path)?, sql).context("Query failed")?
+            }
+            DataSourceType::Sqlite => {
+                tables = Self::sqlite_tables(path)?;
+                let first = chosen_table(&tables, load_options.table.as_deref(), "Database contains no tables")?;
+                let df = Self::load_sqlite(path, &first)?;
+                current_table = Some(first);
+                df
+            }
+            DataSourceType::Postgres | DataSourceType::MySql => unreachable!("handled above"),
+            DataSourceType::Diff | DataSourceType::Concat | DataSourceType::Flight => {
+                unreachable!("never detected from a path")
+            }
         };
 
-        Ok(Self { df, source_type })
+        // The other formats are read whole and cut down afterwards
+        if let (Some(subset), None) = (subset, total_rows) {
+            if !matches!(source_type, DataSourceType::Csv) {
+                total_rows = Some(df.height());
+                df = subset.apply(&df)?;
+            }
+        }
+        let partial = subset.and_then(|subset| Partial::of(subset, df.height(), total_rows, estimate, budget));
+
+        let df = if load_options.categorical { encode_categorical(df)? } else { df };
+
+        Ok(Self {
+            df,
+            source_type,
+            path: path.to_path_buf(),
+            tables,
+            current_table,
+            temp_file: None,
+            paged: None,
+            load_options: load_options.clone(),
+            encoding,
+            partial,
+        })
     }
 
-    fn load_parquet(path: &Path) -> Result<DataFrame> {
-        LazyFrame::scan_parquet(path, Default::default())?
+    /// Compare two loaded sources, pairing rows by position or by the `key` column
+    pub fn diff(old: &DataSource, new: &DataSource, key: Option<&str>) -> Result<Self> {
+        if old.is_paged() || new.is_paged() {
+            anyhow::bail!("Database tables and Parquet files read page by page cannot be compared");
+        }
+        let df = super::diff::diff_frames(&old.df, &new.df, key)?;
+
+        Ok(Self {
+            df,
+            source_type: DataSourceType::Diff,
+            path: old.path.clone(),
+            tables: Vec::new(),
+            current_table: None,
+            temp_file: None,
+            paged: None,
+            load_options: old.load_options.clone(),
+            encoding: None,
+            partial: None,
+        })
+    }
+
+    /// Stack sources with the same columns into one table. Each row records the
+    /// file it came from in a leading `__source_file` column.
+    pub fn concat(sources: &[(String, DataSource)]) -> Result<Self> {
+        let (_, first) = sources.first().context("No files to concatenate")?;
+        let columns = first.columns();
+
+        let mut frames = Vec::with_capacity(sources.len());
+        for (name, source) in sources {
+            if source.is_paged() {
+                anyhow::bail!("Database tables and Parquet files read page by page cannot be concatenated");
+            }
+            if source.columns() != columns {
+                anyhow::bail!(
+                    "'{}' has different columns than '{}': {:?} vs {:?}",
+                    name,
+                    sources[0].0,
+                    source.columns(),
+                    columns
+                );
+            }
+            let origin = lit(name.as_str()).alias(CONCAT_SOURCE_COLUMN);
+            frames.push(source.df.clone().lazy().select([origin, col("*")]));
+        }
+
+        // Columns whose types differ between files (e.g. int and float) are widened
+        let args = UnionArgs {
+            to_supertypes: true,
+            ..Default::default()
+        };
+        let df = concat(frames, args)?
             .collect()
-            .context("Failed to load Parquet file")
+            .context("Failed to concatenate files")?;
+
+        Ok(Self {
+            df,
+            source_type: DataSourceType::Concat,
+            path: first.path.clone(),
+            tables: Vec::new(),
+            current_table: None,
+            temp_file: None,
+            paged: None,
+            load_options: first.load_options.clone(),
+            encoding: None,
+            partial: None,
+        })
     }
 
-    fn load_csv(path: &Path) -> Result<DataFrame> {
-        use encoding_rs::*;
-        use encoding_rs_io::DecodeReaderBytesBuilder;
-        use std::fs::File;
-        use std::io::Read;
-
-        // Try to detect encoding by reading first few KB
-        let mut file = File::open(path).context("Failed to open CSV file")?;
-        let mut buffer = vec![0u8; 8192];
-        let bytes_read = file.read(&mut buffer).context("Failed to read CSV file")?;
-        buffer.truncate(bytes_read);
-
-        // Detect encoding
-        let encoding = if let Some((enc, _bom_len)) = Encoding::for_bom(&buffer) {
-            enc
-        } else {
-            // No BOM, try to detect
-            // Common encodings: UTF-8, GBK (Chinese), GB18030, ISO-8859-1
-            if std::str::from_utf8(&buffer).is_ok() {
-                UTF_8
-            } else {
-                // Try GBK for Chinese systems
-                let (decoded, _, had_errors) = GBK.decode(&buffer);
-                if !had_errors && !decoded.is_empty() {
-                    GBK
-                } else {
-                    // Try GB18030
-                    let (decoded, _, had_errors) = GB18030.decode(&buffer);
-                    if !had_errors && !decoded.is_empty() {
-                        GB18030
-                    } else {
-                        // Fallback to WINDOWS_1252 (similar to ISO-8859-1)
-                        WINDOWS_1252
+    /// The first rows of a CSV file that is still loading, viewed until the loaded
+    /// source replaces it
+    pub fn preview(path: &Path, df: DataFrame) -> Self {
+        Self {
+            df,
+            source_type: DataSourceType::Csv,
+            path: path.to_path_buf(),
+            tables: Vec::new(),
+            current_table: None,
+            temp_file: None,
+            paged: None,
+            load_options: LoadOptions::default(),
+            encoding: None,
+            partial: None,
+        }
+    }
+
+    /// Fetch a dataset from an Arrow Flight server (`grpc://host:port`), addressed by its descriptor path
+    pub fn load_flight(url: &str, path: &str) -> Result<Self> {
+        let df = super::flight::fetch(url, path)?;
+
+        Ok(Self {
+            df,
+            source_type: DataSourceType::Flight,
+            path: PathBuf::from(format!("{}/{}", url.trim_end_matches('/'), path)),
+            tables: Vec::new(),
+            current_table: None,
+            temp_file: None,
+            paged: None,
+            load_options: LoadOptions::default(),
+            encoding: None,
+            partial: None,
+        })
+    }
+
+    /// Connect to a PostgreSQL or MySQL database. Rows are fetched with LIMIT/OFFSET as they are viewed.
+    /// Without a `table=` (or, for MySQL, `query=`) parameter every table is offered in the table picker.
+    /// `--table` and `--query` are opened instead of those parameters.
+    fn load_database(path: &Path, source_type: DataSourceType, options: &LoadOptions) -> Result<Self> {
+        let url = path.to_string_lossy();
+        let table = options.table.as_deref();
+        let (backend, explicit_table): (Box<dyn PagedSource>, bool) = match source_type {
+            DataSourceType::MySql => {
+                let (backend, explicit) = super::mysql::MySqlSource::connect(&url, table, options.query.as_deref())?;
+                (Box::new(backend), explicit)
+            }
+            _ => {
+                let (backend, explicit) = super::postgres::PostgresSource::connect(&url, table)?;
+                (Box::new(backend), explicit)
+            }
+        };
+
+        let tables = if explicit_table { Vec::new() } else { backend.tables()? };
+        let current_table = tables.first().cloned();
+
+        Ok(Self {
+            df: DataFrame::empty(),
+            source_type,
+            path: path.to_path_buf(),
+            tables,
+            current_table,
+            temp_file: None,
+            paged: Some(backend),
+            load_options: LoadOptions::default(),
+            encoding: None,
+            partial: None,
+        })
+    }
+
+    /// Open a Parquet file, dataset directory or glob to be read page by page, as large
+    /// files are (see `LAZY_PARQUET_BYTES`) and `--lazy` asks for
+    pub fn scan_parquet(path: &Path) -> Result<Self> {
+        let backend = super::lazy_parquet::LazyParquetSource::open(path)?;
+        Ok(Self {
+            df: DataFrame::empty(),
+            source_type: DataSourceType::Parquet,
+            path: path.to_path_buf(),
+            tables: Vec::new(),
+            current_table: None,
+            temp_file: None,
+            paged: Some(Box::new(backend)),
+            load_options: LoadOptions::default(),
+            encoding: None,
+            partial: None,
+        })
+    }
+
+    /// Scan a file in object storage (`s3://`, `gs://`, `az://`, ...).
+    /// Credentials are picked up by Polars from the standard environment variables and config files.
+    fn load_cloud(path: &Path, load_options: &LoadOptions) -> Result<Self> {
+        let uri = path.to_string_lossy().to_string();
+        let source_type = DataSourceType::from_path(path)
+            .context("Unsupported file type. Supported in object storage: .parquet, .csv, .ndjson, .jsonl")?;
+
+        let lf = match source_type {
+            DataSourceType::Parquet => LazyFrame::scan_parquet(&uri, Default::default())?,
+            DataSourceType::Csv => LazyCsvReader::new(&uri)
+                .with_has_header(load_options.has_header)
+                .with_skip_rows(load_options.skip_rows)
+                .with_quote_char(load_options.quote_char)
+                .with_comment_prefix(load_options.comment_char.map(|c| (c as char).to_string().into()))
+                .with_infer_schema_length(load_options.infer_schema_rows)
+                .with_dtype_overwrite(load_options.schema_overwrite())
+                .finish()?,
+            DataSourceType::NdJson => LazyJsonLineReader::new(&uri).finish()?,
+            _ => anyhow::bail!("Only Parquet, CSV and NDJSON files can be read from object storage"),
+        };
+        let df = lf
+            .collect()
+            .with_context(|| format!("Failed to read {}", uri))?;
+        if let DataSourceType::Csv = source_type {
+            load_options.check_dtypes(&df)?;
+        }
+        let (df, partial) = match load_options.rows {
+            Some(subset) => {
+                let kept = subset.apply(&df)?;
+                let partial = Partial::of(subset, kept.height(), Some(df.height()), None, None);
+                (kept, partial)
+            }
+            None => (df, None),
+        };
+        let df = if load_options.categorical { encode_categorical(df)? } else { df };
+
+        Ok(Self {
+            df,
+            source_type,
+            path: path.to_path_buf(),
+            tables: Vec::new(),
+            current_table: None,
+            temp_file: None,
+            paged: None,
+            load_options: load_options.clone(),
+            encoding: None,
+            partial,
+        })
+    }
+
+    /// Download a remote HTTP(S) file and load it, noting the bytes received in `progress`
+    pub fn load_remote(url: &str, load_options: &LoadOptions, progress: &LoadProgress) -> Result<Self> {
+        let local_path = super::remote::download(url, |downloaded, total| progress.set_download(downloaded, total))?;
+        match Self::load_with_progress(&local_path, load_options, progress) {
+            Ok(mut source) => {
+                // Keep the download around: sheets and tables are re-read from it on demand
+                source.temp_file = Some(local_path);
+                Ok(source)
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&local_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Load a different named table (sheet or database table) from the same source
+    pub fn select_table(&mut self, name: &str) -> Result<()> {
+        if !self.tables.iter().any(|t| t == name) {
+            anyhow::bail!("Table '{}' not found", name);
+        }
+
+        let df = match self.source_type {
+            DataSourceType::Excel => Self::load_excel(&self.path, name)?,
+            DataSourceType::Sqlite => Self::load_sqlite(&self.path, name)?,
+            DataSourceType::Postgres | DataSourceType::MySql => {
+                self.paged
+                    .as_mut()
+                    .context("Database connection missing")?
+                    .select_table(name)?;
+                DataFrame::empty()
+            }
+            _ => anyhow::bail!("This data source has no selectable tables"),
+        };
+        // Other tables are cut down and encoded like the first one
+        let df = match self.load_options.rows {
+            Some(subset) => {
+                let kept = subset.apply(&df)?;
+                self.partial = Partial::of(subset, kept.height(), Some(df.height()), None, None);
+                kept
+            }
+            None => df,
+        };
+        self.df = if self.load_options.categorical { encode_categorical(df)? } else { df };
+        self.current_table = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Load a Parquet file or dataset, or a subset of its rows, first reporting its row
+    /// count and schema from the footer metadata so the loading screen can show them at
+    /// once. Also returns the row count.
+    fn load_parquet(path: &Path, progress: &LoadProgress, subset: Option<RowSubset>) -> Result<(DataFrame, usize)> {
+        // Directories and globs are scanned as one dataset, with hive-style
+        // `key=value` directories surfaced as regular columns
+        let is_dataset = path.is_dir() || is_glob(path);
+        let args = ScanArgsParquet {
+            hive_options: polars::io::HiveOptions {
+                enabled: Some(is_dataset),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut scan = LazyFrame::scan_parquet(path, args)?;
+        let schema = scan.collect_schema().context("Failed to read Parquet schema")?;
+        // Counting the rows of a scan only reads the metadata
+        let counted = scan.clone().select([len()]).collect().context("Failed to read Parquet metadata")?;
+        let total_rows = counted.column("len")?.get(0)?.extract::<usize>().unwrap_or(0);
+        progress.set_summary(super::FileSummary {
+            rows: total_rows,
+            columns: schema.iter().map(|(name, dtype)| (name.to_string(), dtype.to_string())).collect(),
+        });
+
+        scan = match subset {
+            None => scan,
+            Some(RowSubset::Head(n)) => scan.slice(0, n as IdxSize),
+            Some(RowSubset::Tail(n)) => scan.slice(-(n.min(total_rows) as i64), n as IdxSize),
+            Some(subset @ RowSubset::Sample(_)) => {
+                // Only the picked rows pass the scan, so the others are never held
+                let positions = subset.positions(total_rows);
+                // Positions come in ascending runs, so each batch marks its picks at once
+                let picked = move |column: Column| {
+                    let batch = column.idx()?;
+                    let start = batch.get(0).unwrap_or(0) as usize;
+                    let mut picked = vec![false; batch.len()];
+                    let from = positions.partition_point(|&p| (p as usize) < start);
+                    for &p in positions[from..].iter().take_while(|&&p| (p as usize) < start + batch.len()) {
+                        picked[p as usize - start] = true;
                     }
-                }
+                    Ok(Some(BooleanChunked::from_slice(column.name().clone(), &picked).into_column()))
+                };
+                scan.with_row_index(SAMPLE_POSITION, None)
+                    .filter(col(SAMPLE_POSITION).map(picked, GetOutput::from_type(DataType::Boolean)))
+                    .drop([SAMPLE_POSITION])
+            }
+        };
+        let df = scan.collect().context("Failed to load Parquet file")?;
+        Ok((df, total_rows))
+    }
+
+    /// Re-read the source from disk with the options it was opened with,
+    /// staying on the current sheet or table if it still exists
+    pub fn reload(&mut self) -> Result<()> {
+        if self.is_paged() {
+            anyhow::bail!("Database tables and Parquet files read page by page are always read live");
+        }
+        match self.source_type {
+            DataSourceType::Diff => anyhow::bail!("Comparisons cannot be reloaded"),
+            DataSourceType::Concat => anyhow::bail!("Concatenated files cannot be reloaded"),
+            DataSourceType::Flight => anyhow::bail!("Flight results cannot be reloaded"),
+            _ => {}
+        }
+
+        let mut fresh = Self::load(&self.path, &self.load_options)?;
+        if let Some(table) = &self.current_table {
+            if fresh.tables.contains(table) {
+                fresh.select_table(table)?;
+            }
+        }
+        fresh.temp_file = self.temp_file.take();
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Re-read a local CSV file with a different text encoding
+    pub fn reload_with_encoding(&mut self, encoding: &'static Encoding) -> Result<()> {
+        if self.encoding.is_none() {
+            anyhow::bail!("Only local CSV files can be re-read with another encoding");
+        }
+
+        let mut options = self.load_options.clone();
+        options.encoding = Some(encoding);
+        let subset = self.partial.map(|partial| partial.subset);
+        let (df, used, _) = Self::load_csv(&self.path, &options, &LoadProgress::default(), subset)?;
+        self.df = if options.categorical { encode_categorical(df)? } else { df };
+        self.encoding = Some(used);
+        self.load_options = options;
+        Ok(())
+    }
+
+    fn load_orc(path: &Path) -> Result<DataFrame> {
+        let file = std::fs::File::open(path).context("Failed to open ORC file")?;
+        let builder = orc_rust::ArrowReaderBuilder::try_new(file).context("Failed to read ORC metadata")?;
+        let schema = builder.schema();
+        let batches = builder
+            .build()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("Failed to load ORC file")?;
+        super::record_batch::to_dataframe(&schema, &batches)
+    }
+
+    /// Load a CSV file, or a subset of its rows, returning the encoding it was decoded
+    /// with and, when they had to be counted for the subset, the rows of the whole file
+    fn load_csv(
+        path: &Path,
+        options: &LoadOptions,
+        progress: &LoadProgress,
+        subset: Option<RowSubset>,
+    ) -> Result<(DataFrame, &'static Encoding, Option<usize>)> {
+        use encoding_rs_io::DecodeReaderBytesBuilder;
+
+        let encoding = match options.encoding {
+            Some(encoding) => encoding,
+            None => {
+                // Try to detect encoding by reading first few KB
+                let mut buffer = Vec::with_capacity(8192);
+                open_decompressed(path)
+                    .context("Failed to open CSV file")?
+                    .take(8192)
+                    .read_to_end(&mut buffer)
+                    .context("Failed to read CSV file")?;
+                detect_encoding(&buffer)
             }
         };
+        // The first rows are only read up to where they end
+        let head = match subset {
+            Some(RowSubset::Head(n)) => Some(n),
+            _ => None,
+        };
 
-        // Reopen file and decode with detected encoding
-        let file = File::open(path).context("Failed to open CSV file")?;
-        let transcoded = DecodeReaderBytesBuilder::new()
+        // Plain UTF-8 files are parsed straight from disk (memory-mapped by Polars)
+        if encoding == encoding_rs::UTF_8 && Compression::detect(path)? == Compression::None {
+            // Tails and samples count the rows first, to hold on to only theirs
+            let (positions, total_rows) = match subset {
+                Some(subset @ (RowSubset::Tail(_) | RowSubset::Sample(_))) => {
+                    let total_rows = Self::count_csv_rows(path, options)?;
+                    (Some(subset.positions(total_rows)), Some(total_rows))
+                }
+                _ => (None, None),
+            };
+            let reader = options
+                .read_options()
+                .with_n_rows(head)
+                .try_into_reader_with_file_path(Some(path.to_path_buf()))?;
+            let df = read_csv_batches(reader, progress, positions.as_deref()).context("Failed to parse CSV file")?;
+            return Ok((df, encoding, total_rows));
+        }
+
+        // Everything else is decompressed and transcoded to UTF-8 in memory
+        let file = open_decompressed(path).context("Failed to open CSV file")?;
+        let mut transcoded = DecodeReaderBytesBuilder::new()
             .encoding(Some(encoding))
             .build(file);
+        let mut utf8 = Vec::new();
+        transcoded
+            .read_to_end(&mut utf8)
+            .context("Failed to transcode CSV file")?;
 
-        // Create a temporary file with UTF-8 content
-        let temp_path = std::env::temp_dir().join(format!(
-            "rata_temp_{}.csv",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_micros()
-        ));
+        let reader = options
+            .read_options()
+            .with_n_rows(head)
+            .into_reader_with_file_handle(std::io::Cursor::new(utf8));
+        let df = read_csv_batches(reader, progress, None).context("Failed to parse CSV file")?;
+        match subset {
+            Some(subset @ (RowSubset::Tail(_) | RowSubset::Sample(_))) => {
+                Ok((subset.apply(&df)?, encoding, Some(df.height())))
+            }
+            _ => Ok((df, encoding, None)),
+        }
+    }
 
-        // Write transcoded content to temp file
-        std::io::copy(
-            &mut std::io::BufReader::new(transcoded),
-            &mut std::fs::File::create(&temp_path)?
-        ).context("Failed to transcode CSV file")?;
+    /// Rows of a plain UTF-8 CSV file, counted without parsing their values
+    fn count_csv_rows(path: &Path, options: &LoadOptions) -> Result<usize> {
+        let counted = LazyCsvReader::new(path)
+            .with_has_header(options.has_header)
+            .with_skip_rows(options.skip_rows)
+            .with_quote_char(options.quote_char)
+            .with_comment_prefix(options.comment_char.map(|c| (c as char).to_string().into()))
+            .finish()?
+            .select([len()])
+            .collect()
+            .context("Failed to count CSV rows")?;
+        Ok(counted.column("len")?.get(0)?.extract::<usize>().unwrap_or(0))
+    }
 
-        // Read with Polars
-        let result = CsvReadOptions::default()
-            .try_into_reader_with_file_path(Some(temp_path.clone()))?
-            .finish()
-            .context("Failed to parse CSV file");
+    fn load_json(path: &Path, format: JsonFormat) -> Result<DataFrame> {
+        let df = if Compression::detect(path)? == Compression::None {
+            let file = std::fs::File::open(path).context("Failed to open JSON file")?;
+            JsonReader::new(std::io::BufReader::new(file))
+                .with_json_format(format)
+                .finish()
+        } else {
+            // The JSON reader needs a seekable source, so decompress into memory
+            let mut bytes = Vec::new();
+            open_decompressed(path)?
+                .read_to_end(&mut bytes)
+                .context("Failed to decompress JSON file")?;
+            JsonReader::new(std::io::Cursor::new(bytes))
+                .with_json_format(format)
+                .finish()
+        }
+        .context("Failed to parse JSON file")?;
+
+        // Nested objects come back as struct columns; flatten them so each
+        // leaf field shows up as its own table column
+        flatten_structs(df)
+    }
 
-        // Clean up temp file
-        let _ = std::fs::remove_file(temp_path);
+    fn excel_sheets(path: &Path) -> Result<Vec<String>> {
+        use calamine::Reader;
 
-        result
+        let workbook = calamine::open_workbook_auto(path).context("Failed to open Excel workbook")?;
+        Ok(workbook.sheet_names())
     }
 
-    fn load_sqlite(_path: &Path) -> Result<DataFrame> {
-        // For SQLite, we need to query tables. We'll read the first table by default.
-        // Users can extend this to select specific tables.
-        // This is a simplified version. For full SQLite support, we'd need rusqlite
-        // For now, return an error with a helpful message
-        anyhow::bail!(
-            "SQLite support requires table name. Future version will support table selection."
-        )
+    fn load_excel(path: &Path, sheet: &str) -> Result<DataFrame> {
+        use calamine::Reader;
+
+        let mut workbook = calamine::open_workbook_auto(path).context("Failed to open Excel workbook")?;
+        let range = workbook
+            .worksheet_range(sheet)
+            .with_context(|| format!("Failed to read sheet '{}'", sheet))?;
+
+        let mut rows = range.rows();
+        let header_row = match rows.next() {
+            Some(row) => row,
+            None => return Ok(DataFrame::empty()),
+        };
+
+        // First row holds the column names; blank or repeated names get made unique
+        let mut headers: Vec<String> = Vec::with_capacity(header_row.len());
+        for (i, cell) in header_row.iter().enumerate() {
+            let mut name = cell.to_string().trim().to_string();
+            if name.is_empty() {
+                name = format!("column_{}", i + 1);
+            }
+            let base = name.clone();
+            let mut n = 2;
+            while headers.contains(&name) {
+                name = format!("{}_{}", base, n);
+                n += 1;
+            }
+            headers.push(name);
+        }
+
+        let body: Vec<&[calamine::Data]> = rows.collect();
+        let columns = headers
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let cells: Vec<&calamine::Data> = body
+                    .iter()
+                    .map(|row| row.get(i).unwrap_or(&calamine::Data::Empty))
+                    .collect();
+                excel_column(name, &cells).map(Column::from)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        DataFrame::new(columns).context("Failed to build DataFrame from sheet")
+    }
+
+    fn open_sqlite(path: &Path) -> Result<rusqlite::Connection> {
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .context("Failed to open SQLite database")
+    }
+
+    fn sqlite_tables(path: &Path) -> Result<Vec<String>> {
+        let conn = Self::open_sqlite(path)?;
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' \
+             ORDER BY name",
+        )?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to list SQLite tables")?;
+        Ok(names)
+    }
+
+    fn load_sqlite(path: &Path, table: &str) -> Result<DataFrame> {
+        let conn = Self::open_sqlite(path)?;
+        let sql = format!("SELECT * FROM \"{}\"", table.replace('"', "\"\""));
+        sqlite_query(&conn, &sql).with_context(|| format!("Failed to load table '{}'", table))
     }
 
     /// Get the underlying DataFrame
@@ -139,39 +1053,130 @@ impl DataSource {
         &self.df
     }
 
+    /// Add rows parsed since the source was created, e.g. to a `preview`
+    pub fn append_rows(&mut self, rows: &[DataFrame]) -> Result<()> {
+        for batch in rows {
+            self.df.vstack_mut(batch).context("Failed to add rows")?;
+        }
+        Ok(())
+    }
+
     /// Get the source type
     pub fn source_type(&self) -> DataSourceType {
         self.source_type
     }
 
+    /// Get the named tables (workbook sheets, database tables) available in this source
+    pub fn tables(&self) -> &[String] {
+        &self.tables
+    }
+
+    /// Options this source was loaded with
+    pub fn load_options(&self) -> &LoadOptions {
+        &self.load_options
+    }
+
+    /// Whether to ask which sheet or table to view: there are several and none was
+    /// named with `--table`
+    pub fn asks_for_table(&self) -> bool {
+        self.tables.len() > 1 && self.load_options.table.is_none()
+    }
+
+    /// Get the currently loaded table, if the source has several
+    pub fn current_table(&self) -> Option<&str> {
+        self.current_table.as_deref()
+    }
+
     /// Get column names
     pub fn columns(&self) -> Vec<String> {
-        self.df.get_column_names().iter().map(|s| s.to_string()).collect()
+        match &self.paged {
+            Some(paged) => paged.columns(),
+            None => self.df.get_column_names().iter().map(|s| s.to_string()).collect(),
+        }
     }
 
-    /// Get number of rows
+    /// Get number of rows (for paged sources, the rows matching the pushed-down filter)
     pub fn len(&self) -> usize {
-        self.df.height()
+        match &self.paged {
+            Some(paged) => paged.len(),
+            None => self.df.height(),
+        }
+    }
+
+    /// Encoding a local CSV file was decoded with, `None` for other sources
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        self.encoding
+    }
+
+    /// Which rows of the file were loaded, `None` when all of them were
+    pub fn partial(&self) -> Option<Partial> {
+        self.partial
+    }
+
+    /// Whether rows are fetched page by page instead of held in `dataframe()`
+    pub fn is_paged(&self) -> bool {
+        self.paged.is_some()
+    }
+
+    /// Fetch a page of rows from a paged source
+    pub fn page(&self, offset: usize, limit: usize) -> Result<DataFrame> {
+        match &self.paged {
+            Some(paged) => paged.fetch(offset, limit),
+            None => Ok(self.df.slice(offset as i64, limit)),
+        }
     }
 
-    /// Check if empty
-    pub fn is_empty(&self) -> bool {
-        self.df.height() == 0
+    /// Count the values of `column` of a paged source where it is stored; see
+    /// `PagedSource::value_counts`
+    pub fn value_counts_paged(&self, column: &str, limit: usize) -> Result<DataFrame> {
+        let paged = self.paged.as_ref().context("Data source is not paged")?;
+        paged.value_counts(column, limit)
     }
 
-    /// Apply a filter expression
-    /// Supports advanced filtering with comparison operators and logical expressions
-    /// Examples:
-    ///   - "InstrumentID:IC2602" - substring match
-    ///   - "Price > 5000" - greater than
-    ///   - "InstrumentID = IC2602 AND Price > 5000" - logical AND
-    pub fn filter(&self, pattern: &str) -> Result<DataFrame> {
+    /// Column types of a paged source, which holds no rows to read them from
+    pub fn paged_schema(&self) -> Option<SchemaRef> {
+        self.paged.as_ref().map(|paged| paged.schema())
+    }
+
+    /// Push a filter expression down to a paged source
+    pub fn filter_paged(&mut self, pattern: &str, options: FilterOptions) -> Result<()> {
+        let paged = self.paged.as_mut().context("Data source is not paged")?;
         if pattern.is_empty() {
-            return Ok(self.df.clone());
+            return paged.set_filter(None);
         }
 
-        let expr = FilterExpr::parse(pattern)?;
-        expr.apply(&self.df)
+        let expr = FilterExpr::parse(pattern)?.with_options(options);
+        paged.set_filter(Some(&expr))
+    }
+
+    /// Push a sort down to a paged source
+    pub fn sort_paged(&mut self, sort: &[(String, SortOrder)], nulls_last: bool) -> Result<()> {
+        let paged = self.paged.as_mut().context("Data source is not paged")?;
+        paged.set_sort(sort, nulls_last)
+    }
+
+    /// Run a SQL query against the loaded data, which is available as table `df`.
+    /// Input that does not start with SELECT or WITH is taken as the WHERE clause of
+    /// `SELECT * FROM df`.
+    pub fn query(&self, sql: &str) -> Result<DataFrame> {
+        if self.is_paged() {
+            anyhow::bail!("SQL queries run on loaded files only; sources read page by page are filtered with /");
+        }
+
+        let sql = sql.trim();
+        let first_word = sql.split_whitespace().next().unwrap_or("").to_uppercase();
+        let sql = if first_word == "SELECT" || first_word == "WITH" {
+            sql.to_string()
+        } else {
+            format!("SELECT * FROM df WHERE {}", sql)
+        };
+
+        let mut context = polars::sql::SQLContext::new();
+        context.register("df", self.df.clone().lazy());
+        context
+            .execute(&sql)
+            .and_then(|lf| lf.collect())
+            .context("SQL query failed")
     }
 
     /// Legacy filter method for backward compatibility (deprecated)
@@ -217,3 +1222,207 @@ impl DataSource {
         }
     }
 }
+
+impl Drop for DataSource {
+    fn drop(&mut self) {
+        if let Some(temp_file) = &self.temp_file {
+            let _ = std::fs::remove_file(temp_file);
+        }
+    }
+}
+
+/// Build a typed column from spreadsheet cells.
+/// Numeric, boolean and date columns keep their type; anything mixed becomes a string column.
+fn excel_column(name: &str, cells: &[&calamine::Data]) -> Result<Series> {
+    use calamine::Data;
+
+    let present = || cells.iter().filter(|c| !matches!(c, Data::Empty));
+    let name = PlSmallStr::from(name);
+
+    // xlsx stores every number as a float, so whole-valued columns are treated as integers
+    let is_whole = |c: &Data| match c {
+        Data::Int(_) => true,
+        Data::Float(v) => v.fract() == 0.0 && v.abs() < i64::MAX as f64,
+        _ => false,
+    };
+    if present().all(|c| is_whole(c)) {
+        let values: Vec<Option<i64>> = cells
+            .iter()
+            .map(|c| match c {
+                Data::Int(v) => Some(*v),
+                Data::Float(v) => Some(*v as i64),
+                _ => None,
+            })
+            .collect();
+        return Ok(Series::new(name, values));
+    }
+
+    if present().all(|c| matches!(c, Data::Int(_) | Data::Float(_))) {
+        let values: Vec<Option<f64>> = cells
+            .iter()
+            .map(|c| match c {
+                Data::Int(v) => Some(*v as f64),
+                Data::Float(v) => Some(*v),
+                _ => None,
+            })
+            .collect();
+        return Ok(Series::new(name, values));
+    }
+
+    if present().all(|c| matches!(c, Data::Bool(_))) {
+        let values: Vec<Option<bool>> = cells
+            .iter()
+            .map(|c| match c {
+                Data::Bool(v) => Some(*v),
+                _ => None,
+            })
+            .collect();
+        return Ok(Series::new(name, values));
+    }
+
+    if present().all(|c| matches!(c, Data::DateTime(_))) {
+        let millis: Vec<Option<i64>> = cells
+            .iter()
+            .map(|c| match c {
+                Data::DateTime(dt) => dt.as_datetime().map(|d| d.and_utc().timestamp_millis()),
+                _ => None,
+            })
+            .collect();
+        return Series::new(name, millis)
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+            .context("Failed to convert Excel dates");
+    }
+
+    let values: Vec<Option<String>> = cells
+        .iter()
+        .map(|c| match c {
+            Data::Empty => None,
+            other => Some(other.to_string()),
+        })
+        .collect();
+    Ok(Series::new(name, values))
+}
+
+/// The sheet or table named with `--table`, or the first of `tables`
+fn chosen_table(tables: &[String], name: Option<&str>, empty: &str) -> Result<String> {
+    match name {
+        Some(name) if tables.iter().any(|table| table == name) => Ok(name.to_string()),
+        Some(name) => anyhow::bail!("Table '{}' not found, expected one of {}", name, tables.join(", ")),
+        None => Ok(tables.first().context(empty.to_string())?.clone()),
+    }
+}
+
+/// Run a query and collect the result set into a DataFrame
+fn sqlite_query(conn: &rusqlite::Connection, sql: &str) -> Result<DataFrame> {
+    use rusqlite::types::Value;
+
+    let mut stmt = conn.prepare(sql)?;
+    let names: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+    let mut values: Vec<Vec<Value>> = vec![Vec::new(); names.len()];
+
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        for (i, column) in values.iter_mut().enumerate() {
+            column.push(row.get::<_, Value>(i)?);
+        }
+    }
+
+    let columns = names
+        .iter()
+        .zip(values)
+        .map(|(name, cells)| Column::from(sqlite_column(name, cells)))
+        .collect();
+
+    DataFrame::new(columns).context("Failed to build DataFrame from query")
+}
+
+/// Build a typed column from SQLite values.
+/// SQLite is dynamically typed, so the column type is inferred from the values actually stored.
+fn sqlite_column(name: &str, cells: Vec<rusqlite::types::Value>) -> Series {
+    use rusqlite::types::Value;
+
+    let present = || cells.iter().filter(|c| !matches!(c, Value::Null));
+    let name = PlSmallStr::from(name);
+
+    if present().all(|c| matches!(c, Value::Integer(_))) {
+        let values: Vec<Option<i64>> = cells
+            .iter()
+            .map(|c| match c {
+                Value::Integer(v) => Some(*v),
+                _ => None,
+            })
+            .collect();
+        return Series::new(name, values);
+    }
+
+    if present().all(|c| matches!(c, Value::Integer(_) | Value::Real(_))) {
+        let values: Vec<Option<f64>> = cells
+            .iter()
+            .map(|c| match c {
+                Value::Integer(v) => Some(*v as f64),
+                Value::Real(v) => Some(*v),
+                _ => None,
+            })
+            .collect();
+        return Series::new(name, values);
+    }
+
+    let values: Vec<Option<String>> = cells
+        .into_iter()
+        .map(|c| match c {
+            Value::Null => None,
+            Value::Integer(v) => Some(v.to_string()),
+            Value::Real(v) => Some(v.to_string()),
+            Value::Text(v) => Some(v),
+            Value::Blob(v) => Some(format!("<blob {} bytes>", v.len())),
+        })
+        .collect();
+    Series::new(name, values)
+}
+
+/// Flatten struct columns into dotted column names (e.g. `order.price`)
+fn flatten_structs(df: DataFrame) -> Result<DataFrame> {
+    if !df.get_columns().iter().any(|c| matches!(c.dtype(), DataType::Struct(_))) {
+        return Ok(df);
+    }
+
+    let mut columns: Vec<Column> = Vec::with_capacity(df.width());
+    for col in df.get_columns() {
+        flatten_column(col.as_materialized_series().clone(), &mut columns)?;
+    }
+
+    DataFrame::new(columns).context("Failed to flatten nested JSON columns")
+}
+
+fn flatten_column(series: Series, out: &mut Vec<Column>) -> Result<()> {
+    if let DataType::Struct(_) = series.dtype() {
+        let parent = series.name().to_string();
+        for field in series.struct_()?.fields_as_series() {
+            let name = format!("{}.{}", parent, field.name());
+            flatten_column(field.with_name(name.into()), out)?;
+        }
+    } else {
+        out.push(series.into());
+    }
+    Ok(())
+}
+
+/// Check that a filter parses and fits the columns of `schema`, without running it
+pub fn check_filter(schema: &Schema, pattern: &str, options: FilterOptions) -> Result<()> {
+    if pattern.is_empty() {
+        return Ok(());
+    }
+    FilterExpr::parse(pattern)?
+        .with_options(options)
+        .to_expr(schema, options.strings_only)
+        .map(|_| ())
+}
+
+/// Filter a DataFrame with a filter expression (empty keeps every row).
+/// Kept free of `DataSource` so it can run on a background thread.
+pub fn filter_frame(df: &DataFrame, pattern: &str, options: FilterOptions) -> Result<DataFrame> {
+    if pattern.is_empty() {
+        return Ok(df.clone());
+    }
+    FilterExpr::parse(pattern)?.with_options(options).apply(df, options.strings_only)
+}
@@ -0,0 +1,274 @@
+use crate::filter::{FilterExpr, SqlDialect};
+use crate::sort::SortOrder;
+use anyhow::{bail, Result};
+use polars::prelude::*;
+use std::cell::RefCell;
+
+/// How a column's values are transferred and stored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnKind {
+    Bool,
+    Int,
+    Float,
+    /// Transferred as days since 1970-01-01
+    Date,
+    /// Transferred as microseconds since 1970-01-01 00:00
+    Datetime,
+    /// Instants such as PostgreSQL's TIMESTAMPTZ, transferred like `Datetime` in UTC
+    DatetimeUtc,
+    /// Transferred as nanoseconds since midnight
+    Time,
+    /// Everything else is cast to text on the server
+    Text,
+}
+
+impl ColumnKind {
+    /// Type the column is decoded as
+    pub fn dtype(&self) -> DataType {
+        match self {
+            ColumnKind::Bool => DataType::Boolean,
+            ColumnKind::Int => DataType::Int64,
+            ColumnKind::Float => DataType::Float64,
+            ColumnKind::Date => DataType::Date,
+            ColumnKind::Datetime => DataType::Datetime(TimeUnit::Microseconds, None),
+            ColumnKind::DatetimeUtc => DataType::Datetime(TimeUnit::Microseconds, Some("UTC".into())),
+            ColumnKind::Time => DataType::Time,
+            ColumnKind::Text => DataType::String,
+        }
+    }
+
+    /// The integers a date, datetime or time column is transferred as, in its type
+    pub fn temporal(&self, name: PlSmallStr, values: Vec<Option<i64>>) -> Result<Series> {
+        Ok(Series::new(name, values).cast(&self.dtype())?)
+    }
+
+    /// Select expression converting the column to the type we decode
+    fn select_expr(&self, dialect: SqlDialect, column: &str) -> String {
+        let ident = dialect.ident(column);
+        match (dialect, self) {
+            (_, ColumnKind::Bool) => ident,
+            (SqlDialect::Postgres, ColumnKind::Int) => format!("{}::int8 AS {}", ident, ident),
+            (SqlDialect::Postgres, ColumnKind::Float) => format!("{}::float8 AS {}", ident, ident),
+            // Infinite dates and timestamps have no such number, and come through as nulls
+            (SqlDialect::Postgres, ColumnKind::Date) => {
+                format!("CASE WHEN isfinite({0}) THEN ({0} - DATE '1970-01-01')::int8 END AS {0}", ident)
+            }
+            (SqlDialect::Postgres, ColumnKind::Datetime | ColumnKind::DatetimeUtc) => {
+                format!("CASE WHEN isfinite({0}) THEN (EXTRACT(EPOCH FROM {0}) * 1000000)::int8 END AS {0}", ident)
+            }
+            (SqlDialect::Postgres, ColumnKind::Time) => {
+                format!("(EXTRACT(EPOCH FROM {0}) * 1000000000)::int8 AS {0}", ident)
+            }
+            (SqlDialect::Postgres, ColumnKind::Text) => format!("{}::text AS {}", ident, ident),
+            (SqlDialect::MySql, ColumnKind::Int | ColumnKind::Float) => ident,
+            (SqlDialect::MySql, ColumnKind::Date) => format!("DATEDIFF({0}, '1970-01-01') AS {0}", ident),
+            (SqlDialect::MySql, ColumnKind::Datetime | ColumnKind::DatetimeUtc) => {
+                format!("TIMESTAMPDIFF(MICROSECOND, '1970-01-01', {0}) AS {0}", ident)
+            }
+            (SqlDialect::MySql, ColumnKind::Time) => {
+                format!("TIME_TO_SEC({0}) * 1000000000 + MICROSECOND({0}) * 1000 AS {0}", ident)
+            }
+            (SqlDialect::MySql, ColumnKind::Text) => format!("CAST({} AS CHAR) AS {}", ident, ident),
+        }
+    }
+}
+
+/// A table or query result of an SQL database viewed page by page with LIMIT/OFFSET:
+/// the SQL for its pages, filter and sort, and the last page fetched. The sources run
+/// the SQL on their own connections.
+pub struct SqlTable {
+    dialect: SqlDialect,
+    /// Quoted relation name, e.g. `"public"."trades"`, or a query as a derived table
+    relation: String,
+    columns: Vec<(String, ColumnKind)>,
+    /// Types the columns are decoded as
+    schema: SchemaRef,
+    /// Pushed-down filter condition
+    where_clause: Option<String>,
+    /// Pushed-down sort, e.g. `"price" DESC NULLS LAST` in PostgreSQL and
+    /// `` `price` IS NULL ASC, `price` DESC `` in MySQL
+    order_clause: Option<String>,
+    /// Row count for the current filter
+    row_count: usize,
+    /// Last fetched page, keyed by (offset, limit)
+    cache: RefCell<Option<(usize, usize, DataFrame)>>,
+}
+
+impl SqlTable {
+    pub fn new(dialect: SqlDialect) -> Self {
+        Self {
+            dialect,
+            relation: String::new(),
+            columns: Vec::new(),
+            schema: Arc::new(Schema::default()),
+            where_clause: None,
+            order_clause: None,
+            row_count: 0,
+            cache: RefCell::new(None),
+        }
+    }
+
+    /// Quoted name of a table, `schema.table` naming one in another schema
+    pub fn relation_of(&self, table: &str) -> String {
+        table
+            .split('.')
+            .map(|part| self.dialect.ident(part))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// The SQL describing the columns of `relation` without fetching any rows
+    pub fn describe_sql(relation: &str) -> String {
+        format!("SELECT * FROM {} LIMIT 0", relation)
+    }
+
+    /// View `relation`, whose columns are `columns`, without a filter or sort. `count`
+    /// runs the SQL counting its rows.
+    pub fn open(
+        &mut self,
+        relation: String,
+        columns: Vec<(String, ColumnKind)>,
+        count: impl FnOnce(&str) -> Result<usize>,
+    ) -> Result<()> {
+        let fields = columns.iter().map(|(name, kind)| Field::new(name.as_str().into(), kind.dtype()));
+        self.schema = Arc::new(Schema::from_iter(fields));
+        self.columns = columns;
+        self.relation = relation;
+        self.where_clause = None;
+        self.order_clause = None;
+        *self.cache.borrow_mut() = None;
+        self.row_count = count(&self.count_sql())?;
+        Ok(())
+    }
+
+    pub fn columns(&self) -> Vec<String> {
+        self.columns.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Rows matching the current filter
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    /// Up to `limit` rows from `offset`, read by `query` from the SQL given with the
+    /// columns to decode, unless they are the page last read
+    pub fn fetch(
+        &self,
+        offset: usize,
+        limit: usize,
+        query: impl FnOnce(&str, &[(String, ColumnKind)]) -> Result<DataFrame>,
+    ) -> Result<DataFrame> {
+        if let Some((cached_offset, cached_limit, df)) = self.cache.borrow().as_ref() {
+            if *cached_offset == offset && *cached_limit == limit {
+                return Ok(df.clone());
+            }
+        }
+
+        let select_list: Vec<String> = self
+            .columns
+            .iter()
+            .map(|(name, kind)| kind.select_expr(self.dialect, name))
+            .collect();
+        let sql = format!(
+            "SELECT {} FROM {}{}{} LIMIT {} OFFSET {}",
+            select_list.join(", "),
+            self.relation,
+            self.where_sql(),
+            self.order_sql(),
+            limit,
+            offset
+        );
+        let df = query(&sql, &self.columns)?;
+        *self.cache.borrow_mut() = Some((offset, limit, df.clone()));
+        Ok(df)
+    }
+
+    /// The SQL counting the `limit` most frequent values of `column`, and the columns it
+    /// gives, the values and then `COUNT_COLUMN`
+    pub fn value_counts_sql(&self, column: &str, limit: usize) -> Result<(String, Vec<(String, ColumnKind)>)> {
+        let kind = match self.columns.iter().find(|(name, _)| name == column) {
+            Some((_, kind)) => *kind,
+            None => bail!("Column '{}' not found", column),
+        };
+        let sql = format!(
+            "SELECT {}, COUNT(*) FROM {}{} GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT {}",
+            kind.select_expr(self.dialect, column),
+            self.relation,
+            self.where_sql(),
+            limit
+        );
+        let columns = vec![(column.to_string(), kind), (super::paged::COUNT_COLUMN.to_string(), ColumnKind::Int)];
+        Ok((sql, columns))
+    }
+
+    /// Push `filter` down as a WHERE clause, keeping the previous one if `count` fails to
+    /// count the rows it matches
+    pub fn set_filter(&mut self, filter: Option<&FilterExpr>, count: impl FnOnce(&str) -> Result<usize>) -> Result<()> {
+        let names = self.columns();
+        let is_numeric = |column: &str| {
+            self.columns
+                .iter()
+                .any(|(name, kind)| name == column && matches!(kind, ColumnKind::Int | ColumnKind::Float))
+        };
+        let where_clause = filter.map(|f| f.to_sql(&names, &is_numeric, self.dialect)).transpose()?;
+
+        let previous = std::mem::replace(&mut self.where_clause, where_clause);
+        match count(&self.count_sql()) {
+            Ok(count) => {
+                self.row_count = count;
+                *self.cache.borrow_mut() = None;
+                Ok(())
+            }
+            Err(e) => {
+                self.where_clause = previous;
+                Err(e)
+            }
+        }
+    }
+
+    pub fn set_sort(&mut self, sort: &[(String, SortOrder)], nulls_last: bool) -> Result<()> {
+        let mut keys = Vec::new();
+        for (column, order) in sort {
+            if !self.columns.iter().any(|(name, _)| name == column) {
+                bail!("Column '{}' not found", column);
+            }
+            let column = self.dialect.ident(column);
+            keys.push(match self.dialect {
+                SqlDialect::Postgres => {
+                    let nulls = if nulls_last { "NULLS LAST" } else { "NULLS FIRST" };
+                    format!("{} {} {}", column, order.sql(), nulls)
+                }
+                // MySQL has no NULLS FIRST/LAST, so order by nullness first
+                SqlDialect::MySql => {
+                    let nulls = if nulls_last { "ASC" } else { "DESC" };
+                    format!("{} IS NULL {}, {} {}", column, nulls, column, order.sql())
+                }
+            });
+        }
+        self.order_clause = (!keys.is_empty()).then(|| keys.join(", "));
+        *self.cache.borrow_mut() = None;
+        Ok(())
+    }
+
+    fn count_sql(&self) -> String {
+        format!("SELECT COUNT(*) FROM {}{}", self.relation, self.where_sql())
+    }
+
+    fn where_sql(&self) -> String {
+        match &self.where_clause {
+            Some(condition) => format!(" WHERE {}", condition),
+            None => String::new(),
+        }
+    }
+
+    fn order_sql(&self) -> String {
+        match &self.order_clause {
+            Some(order) => format!(" ORDER BY {}", order),
+            None => String::new(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::memory::MemoryEstimate;
+use anyhow::{Context, Result};
+use polars::prelude::*;
+
+/// Rows loaded instead of a whole file, for a quick look at large files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowSubset {
+    /// The first rows
+    Head(usize),
+    /// The last rows
+    Tail(usize),
+    /// Rows picked at random, kept in file order
+    Sample(usize),
+}
+
+impl RowSubset {
+    /// Rows kept at most
+    pub fn len(self) -> usize {
+        match self {
+            RowSubset::Head(n) | RowSubset::Tail(n) | RowSubset::Sample(n) => n,
+        }
+    }
+
+    /// Positions of the rows kept out of `total`, ascending
+    pub fn positions(self, total: usize) -> Vec<IdxSize> {
+        let n = self.len().min(total);
+        match self {
+            RowSubset::Head(_) => (0..n as IdxSize).collect(),
+            RowSubset::Tail(_) => ((total - n) as IdxSize..total as IdxSize).collect(),
+            RowSubset::Sample(_) => {
+                let mut positions: Vec<IdxSize> = rand::seq::index::sample(&mut rand::thread_rng(), total, n)
+                    .into_iter()
+                    .map(|i| i as IdxSize)
+                    .collect();
+                positions.sort_unstable();
+                positions
+            }
+        }
+    }
+
+    /// The rows of a whole file kept, for formats read in one go
+    pub fn apply(self, df: &DataFrame) -> Result<DataFrame> {
+        Ok(match self {
+            RowSubset::Head(n) => df.head(Some(n)),
+            RowSubset::Tail(n) => df.tail(Some(n)),
+            RowSubset::Sample(_) => keep_rows(df, 0, &self.positions(df.height())).context("Failed to sample rows")?,
+        })
+    }
+}
+
+/// The rows of `chunk`, which starts at row `offset` of its file, that are at one of
+/// the ascending `positions`
+pub fn keep_rows(chunk: &DataFrame, offset: usize, positions: &[IdxSize]) -> PolarsResult<DataFrame> {
+    let start = positions.partition_point(|&p| (p as usize) < offset);
+    let end = positions.partition_point(|&p| (p as usize) < offset + chunk.height());
+    let local: Vec<IdxSize> = positions[start..end].iter().map(|&p| p - offset as IdxSize).collect();
+    chunk.take(&IdxCa::from_vec("".into(), local))
+}
+
+/// The rows a source holds when it loaded only some of its file
+#[derive(Debug, Clone, Copy)]
+pub struct Partial {
+    /// Which rows were asked for
+    pub subset: RowSubset,
+    /// Rows loaded
+    pub rows: usize,
+    /// Rows in the whole file, when known
+    pub total_rows: Option<usize>,
+    /// Whether `total_rows` is counted rather than estimated from the file size
+    pub exact: bool,
+    /// The `--max-memory` budget the whole file would not fit in, `None` when the
+    /// rows were asked for with `--head`, `--tail` or `--sample`
+    pub budget: Option<u64>,
+}
+
+impl Partial {
+    /// What a source holds of `subset` after loading `rows` of it, `None` when that is
+    /// the whole file. Uncounted files take their row count from `estimate`.
+    pub fn of(
+        subset: RowSubset,
+        rows: usize,
+        total_rows: Option<usize>,
+        estimate: Option<MemoryEstimate>,
+        budget: Option<u64>,
+    ) -> Option<Self> {
+        let (total_rows, exact) = match (total_rows, estimate) {
+            (Some(total_rows), _) => (Some(total_rows), true),
+            (None, Some(estimate)) => (Some(estimate.total_rows.max(rows)), estimate.exact),
+            (None, None) => (None, false),
+        };
+        // Fewer rows than asked for means the file ran out
+        let whole = rows < subset.len() || (exact && total_rows.is_some_and(|total_rows| rows >= total_rows));
+        (!whole).then_some(Self { subset, rows, total_rows, exact, budget })
+    }
+}
@@ -1,19 +1,36 @@
+use super::format::type_label;
+use super::CellFormat;
 use polars::prelude::*;
 
 /// Table data structure for UI display
 pub struct TableData {
     pub headers: Vec<String>,
-    pub rows: Vec<Vec<String>>,
+    /// Short data type name of each column, like `f64` or `datetime`
+    pub types: Vec<String>,
+    /// Cell text by row, `None` for missing values
+    pub rows: Vec<Vec<Option<String>>>,
+    /// Whether each column's cells were formatted; the others are off screen, and
+    /// their cells left empty
+    pub formatted: Vec<bool>,
     pub total_rows: usize,
 }
 
 impl TableData {
-    /// Create TableData from a DataFrame with pagination
-    pub fn from_dataframe(df: &DataFrame, offset: usize, limit: usize) -> Self {
+    /// Create TableData from a DataFrame with pagination, formatting the cells of the
+    /// columns `on_screen` accepts (by position)
+    pub fn from_dataframe(
+        df: &DataFrame,
+        offset: usize,
+        limit: usize,
+        format: &CellFormat,
+        on_screen: impl Fn(usize) -> bool,
+    ) -> Self {
         let headers = df.get_column_names()
             .iter()
             .map(|s| s.to_string())
             .collect();
+        let types = df.dtypes().iter().map(type_label).collect();
+        let formatted: Vec<bool> = (0..df.width()).map(on_screen).collect();
 
         let total_rows = df.height();
         let end = (offset + limit).min(total_rows);
@@ -22,10 +39,12 @@ impl TableData {
             .map(|idx| {
                 df.get_columns()
                     .iter()
-                    .map(|col| {
-                        col.get(idx)
-                            .map(|av| format!("{}", av))
-                            .unwrap_or_else(|_| "NULL".to_string())
+                    .zip(&formatted)
+                    .map(|(col, &formatted)| {
+                        if !formatted {
+                            return None;
+                        }
+                        col.get(idx).ok().and_then(|av| format.format(&av))
                     })
                     .collect()
             })
@@ -33,47 +52,13 @@ impl TableData {
 
         Self {
             headers,
+            types,
             rows,
+            formatted,
             total_rows,
         }
     }
 
-    /// Filter to only show selected columns
-    pub fn filter_columns(&mut self, selected_columns: &[String]) {
-        // Find indices of selected columns
-        let indices: Vec<usize> = selected_columns
-            .iter()
-            .filter_map(|col| self.headers.iter().position(|h| h == col))
-            .collect();
-
-        if indices.is_empty() {
-            // No columns selected, show nothing
-            self.headers.clear();
-            self.rows.clear();
-            return;
-        }
-
-        // Filter headers
-        let new_headers: Vec<String> = indices
-            .iter()
-            .map(|&i| self.headers[i].clone())
-            .collect();
-
-        // Filter rows
-        let new_rows: Vec<Vec<String>> = self.rows
-            .iter()
-            .map(|row| {
-                indices
-                    .iter()
-                    .map(|&i| row[i].clone())
-                    .collect()
-            })
-            .collect();
-
-        self.headers = new_headers;
-        self.rows = new_rows;
-    }
-
     /// Get number of columns
     pub fn num_columns(&self) -> usize {
         self.headers.len()
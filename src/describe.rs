@@ -0,0 +1,210 @@
+use crate::app::App;
+use crate::data::CellFormat;
+use crate::ui::format_count;
+use anyhow::Result;
+use polars::prelude::*;
+use std::cell::Cell;
+
+/// Figures of each numeric column, as in Polars' `describe()`
+pub const STATISTICS: [&str; 9] = ["count", "nulls", "mean", "std", "min", "25%", "50%", "75%", "max"];
+
+/// Decimal places of the means, deviations and quantiles when `--precision` is not given
+const DEFAULT_PRECISION: usize = 4;
+
+/// Summary of every numeric column of the filtered rows, one row per column, for the
+/// describe screen (`d`)
+pub struct Describe {
+    /// Column names with their `STATISTICS`, formatted
+    pub rows: Vec<(String, Vec<String>)>,
+    /// Rows summarized
+    pub row_count: usize,
+    /// Filtered rows of a paged source the summarized ones are the first of, when more
+    pub sampled_from: Option<usize>,
+    /// Column under the cursor
+    pub cursor: usize,
+    /// First of the `STATISTICS` shown, for terminals too narrow for all of them
+    pub first_statistic: usize,
+    /// First row shown, kept while drawn so the table only scrolls when the cursor leaves it
+    offset: Cell<usize>,
+}
+
+impl Describe {
+    /// Summarize the numeric columns of `df` among `columns`, in that order, in one pass
+    pub fn of(df: &DataFrame, columns: &[String], format: &CellFormat) -> Result<Self> {
+        let schema = df.schema();
+        let numeric: Vec<&String> = columns
+            .iter()
+            .filter(|column| schema.get(column).is_some_and(|dtype| dtype.is_numeric()))
+            .collect();
+
+        let mut exprs = Vec::with_capacity(numeric.len() * STATISTICS.len());
+        for (i, column) in numeric.iter().enumerate() {
+            let values = col(column.as_str());
+            let floats = values.clone().cast(DataType::Float64);
+            let quantile = |q: f64| floats.clone().quantile(lit(q), QuantileMethod::Linear);
+            let stats = [
+                values.clone().count().cast(DataType::UInt64),
+                values.clone().null_count().cast(DataType::UInt64),
+                floats.clone().mean(),
+                floats.clone().std(1),
+                values.clone().min(),
+                quantile(0.25),
+                quantile(0.5),
+                quantile(0.75),
+                values.max(),
+            ];
+            exprs.extend(stats.into_iter().zip(STATISTICS).map(|(expr, stat)| expr.alias(format!("{}:{}", i, stat))));
+        }
+        let summary = if exprs.is_empty() { DataFrame::empty() } else { df.clone().lazy().select(exprs).collect()? };
+
+        // Means and quantiles of integers are fractions too, so they get the default places
+        let fractions = format.with_default_precision(DEFAULT_PRECISION);
+        let mut rows = Vec::with_capacity(numeric.len());
+        for (i, column) in numeric.iter().enumerate() {
+            let mut figures = Vec::with_capacity(STATISTICS.len());
+            for stat in STATISTICS {
+                let value = summary.column(&format!("{}:{}", i, stat))?.get(0)?;
+                let format = if matches!(stat, "min" | "max") { format } else { &fractions };
+                figures.push(format.format(&value).unwrap_or_else(|| format.null.clone()));
+            }
+            rows.push((column.to_string(), figures));
+        }
+        Ok(Self {
+            rows,
+            row_count: df.height(),
+            sampled_from: None,
+            cursor: 0,
+            first_statistic: 0,
+            offset: Cell::new(0),
+        })
+    }
+
+    /// Move the cursor by `delta` columns, stopping at either end
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.rows.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Scroll the statistics shown by `delta`, keeping at least the last one in view
+    pub fn scroll_statistics(&mut self, delta: isize) {
+        self.first_statistic = self.first_statistic.saturating_add_signed(delta).min(STATISTICS.len() - 1);
+    }
+}
+
+/// Render the describe screen
+pub fn render_describe(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::{
+        layout::{Constraint, Direction, Layout},
+        style::{Modifier, Style},
+        text::{Line, Span},
+        widgets::{Block, Borders, Cell as TableCell, Paragraph, Row, Table, TableState},
+    };
+
+    let Some(describe) = &app.describe else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),  // Header
+            Constraint::Min(0),     // Summary table
+            Constraint::Length(3),  // Footer
+        ])
+        .split(area);
+
+    // Header
+    let header_block = Block::default()
+        .title(" Describe ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(app.theme.frame));
+
+    let header_text = vec![Line::from(vec![
+        Span::styled("↑↓ PgUp/PgDn g/G", app.theme.key()),
+        Span::raw(" navigate | "),
+        Span::styled("←→", app.theme.key()),
+        Span::raw(" scroll statistics | "),
+        Span::styled("Esc", app.theme.key()),
+        Span::raw(" back"),
+    ])];
+
+    let header = Paragraph::new(header_text).block(header_block);
+    f.render_widget(header, chunks[0]);
+
+    // Figures are right-aligned so their digits line up, and only as many statistics as
+    // fit are shown, as squeezed figures would be cut
+    const SPACING: usize = 2;
+    let name_width = describe.rows.iter().map(|(name, _)| crate::text::width(name)).max().unwrap_or(0).max(6);
+    let mut widths = vec![name_width];
+    let mut used = name_width;
+    let available = chunks[1].width.saturating_sub(2) as usize;
+    for i in describe.first_statistic..STATISTICS.len() {
+        let width = describe
+            .rows
+            .iter()
+            .map(|(_, figures)| crate::text::width(&figures[i]))
+            .max()
+            .unwrap_or(0)
+            .max(STATISTICS[i].len());
+        if widths.len() > 1 && used + SPACING + width > available {
+            break;
+        }
+        widths.push(width);
+        used += SPACING + width;
+    }
+    let shown = describe.first_statistic..describe.first_statistic + widths.len() - 1;
+    let right = |text: &str, width: usize| format!("{:>width$}", text, width = width);
+
+    let mut header_cells = vec![TableCell::from("Column")];
+    header_cells.extend(shown.clone().zip(&widths[1..]).map(|(i, width)| TableCell::from(right(STATISTICS[i], *width))));
+    let rows: Vec<Row> = describe
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(i, (name, figures))| {
+            let mut cells = vec![TableCell::from(name.as_str())];
+            cells.extend(figures[shown.clone()].iter().zip(&widths[1..]).map(|(figure, width)| TableCell::from(right(figure, *width))));
+            let style = if i == describe.cursor { app.theme.cursor_entry() } else { Style::default() };
+            Row::new(cells).style(style)
+        })
+        .collect();
+
+    let table = Table::new(rows, widths.into_iter().map(|width| Constraint::Length(width as u16)))
+        .header(
+            Row::new(header_cells)
+                .style(Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD))
+                .bottom_margin(1),
+        )
+        .column_spacing(SPACING as u16)
+        .block(Block::default().title(" Numeric columns ").borders(Borders::ALL))
+        .style(Style::default().fg(app.theme.text));
+
+    // The table scrolls from where it was drawn last, just enough to keep the cursor in view
+    let mut state = TableState::default()
+        .with_offset(describe.offset.get())
+        .with_selected(Some(describe.cursor));
+    f.render_stateful_widget(table, chunks[1], &mut state);
+    describe.offset.set(state.offset());
+
+    // Footer
+    let footer_block = Block::default()
+        .title(" Info ")
+        .borders(Borders::ALL);
+
+    let footer_text = if describe.rows.is_empty() {
+        "No numeric columns".to_string()
+    } else {
+        let rows = match describe.sampled_from {
+            Some(total) => format!("first {} of {} filtered rows", format_count(describe.row_count), format_count(total)),
+            None => format!("{} filtered rows", format_count(describe.row_count)),
+        };
+        let mut text = format!("{} numeric columns | {}", describe.rows.len(), rows);
+        if shown.len() < STATISTICS.len() {
+            text.push_str(&format!(" | showing {} to {}", STATISTICS[shown.start], STATISTICS[shown.end - 1]));
+        }
+        text.push_str(" | std is the sample standard deviation");
+        text
+    };
+
+    let footer = Paragraph::new(footer_text).block(footer_block);
+    f.render_widget(footer, chunks[2]);
+}
@@ -0,0 +1,80 @@
+use crate::app::App;
+use crate::data::ENCODINGS;
+
+/// Render encoding selection UI
+pub fn render_encoding_selection(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::{
+        layout::{Constraint, Direction, Layout},
+        style::Style,
+        text::{Line, Span},
+        widgets::{Block, Borders, List, ListItem, Paragraph},
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),  // Header
+            Constraint::Min(0),     // Encoding list
+            Constraint::Length(3),  // Footer
+        ])
+        .split(area);
+
+    // Header
+    let header_block = Block::default()
+        .title(" Encoding Selection ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(app.theme.frame));
+
+    let header_text = vec![Line::from(vec![
+        Span::styled("↑↓", app.theme.key()),
+        Span::raw(" navigate | "),
+        Span::styled("Enter", app.theme.key()),
+        Span::raw(" reload | "),
+        Span::styled("Esc", app.theme.key()),
+        Span::raw(" keep current"),
+    ])];
+
+    let header = Paragraph::new(header_text).block(header_block);
+    f.render_widget(header, chunks[0]);
+
+    // Encoding list
+    let current = app.encoding_name();
+    let items: Vec<ListItem> = ENCODINGS
+        .iter()
+        .enumerate()
+        .map(|(i, encoding)| {
+            let is_current = current == Some(encoding.name());
+            let marker = if is_current { "●" } else { " " };
+            let content = format!("{} {}", marker, encoding.name());
+
+            let style = if i == app.encoding_selection_cursor {
+                app.theme.cursor_entry()
+            } else if is_current {
+                Style::default().fg(app.theme.good)
+            } else {
+                Style::default()
+            };
+
+            ListItem::new(content).style(style)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().title(" Encodings ").borders(Borders::ALL))
+        .style(Style::default().fg(app.theme.text));
+
+    f.render_widget(list, chunks[1]);
+
+    // Footer
+    let footer_block = Block::default()
+        .title(" Info ")
+        .borders(Borders::ALL);
+
+    let footer_text = format!(
+        "current: {} | pick another encoding if text looks garbled",
+        current.unwrap_or("-")
+    );
+
+    let footer = Paragraph::new(footer_text).block(footer_block);
+    f.render_widget(footer, chunks[2]);
+}
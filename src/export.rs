@@ -0,0 +1,137 @@
+use anyhow::{bail, Context, Result};
+use polars::prelude::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Rows fetched and written at a time by `write_chunked`
+pub const CHUNK_ROWS: usize = 50_000;
+
+/// Lower-cased extension of `path`, if it names a format rows can be written in
+fn format_of(path: &Path) -> Result<String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+    match extension {
+        Some(extension) if matches!(extension.as_str(), "csv" | "parquet" | "ndjson" | "jsonl" | "md") => Ok(extension),
+        _ => bail!(
+            "Cannot export to '{}': use a .csv, .parquet, .ndjson, .jsonl or .md file name",
+            path.display()
+        ),
+    }
+}
+
+/// Write `df` to `path`, in the format its extension names: CSV, Parquet (keeping the
+/// column types), newline-delimited JSON or a Markdown table
+pub fn write(df: &mut DataFrame, path: &Path) -> Result<()> {
+    let extension = format_of(path)?;
+    let extension = extension.as_str();
+    if extension == "md" {
+        return std::fs::write(path, markdown(df)).with_context(|| format!("Failed to write '{}'", path.display()));
+    }
+
+    let file = File::create(path).with_context(|| format!("Failed to create '{}'", path.display()))?;
+    let writer = BufWriter::new(file);
+    match extension {
+        "csv" => CsvWriter::new(writer).finish(df),
+        "parquet" => ParquetWriter::new(writer).finish(df).map(|_| ()),
+        _ => JsonWriter::new(writer).with_json_format(JsonFormat::JsonLines).finish(df),
+    }
+    .with_context(|| format!("Failed to write '{}'", path.display()))
+}
+
+/// Write `rows` rows to `path` as `write` does, fetching them `CHUNK_ROWS` at a time
+/// with `fetch(offset, limit)`, so tables read page by page never have to fit in memory
+pub fn write_chunked(path: &Path, rows: usize, fetch: impl Fn(usize, usize) -> Result<DataFrame>) -> Result<()> {
+    let extension = format_of(path)?;
+    let failed = || format!("Failed to write '{}'", path.display());
+    // The first chunk is fetched even without rows, for the column names and types
+    let mut first = fetch(0, rows.min(CHUNK_ROWS))?;
+    let rest = (CHUNK_ROWS..rows).step_by(CHUNK_ROWS);
+    let next = |offset: usize| fetch(offset, CHUNK_ROWS.min(rows - offset));
+
+    let file = File::create(path).with_context(|| format!("Failed to create '{}'", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    if extension == "parquet" {
+        let mut batched = ParquetWriter::new(writer).batched(&first.schema()).with_context(failed)?;
+        batched.write_batch(&first).with_context(failed)?;
+        for offset in rest {
+            batched.write_batch(&next(offset)?).with_context(failed)?;
+        }
+        batched.finish().with_context(failed)?;
+        return Ok(());
+    }
+
+    write_chunk(&mut writer, &extension, &mut first, true).with_context(failed)?;
+    for offset in rest {
+        write_chunk(&mut writer, &extension, &mut next(offset)?, false).with_context(failed)?;
+    }
+    writer.flush().with_context(failed)
+}
+
+/// Append the rows of `chunk` to a CSV, NDJSON or Markdown file, after the header when
+/// it is the `first`
+fn write_chunk(writer: &mut impl Write, extension: &str, chunk: &mut DataFrame, first: bool) -> Result<()> {
+    match extension {
+        "csv" => CsvWriter::new(writer).include_header(first).finish(chunk)?,
+        "md" => {
+            if first {
+                writer.write_all(markdown_header(chunk).as_bytes())?;
+            }
+            writer.write_all(markdown_rows(chunk).as_bytes())?;
+        }
+        _ => JsonWriter::new(writer).with_json_format(JsonFormat::JsonLines).finish(chunk)?,
+    }
+    Ok(())
+}
+
+/// `df` as CSV text, with a header line
+pub fn csv(df: &mut DataFrame) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    CsvWriter::new(&mut buffer).finish(df).context("Failed to write CSV")?;
+    Ok(buffer)
+}
+
+/// `df` as a GitHub-flavored Markdown table, with numeric columns aligned right
+pub fn markdown(df: &DataFrame) -> String {
+    markdown_header(df) + &markdown_rows(df)
+}
+
+/// One line of a Markdown table
+fn markdown_row(cells: Vec<String>) -> String {
+    format!("| {} |\n", cells.join(" | "))
+}
+
+/// The column names of a Markdown table and the line aligning numeric columns right
+fn markdown_header(df: &DataFrame) -> String {
+    let columns = df.get_columns();
+    let mut header = markdown_row(columns.iter().map(|column| markdown_cell(column.name())).collect());
+    header.push_str(&markdown_row(columns
+        .iter()
+        .map(|column| if column.dtype().is_numeric() { "---:" } else { "---" }.to_string())
+        .collect()));
+    header
+}
+
+/// The rows of `df` as lines of a Markdown table
+fn markdown_rows(df: &DataFrame) -> String {
+    let columns = df.get_columns();
+    let mut table = String::new();
+    for i in 0..df.height() {
+        let cells = columns
+            .iter()
+            .map(|column| match column.get(i) {
+                Ok(AnyValue::Null) | Err(_) => String::new(),
+                Ok(value) => markdown_cell(&value.str_value()),
+            })
+            .collect();
+        table.push_str(&markdown_row(cells));
+    }
+    table
+}
+
+/// A value as the text of one table cell: pipes escaped, line breaks as `<br>`
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>")
+}
@@ -1,5 +1,7 @@
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use polars::prelude::*;
+use polars::sql::sql_expr;
 
 /// Filter expression for advanced filtering
 #[derive(Debug, Clone)]
@@ -9,6 +11,8 @@ pub enum FilterExpr {
         column: String,
         op: ComparisonOp,
         value: String,
+        /// Whether string matches (contains, equality, prefix/suffix) ignore case
+        ignore_case: bool,
     },
     /// Logical AND
     And(Box<FilterExpr>, Box<FilterExpr>),
@@ -16,8 +20,13 @@ pub enum FilterExpr {
     Or(Box<FilterExpr>, Box<FilterExpr>),
     /// Negation
     Not(Box<FilterExpr>),
+    /// Polars SQL expression written after `sql:`, e.g. `sql: ABS(bid - ask) > 1`
+    Sql(Expr),
 }
 
+/// Prefix of a condition that is handed to Polars as an SQL expression
+const SQL_PREFIX: &str = "sql:";
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ComparisonOp {
     Equal,           // =
@@ -27,110 +36,464 @@ pub enum ComparisonOp {
     GreaterOrEqual,  // >=
     LessOrEqual,     // <=
     Contains,        // : (substring match)
+    NotContains,     // !:
+    Regex,           // ~ (regular expression match)
+    NotRegex,        // !~
+    StartsWith,      // ^=
+    EndsWith,        // $=
+    IsNull,          // IS NULL (no value)
+    IsNotNull,       // IS NOT NULL
+    Fuzzy,           // global search in fuzzy mode (see `fuzzy_match`)
 }
 
-impl FilterExpr {
-    /// Parse filter expression from string
-    pub fn parse(input: &str) -> Result<Self> {
-        let input = input.trim();
-        if input.is_empty() {
-            bail!("Empty filter expression");
+/// Settings that change how a parsed filter matches, toggled from the UI
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FilterOptions {
+    /// String matches (contains, equality, prefix/suffix) ignore case
+    pub ignore_case: bool,
+    /// Global search matches fuzzily instead of by substring
+    pub fuzzy: bool,
+    /// Global search skips non-string columns instead of matching their text form,
+    /// which is faster on wide numeric data. Servers always search every column.
+    pub strings_only: bool,
+}
+
+/// Pseudo column holding each row's 1-based position, e.g. `#row BETWEEN 1000 AND 2000`
+pub(crate) const ROW_COLUMN: &str = "#row";
+
+/// SQL flavour a filter is rendered in for server-side filtering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SqlDialect {
+    Postgres,
+    MySql,
+}
+
+impl SqlDialect {
+    /// Quote an identifier (column or table name)
+    pub fn ident(&self, name: &str) -> String {
+        match self {
+            SqlDialect::Postgres => format!("\"{}\"", name.replace('"', "\"\"")),
+            SqlDialect::MySql => format!("`{}`", name.replace('`', "``")),
         }
+    }
 
-        // Try to parse logical expressions first
-        if let Some(expr) = Self::try_parse_or(input)? {
-            return Ok(expr);
+    /// Quote a string literal
+    fn string(&self, value: &str) -> String {
+        let value = match self {
+            SqlDialect::Postgres => value.to_string(),
+            // MySQL treats backslashes in literals as escapes
+            SqlDialect::MySql => value.replace('\\', "\\\\"),
+        };
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    /// Cast an expression to text
+    fn text(&self, expr: &str) -> String {
+        match self {
+            SqlDialect::Postgres => format!("CAST({} AS TEXT)", expr),
+            SqlDialect::MySql => format!("CAST({} AS CHAR)", expr),
         }
+    }
 
-        // Try AND
-        if let Some(expr) = Self::try_parse_and(input)? {
-            return Ok(expr);
+    /// Length of `expr` in characters
+    fn length(&self, expr: &str) -> String {
+        format!("CHAR_LENGTH({})", self.text(expr))
+    }
+
+    /// Condition matching `expr` against a regular expression
+    fn regex(&self, expr: &str, pattern: &str, negated: bool) -> String {
+        let op = match (self, negated) {
+            (SqlDialect::Postgres, false) => "~",
+            (SqlDialect::Postgres, true) => "!~",
+            (SqlDialect::MySql, false) => "REGEXP",
+            (SqlDialect::MySql, true) => "NOT REGEXP",
+        };
+        format!("{} {} {}", self.text(expr), op, self.string(pattern))
+    }
+
+    /// Condition matching `expr` against a LIKE pattern built by `like_pattern`.
+    /// `!` is used as the escape character since it needs no quoting in either dialect.
+    fn like(&self, expr: &str, pattern: &str, ignore_case: bool) -> String {
+        if ignore_case {
+            let pattern = self.string(&pattern.to_lowercase());
+            format!("LOWER({}) LIKE {} ESCAPE '!'", self.text(expr), pattern)
+        } else {
+            format!("{} LIKE {} ESCAPE '!'", self.text(expr), self.string(pattern))
         }
+    }
+}
+
+/// Escape LIKE wildcards in `value` so it matches literally
+fn like_pattern(value: &str) -> String {
+    value
+        .replace('!', "!!")
+        .replace('%', "!%")
+        .replace('_', "!_")
+}
+
+/// LIKE pattern matching the characters of `value` in order, with anything in between
+fn subsequence_pattern(value: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in value.chars().filter(|c| c.is_alphanumeric()) {
+        pattern.push_str(&like_pattern(&c.to_string()));
+        pattern.push('%');
+    }
+    pattern
+}
+
+/// Fuzzy match of a string expression against `value`, see `fuzzy_match`
+fn fuzzy_expr(text: Expr, value: &str) -> Expr {
+    let term = fuzzy_chars(value);
+    text.map(
+        move |column| {
+            let matches: BooleanChunked = column
+                .str()?
+                .into_iter()
+                .map(|opt_val| opt_val.map(|v| fuzzy_match(v, &term)))
+                .collect();
+            Ok(Some(matches.into_column()))
+        },
+        GetOutput::from_type(DataType::Boolean),
+    )
+}
+
+/// Substring match of a string expression, lower-casing both sides first when
+/// `ignore_case` is set
+fn contains_expr(text: Expr, value: &str, ignore_case: bool) -> Expr {
+    if ignore_case {
+        text.str().to_lowercase().str().contains_literal(lit(value.to_lowercase()))
+    } else {
+        text.str().contains_literal(lit(value.to_string()))
+    }
+}
 
-        // Try NOT
-        if let Some(expr) = Self::try_parse_not(input)? {
-            return Ok(expr);
+/// A Date, Datetime or Time column and a value parsed into the same representation,
+/// both as integers to compare. Partial dates mean the start of the period: `2024-06` is
+/// 2024-06-01 00:00:00. Timezone-aware columns are compared in UTC.
+fn temporal_operands(target: Expr, dtype: &DataType, value: &str) -> Result<(Expr, Expr)> {
+    if *dtype == DataType::Time {
+        let time = parse_time(value).with_context(|| format!("Cannot parse '{}' as a time (HH:MM[:SS])", value))?;
+        let nanos = time.num_seconds_from_midnight() as i64 * 1_000_000_000 + time.nanosecond() as i64;
+        return Ok((target.cast(DataType::Int64), lit(nanos)));
+    }
+    let unit = match dtype {
+        DataType::Datetime(unit, _) => *unit,
+        _ => TimeUnit::Milliseconds,
+    };
+    let datetime = parse_datetime(value)
+        .with_context(|| format!("Cannot parse '{}' as a date (YYYY[-MM[-DD]] [HH:MM[:SS]])", value))?
+        .and_utc();
+    let timestamp = match unit {
+        TimeUnit::Nanoseconds => datetime
+            .timestamp_nanos_opt()
+            .context("Date is out of range for nanosecond timestamps")?,
+        TimeUnit::Microseconds => datetime.timestamp_micros(),
+        TimeUnit::Milliseconds => datetime.timestamp_millis(),
+    };
+    let physical = target.cast(DataType::Datetime(unit, None)).cast(DataType::Int64);
+    Ok((physical, lit(timestamp)))
+}
+
+/// Lexical unit of a filter expression
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    /// A single condition such as `Price > 5000`, kept as written
+    Condition(String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::And => "'AND'".to_string(),
+            Token::Or => "'OR'".to_string(),
+            Token::Not => "'NOT'".to_string(),
+            Token::Condition(text) => format!("'{}'", text),
         }
+    }
+}
+
+/// Upper-cased word at the start of `input`, if it is followed by a word boundary
+fn leading_word(input: &str) -> Option<(String, &str)> {
+    let word_len = input
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(input.len());
+    let (word, rest) = input.split_at(word_len);
+    let at_boundary = rest.is_empty() || rest.starts_with(char::is_whitespace) || rest.starts_with('(');
+    (word_len > 0 && at_boundary).then(|| (word.to_ascii_uppercase(), rest))
+}
 
-        // Parse comparison
-        Self::parse_comparison(input)
+/// Logical keyword at the start of `input` and the text after it
+fn keyword_at(input: &str) -> Option<(Token, &str)> {
+    let (word, rest) = leading_word(input)?;
+    match word.as_str() {
+        "AND" => Some((Token::And, rest)),
+        "OR" => Some((Token::Or, rest)),
+        "NOT" => Some((Token::Not, rest)),
+        _ => None,
     }
+}
+
+/// Split a filter into parentheses, logical keywords and conditions
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
 
-    fn try_parse_or(input: &str) -> Result<Option<Self>> {
-        // Find " OR " (case-insensitive, with spaces)
-        let input_upper = input.to_uppercase();
-        if let Some(pos) = input_upper.find(" OR ") {
-            let left = input[..pos].trim();
-            let right = input[pos + 4..].trim();
+    while let Some(c) = rest.chars().next() {
+        rest = match c {
+            '(' => {
+                tokens.push(Token::LParen);
+                &rest[1..]
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                &rest[1..]
+            }
+            _ => match keyword_at(rest) {
+                Some((keyword, after)) => {
+                    tokens.push(keyword);
+                    after
+                }
+                None => {
+                    let end = condition_end(rest);
+                    tokens.push(Token::Condition(rest[..end].trim_end().to_string()));
+                    &rest[end..]
+                }
+            },
+        }
+        .trim_start();
+    }
+    Ok(tokens)
+}
 
-            let left_expr = Self::parse(left)?;
-            let right_expr = Self::parse(right)?;
+/// Length of the condition at the start of `input`. A condition runs until an AND/OR
+/// keyword or a `)` closing an enclosing group. Quoted text and parentheses inside a value
+/// (e.g. the regex `~ ^(IC|IF)`) are kept, as is the AND of a `BETWEEN low AND high`.
+fn condition_end(input: &str) -> usize {
+    // An `sql:` expression has its own AND/OR, so it runs to the end of its group
+    let raw = input
+        .get(..SQL_PREFIX.len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(SQL_PREFIX));
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut open_between = false;
+    let mut prev = ' ';
 
-            return Ok(Some(FilterExpr::Or(
-                Box::new(left_expr),
-                Box::new(right_expr),
-            )));
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if let Some(q) = quote {
+            if c == '\\' && input[i + 1..].starts_with([q, '\\']) {
+                chars.next();
+            } else if c == q {
+                quote = None;
+            }
+            prev = c;
+            continue;
         }
-        Ok(None)
+        match c {
+            // Only a quote opening a word starts quoted text, so `O'Brien` stays literal
+            '"' | '\'' if prev.is_whitespace() || "=<>!:~".contains(prev) => quote = Some(c),
+            '(' => depth += 1,
+            ')' if depth == 0 => return i,
+            ')' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 && !raw => {
+                let word = leading_word(input[i..].trim_start()).map(|(word, _)| word);
+                match word.as_deref() {
+                    Some("AND") if open_between => open_between = false,
+                    Some("AND" | "OR") => return i,
+                    Some("BETWEEN") => open_between = true,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        prev = c;
     }
+    input.len()
+}
 
-    fn try_parse_and(input: &str) -> Result<Option<Self>> {
-        // Find " AND " (case-insensitive, with spaces)
-        let input_upper = input.to_uppercase();
-        if let Some(pos) = input_upper.find(" AND ") {
-            let left = input[..pos].trim();
-            let right = input[pos + 5..].trim();
+/// `value` without the pair of quotes around it, if it has one, and with a `\"` (or `\'`)
+/// and `\\` inside the quotes read as the character escaped
+fn unquote(value: &str) -> String {
+    let quote = value.chars().next().filter(|&c| c == '"' || c == '\'');
+    let Some(quote) = quote.filter(|&q| value.len() > 1 && value.ends_with(q)) else {
+        return value.to_string();
+    };
+    let mut text = String::new();
+    let mut chars = value[1..value.len() - 1].chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek().is_some_and(|&next| next == quote || next == '\\') {
+            text.extend(chars.next());
+        } else {
+            text.push(c);
+        }
+    }
+    text
+}
 
-            let left_expr = Self::parse(left)?;
-            let right_expr = Self::parse(right)?;
+/// Quote a value for use in a filter expression when it would not parse as a bare word,
+/// so that `unquote` gives it back
+pub fn quote_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.contains(|c: char| c.is_whitespace() || "()\"'".contains(c));
+    if !needs_quotes {
+        return value.to_string();
+    }
+    let quote = if value.contains('"') && !value.contains('\'') { '\'' } else { '"' };
+    let escaped = value.replace('\\', "\\\\").replace(quote, &format!("\\{}", quote));
+    format!("{}{}{}", quote, escaped, quote)
+}
+
+/// Precedence-climbing parser over the token stream
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
 
-            return Ok(Some(FilterExpr::And(
-                Box::new(left_expr),
-                Box::new(right_expr),
-            )));
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Parse binary operators binding at least as tightly as `min_precedence`
+    fn parse_expr(&mut self, min_precedence: u8) -> Result<FilterExpr> {
+        let mut left = self.parse_unary()?;
+
+        loop {
+            let precedence = match self.tokens.get(self.pos) {
+                Some(Token::Or) => 1,
+                Some(Token::And) => 2,
+                _ => break,
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            let op = self.next();
+            let right = self.parse_expr(precedence + 1)?;
+            left = match op {
+                Some(Token::Or) => FilterExpr::Or(Box::new(left), Box::new(right)),
+                _ => FilterExpr::And(Box::new(left), Box::new(right)),
+            };
         }
-        Ok(None)
+        Ok(left)
     }
 
-    fn try_parse_not(input: &str) -> Result<Option<Self>> {
-        let input_upper = input.to_uppercase();
-        if input_upper.starts_with("NOT ") {
-            let inner = input[4..].trim();
-            let inner_expr = Self::parse(inner)?;
-            return Ok(Some(FilterExpr::Not(Box::new(inner_expr))));
+    /// Parse a condition, a parenthesized group, or a negation of either
+    fn parse_unary(&mut self) -> Result<FilterExpr> {
+        match self.next() {
+            Some(Token::Not) => Ok(FilterExpr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let expr = self.parse_expr(0)?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => bail!("Missing closing parenthesis"),
+                }
+            }
+            Some(Token::Condition(text)) => FilterExpr::parse_comparison(&text),
+            Some(token) => bail!("Expected a condition before {}", token.describe()),
+            None => bail!("Expected a condition at end of filter"),
+        }
+    }
+}
+
+impl FilterExpr {
+    /// Parse filter expression from string.
+    ///
+    /// NOT binds tighter than AND, which binds tighter than OR; parentheses group.
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            bail!("Empty filter expression");
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_expr(0)?;
+        match parser.next() {
+            None => Ok(expr),
+            Some(token) => bail!("Unexpected {}", token.describe()),
         }
-        Ok(None)
     }
 
     fn parse_comparison(input: &str) -> Result<Self> {
-        // Try operators in order of precedence (longer first to avoid partial matches)
+        if let Some(prefix) = input.get(..SQL_PREFIX.len()) {
+            if prefix.eq_ignore_ascii_case(SQL_PREFIX) {
+                let expr = sql_expr(input[SQL_PREFIX.len()..].trim())
+                    .map_err(|e| anyhow!("Invalid SQL expression: {}", e))?;
+                return Ok(FilterExpr::Sql(expr));
+            }
+        }
+
+        // Longer operators first, so `>=` wins over `>` at the same position
         let operators = [
             (">=", ComparisonOp::GreaterOrEqual),
             ("<=", ComparisonOp::LessOrEqual),
             ("!=", ComparisonOp::NotEqual),
+            ("!~", ComparisonOp::NotRegex),
+            ("!:", ComparisonOp::NotContains),
+            ("^=", ComparisonOp::StartsWith),
+            ("$=", ComparisonOp::EndsWith),
             ("=", ComparisonOp::Equal),
             (">", ComparisonOp::GreaterThan),
             ("<", ComparisonOp::LessThan),
             (":", ComparisonOp::Contains),
+            ("~", ComparisonOp::Regex),
         ];
 
-        for (op_str, op) in operators {
-            if let Some(pos) = input.find(op_str) {
-                let column = input[..pos].trim().to_string();
-                let value = input[pos + op_str.len()..].trim();
-
-                // Remove quotes if present
-                let value = value
-                    .trim_matches('"')
-                    .trim_matches('\'')
-                    .to_string();
+        // Split at the leftmost operator, so values may contain operator characters
+        // (e.g. `InstrumentID ~ ^IC\d{4}$` or `Time > 09:30:00`)
+        let first = operators
+            .into_iter()
+            .filter_map(|(op_str, op)| input.find(op_str).map(|pos| (pos, op_str, op)))
+            .min_by_key(|(pos, op_str, _)| (*pos, std::cmp::Reverse(op_str.len())));
 
-                if column.is_empty() || value.is_empty() {
-                    bail!("Invalid comparison: both column and value required");
+        // `column IS [NOT] NULL`
+        if first.is_none() {
+            let input_upper = input.to_ascii_uppercase();
+            for (suffix, op) in [(" IS NOT NULL", ComparisonOp::IsNotNull), (" IS NULL", ComparisonOp::IsNull)] {
+                if input_upper.ends_with(suffix) {
+                    let column = input[..input.len() - suffix.len()].trim().to_string();
+                    if column.is_empty() {
+                        bail!("Invalid null check: column required");
+                    }
+                    return Ok(FilterExpr::Comparison {
+                        column,
+                        op,
+                        value: String::new(),
+                        ignore_case: false,
+                    });
                 }
+            }
+        }
+
+        // `column BETWEEN low AND high`, unless the keyword is part of a value
+        let between = input.to_ascii_uppercase().find(" BETWEEN ");
+        if let Some(pos) = between.filter(|b| first.as_ref().is_none_or(|(p, _, _)| b < p)) {
+            return Self::parse_between(input[..pos].trim(), &input[pos + 9..]);
+        }
+
+        if let Some((pos, op_str, op)) = first {
+            let column = input[..pos].trim().to_string();
+            let value = input[pos + op_str.len()..].trim();
+
+            let value = unquote(value);
+
+            if column.is_empty() || value.is_empty() {
+                bail!("Invalid comparison: both column and value required");
+            }
 
-                return Ok(FilterExpr::Comparison { column, op, value });
+            if matches!(op, ComparisonOp::Regex | ComparisonOp::NotRegex) {
+                regex::Regex::new(&value).with_context(|| format!("Invalid regex '{}'", value))?;
             }
+
+            return Ok(FilterExpr::Comparison { column, op, value, ignore_case: false });
         }
 
         // No operator found - treat as global search (contains in any column)
@@ -138,181 +501,613 @@ impl FilterExpr {
             column: "*".to_string(),
             op: ComparisonOp::Contains,
             value: input.to_string(),
+            ignore_case: false,
         })
     }
 
-    /// Apply filter expression to DataFrame
-    pub fn apply(&self, df: &DataFrame) -> Result<DataFrame> {
-        let mask = self.evaluate(df)?;
-        df.filter(&mask).context("Failed to apply filter")
+    /// Parse the `low AND high` part of a BETWEEN into an inclusive `>= low AND <= high` pair
+    fn parse_between(column: &str, range: &str) -> Result<Self> {
+        let pos = range
+            .to_ascii_uppercase()
+            .find(" AND ")
+            .context("BETWEEN requires 'low AND high'")?;
+        let (low, high) = (unquote(range[..pos].trim()), unquote(range[pos + 5..].trim()));
+
+        if column.is_empty() || low.is_empty() || high.is_empty() {
+            bail!("Invalid BETWEEN: column, low and high values required");
+        }
+
+        let bound = |op, value| FilterExpr::Comparison {
+            column: column.to_string(),
+            op,
+            value,
+            ignore_case: false,
+        };
+        Ok(FilterExpr::And(
+            Box::new(bound(ComparisonOp::GreaterOrEqual, low)),
+            Box::new(bound(ComparisonOp::LessOrEqual, high)),
+        ))
+    }
+
+    /// Apply the UI's matching settings to the expression
+    pub fn with_options(self, options: FilterOptions) -> Self {
+        let expr = if options.ignore_case { self.ignoring_case() } else { self };
+        if options.fuzzy { expr.fuzzy_global_search() } else { expr }
     }
 
-    /// Evaluate expression to boolean mask
-    fn evaluate(&self, df: &DataFrame) -> Result<BooleanChunked> {
+    /// Turn global search terms into fuzzy matches
+    fn fuzzy_global_search(self) -> Self {
         match self {
-            FilterExpr::Comparison { column, op, value } => {
-                if column == "*" {
-                    // Global search across all columns
-                    Self::evaluate_global_search(df, value)
-                } else {
-                    Self::evaluate_comparison(df, column, op, value)
+            FilterExpr::Comparison { column, op: ComparisonOp::Contains, value, ignore_case } if column == "*" => {
+                FilterExpr::Comparison {
+                    column,
+                    op: ComparisonOp::Fuzzy,
+                    value,
+                    ignore_case,
                 }
             }
             FilterExpr::And(left, right) => {
-                let left_mask = left.evaluate(df)?;
-                let right_mask = right.evaluate(df)?;
-                Ok(&left_mask & &right_mask)
+                FilterExpr::And(Box::new(left.fuzzy_global_search()), Box::new(right.fuzzy_global_search()))
             }
             FilterExpr::Or(left, right) => {
-                let left_mask = left.evaluate(df)?;
-                let right_mask = right.evaluate(df)?;
-                Ok(&left_mask | &right_mask)
+                FilterExpr::Or(Box::new(left.fuzzy_global_search()), Box::new(right.fuzzy_global_search()))
+            }
+            FilterExpr::Not(inner) => FilterExpr::Not(Box::new(inner.fuzzy_global_search())),
+            expr => expr,
+        }
+    }
+
+    /// Make every string match in the expression case-insensitive
+    fn ignoring_case(self) -> Self {
+        match self {
+            FilterExpr::Comparison { column, op, value, .. } => FilterExpr::Comparison {
+                column,
+                op,
+                value,
+                ignore_case: true,
+            },
+            FilterExpr::And(left, right) => {
+                FilterExpr::And(Box::new(left.ignoring_case()), Box::new(right.ignoring_case()))
             }
-            FilterExpr::Not(inner) => {
-                let mask = inner.evaluate(df)?;
-                Ok(!mask)
+            FilterExpr::Or(left, right) => {
+                FilterExpr::Or(Box::new(left.ignoring_case()), Box::new(right.ignoring_case()))
             }
+            FilterExpr::Not(inner) => FilterExpr::Not(Box::new(inner.ignoring_case())),
+            FilterExpr::Sql(expr) => FilterExpr::Sql(expr),
         }
     }
 
-    fn evaluate_global_search(df: &DataFrame, pattern: &str) -> Result<BooleanChunked> {
-        let mut mask: Option<BooleanChunked> = None;
+    /// Substrings looked for by `:` conditions and global search, with the column they
+    /// apply to (None for global search). Negated conditions are left out, since their
+    /// text does not appear in the rows they keep.
+    pub fn contains_terms(&self) -> Vec<(Option<&str>, &str)> {
+        match self {
+            FilterExpr::Comparison { column, op: ComparisonOp::Contains, value, .. } => {
+                let column = (column != "*").then_some(column.as_str());
+                vec![(column, value.as_str())]
+            }
+            FilterExpr::Comparison { .. } | FilterExpr::Not(_) | FilterExpr::Sql(_) => Vec::new(),
+            FilterExpr::And(left, right) | FilterExpr::Or(left, right) => {
+                let mut terms = left.contains_terms();
+                terms.extend(right.contains_terms());
+                terms
+            }
+        }
+    }
 
-        for col_name in df.get_column_names() {
-            if let Ok(col) = df.column(col_name) {
-                if let Ok(str_col) = col.str() {
-                    if let Ok(contains) = str_col.contains_literal(pattern) {
-                        mask = match mask {
-                            None => Some(contains),
-                            Some(existing) => Some(existing | contains),
-                        };
+    /// Render the expression as a SQL WHERE condition, for sources that filter server-side.
+    /// `columns` is used to expand global search, `is_numeric` decides how values are compared.
+    pub fn to_sql(
+        &self,
+        columns: &[String],
+        is_numeric: &dyn Fn(&str) -> bool,
+        dialect: SqlDialect,
+    ) -> Result<String> {
+        match self {
+            FilterExpr::Comparison { column, op, value, ignore_case } => {
+                let ignore_case = *ignore_case;
+                if column == "*" {
+                    // Global search across all columns. Fuzzy search keeps the subsequence
+                    // match but has no typo tolerance in SQL.
+                    let (pattern, ignore_case) = match op {
+                        ComparisonOp::Fuzzy => (subsequence_pattern(value), true),
+                        _ => (format!("%{}%", like_pattern(value)), ignore_case),
+                    };
+                    let terms: Vec<String> = columns
+                        .iter()
+                        .map(|c| dialect.like(&dialect.ident(c), &pattern, ignore_case))
+                        .collect();
+                    if terms.is_empty() {
+                        bail!("No searchable columns found");
                     }
+                    return Ok(format!("({})", terms.join(" OR ")));
                 }
-            }
-        }
 
-        mask.context("No searchable columns found")
-    }
+                if column == ROW_COLUMN && !columns.iter().any(|c| c == column) {
+                    bail!("{} is not supported for database sources, whose rows have no fixed order", ROW_COLUMN);
+                }
+                let exists = |name: &str| columns.iter().any(|c| c == name);
+                let (ident, numeric) = match length_of(column) {
+                    Some(name) if !exists(column) => {
+                        if !exists(name) {
+                            return Err(unknown_column(name, columns.iter().map(String::as_str)));
+                        }
+                        (dialect.length(&dialect.ident(name)), true)
+                    }
+                    _ => {
+                        if !exists(column) {
+                            return Err(unknown_column(column, columns.iter().map(String::as_str)));
+                        }
+                        (dialect.ident(column), is_numeric(column))
+                    }
+                };
 
-    fn evaluate_comparison(
-        df: &DataFrame,
-        column: &str,
-        op: &ComparisonOp,
-        value: &str,
-    ) -> Result<BooleanChunked> {
-        let col = df
-            .column(column)
-            .with_context(|| format!("Column '{}' not found", column))?;
+                let pattern = match op {
+                    ComparisonOp::Contains => Some(format!("%{}%", like_pattern(value))),
+                    ComparisonOp::StartsWith => Some(format!("{}%", like_pattern(value))),
+                    ComparisonOp::EndsWith => Some(format!("%{}", like_pattern(value))),
+                    ComparisonOp::Fuzzy => Some(subsequence_pattern(value)),
+                    _ => None,
+                };
+                if let Some(pattern) = pattern {
+                    return Ok(dialect.like(&ident, &pattern, ignore_case));
+                }
+                if *op == ComparisonOp::NotContains {
+                    let pattern = format!("%{}%", like_pattern(value));
+                    return Ok(format!("NOT ({})", dialect.like(&ident, &pattern, ignore_case)));
+                }
+                if *op == ComparisonOp::IsNull {
+                    return Ok(format!("{} IS NULL", ident));
+                }
+                if *op == ComparisonOp::IsNotNull {
+                    return Ok(format!("{} IS NOT NULL", ident));
+                }
+                if matches!(op, ComparisonOp::Regex | ComparisonOp::NotRegex) {
+                    return Ok(dialect.regex(&ident, value, *op == ComparisonOp::NotRegex));
+                }
 
-        match op {
-            ComparisonOp::Contains => {
-                // String contains (substring match)
-                let str_col = col.str().context("Column is not string type for contains operation")?;
-                str_col.contains_literal(value).context("Contains operation failed")
-            }
-            ComparisonOp::Equal => {
-                // Try as string first, then numeric
-                if let Ok(str_col) = col.str() {
-                    Ok(str_col.equal(value))
-                } else if let Ok(num_value) = value.parse::<f64>() {
-                    Self::numeric_comparison(col, num_value, |a, b| a == b)
+                let sql_op = match op {
+                    ComparisonOp::Equal => "=",
+                    ComparisonOp::NotEqual => "<>",
+                    ComparisonOp::GreaterThan => ">",
+                    ComparisonOp::LessThan => "<",
+                    ComparisonOp::GreaterOrEqual => ">=",
+                    ComparisonOp::LessOrEqual => "<=",
+                    ComparisonOp::Contains
+                    | ComparisonOp::NotContains
+                    | ComparisonOp::StartsWith
+                    | ComparisonOp::EndsWith
+                    | ComparisonOp::Regex
+                    | ComparisonOp::NotRegex
+                    | ComparisonOp::IsNull
+                    | ComparisonOp::IsNotNull
+                    | ComparisonOp::Fuzzy => unreachable!(),
+                };
+
+                if numeric {
+                    // MySQL stores booleans as TINYINT(1)
+                    let num_value = value
+                        .parse::<f64>()
+                        .ok()
+                        .or_else(|| parse_bool(value).map(|b| if b { 1.0 } else { 0.0 }))
+                        .with_context(|| format!("Cannot compare numeric column '{}' with '{}'", column, value))?;
+                    Ok(format!("{} {} {}", ident, sql_op, num_value))
+                } else if ignore_case && matches!(op, ComparisonOp::Equal | ComparisonOp::NotEqual) {
+                    let value = dialect.string(&value.to_lowercase());
+                    Ok(format!("LOWER({}) {} {}", dialect.text(&ident), sql_op, value))
                 } else {
-                    bail!("Cannot parse value for equality comparison")
+                    Ok(format!("{} {} {}", dialect.text(&ident), sql_op, dialect.string(value)))
                 }
             }
-            ComparisonOp::NotEqual => {
-                if let Ok(str_col) = col.str() {
-                    Ok(str_col.not_equal(value))
-                } else if let Ok(num_value) = value.parse::<f64>() {
-                    Self::numeric_comparison(col, num_value, |a, b| a != b)
-                } else {
-                    bail!("Cannot parse value for inequality comparison")
+            FilterExpr::And(left, right) => Ok(format!(
+                "({} AND {})",
+                left.to_sql(columns, is_numeric, dialect)?,
+                right.to_sql(columns, is_numeric, dialect)?
+            )),
+            FilterExpr::Or(left, right) => Ok(format!(
+                "({} OR {})",
+                left.to_sql(columns, is_numeric, dialect)?,
+                right.to_sql(columns, is_numeric, dialect)?
+            )),
+            FilterExpr::Not(inner) => Ok(format!("(NOT {})", inner.to_sql(columns, is_numeric, dialect)?)),
+            FilterExpr::Sql(_) => bail!("sql: expressions are not supported for database sources"),
+        }
+    }
+
+    /// Compile the expression into a Polars expression over a frame with `schema`, run
+    /// vectorized by the lazy engine (and pushed into scans of lazy sources). `#row`
+    /// needs a 1-based row index column of that name, which `filter_lazy` adds.
+    pub fn to_expr(&self, schema: &Schema, strings_only: bool) -> Result<Expr> {
+        match self {
+            FilterExpr::Comparison { column, op, value, ignore_case } => {
+                let ignore_case = *ignore_case;
+                if column == "*" {
+                    let terms: Vec<Expr> = schema
+                        .iter()
+                        // Types without a text form are skipped
+                        .filter(|(_, dtype)| !dtype.is_nested() && **dtype != DataType::Binary)
+                        .filter(|(_, dtype)| !strings_only || is_text(dtype))
+                        .map(|(name, _)| {
+                            let text = col(name.clone()).cast(DataType::String);
+                            match op {
+                                ComparisonOp::Fuzzy => fuzzy_expr(text, value),
+                                _ => contains_expr(text, value, ignore_case),
+                            }
+                        })
+                        .collect();
+                    return terms
+                        .into_iter()
+                        .reduce(|a, b| a.or(b))
+                        .context("No searchable columns found");
                 }
-            }
-            ComparisonOp::GreaterThan => {
-                // Try numeric comparison first
-                if let Ok(num_value) = value.parse::<f64>() {
-                    Self::numeric_comparison(col, num_value, |a, b| a > b)
-                } else if let Ok(str_col) = col.str() {
-                    // Fall back to string comparison (works for times like "09:30:00")
-                    Self::string_comparison(str_col, value, |a, b| a > b)
+
+                let (target, dtype) = match schema.get(column) {
+                    Some(dtype) => (col(column.as_str()), dtype.clone()),
+                    None if column == ROW_COLUMN => (col(ROW_COLUMN), DataType::Int64),
+                    None => match length_of(column) {
+                        Some(name) if schema.contains(name) => (
+                            col(name).cast(DataType::String).str().len_chars().cast(DataType::Int64),
+                            DataType::Int64,
+                        ),
+                        Some(name) => return Err(unknown_column(name, schema.iter_names().map(|n| n.as_str()))),
+                        None => return Err(unknown_column(column, schema.iter_names().map(|n| n.as_str()))),
+                    },
+                };
+                let is_string = is_text(&dtype);
+                // Categoricals are matched by their text, except for exact equality,
+                // which Polars checks on the category codes
+                let exact_equality = matches!(op, ComparisonOp::Equal | ComparisonOp::NotEqual) && !ignore_case;
+                let target = if matches!(dtype, DataType::Categorical(..)) && !exact_equality {
+                    target.cast(DataType::String)
                 } else {
-                    bail!("Column must be numeric or string type for > comparison")
+                    target
+                };
+                let needs_string = |what: &str| -> Result<()> {
+                    if is_string {
+                        Ok(())
+                    } else {
+                        bail!("Column is not string type for {}", what)
+                    }
+                };
+
+                match op {
+                    ComparisonOp::IsNull => Ok(target.is_null()),
+                    ComparisonOp::IsNotNull => Ok(target.is_not_null()),
+                    ComparisonOp::Contains => {
+                        needs_string("contains operation")?;
+                        Ok(contains_expr(target, value, ignore_case))
+                    }
+                    ComparisonOp::NotContains => {
+                        needs_string("does-not-contain match")?;
+                        Ok(contains_expr(target, value, ignore_case).not())
+                    }
+                    ComparisonOp::Fuzzy => {
+                        needs_string("fuzzy match")?;
+                        Ok(fuzzy_expr(target, value))
+                    }
+                    ComparisonOp::StartsWith | ComparisonOp::EndsWith => {
+                        needs_string("prefix and suffix matches")?;
+                        let (target, value) = if ignore_case {
+                            (target.str().to_lowercase(), value.to_lowercase())
+                        } else {
+                            (target, value.clone())
+                        };
+                        Ok(match op {
+                            ComparisonOp::StartsWith => target.str().starts_with(lit(value)),
+                            _ => target.str().ends_with(lit(value)),
+                        })
+                    }
+                    ComparisonOp::Regex | ComparisonOp::NotRegex => {
+                        needs_string("regex match")?;
+                        regex::Regex::new(value).with_context(|| format!("Invalid regex '{}'", value))?;
+                        let matches = target.str().contains(lit(value.clone()), true);
+                        Ok(if *op == ComparisonOp::NotRegex { matches.not() } else { matches })
+                    }
+                    ComparisonOp::Equal
+                    | ComparisonOp::NotEqual
+                    | ComparisonOp::GreaterThan
+                    | ComparisonOp::LessThan
+                    | ComparisonOp::GreaterOrEqual
+                    | ComparisonOp::LessOrEqual => {
+                        let equality = matches!(op, ComparisonOp::Equal | ComparisonOp::NotEqual);
+                        let (target, value) = if is_date_or_time(&dtype) {
+                            temporal_operands(target, &dtype, value)?
+                        } else if is_string {
+                            if ignore_case && equality {
+                                (target.str().to_lowercase(), lit(value.to_lowercase()))
+                            } else {
+                                (target, lit(value.clone()))
+                            }
+                        } else if dtype == DataType::Boolean && equality {
+                            let value = parse_bool(value)
+                                .with_context(|| format!("Value '{}' must be true or false for a boolean column", value))?;
+                            (target, lit(value))
+                        } else if dtype.is_numeric() {
+                            let value = value
+                                .parse::<f64>()
+                                .with_context(|| format!("Value '{}' must be numeric for this column", value))?;
+                            (target.cast(DataType::Float64), lit(value))
+                        } else {
+                            bail!("Cannot compare column '{}' of type {} with '{}'", column, dtype, value)
+                        };
+                        Ok(match op {
+                            ComparisonOp::Equal => target.eq(value),
+                            ComparisonOp::NotEqual => target.neq(value),
+                            ComparisonOp::GreaterThan => target.gt(value),
+                            ComparisonOp::LessThan => target.lt(value),
+                            ComparisonOp::GreaterOrEqual => target.gt_eq(value),
+                            _ => target.lt_eq(value),
+                        })
+                    }
                 }
             }
-            ComparisonOp::LessThan => {
-                // Try numeric comparison first
-                if let Ok(num_value) = value.parse::<f64>() {
-                    Self::numeric_comparison(col, num_value, |a, b| a < b)
-                } else if let Ok(str_col) = col.str() {
-                    // Fall back to string comparison
-                    Self::string_comparison(str_col, value, |a, b| a < b)
-                } else {
-                    bail!("Column must be numeric or string type for < comparison")
-                }
+            FilterExpr::And(left, right) => {
+                Ok(left.to_expr(schema, strings_only)?.and(right.to_expr(schema, strings_only)?))
             }
-            ComparisonOp::GreaterOrEqual => {
-                // Try numeric comparison first
-                if let Ok(num_value) = value.parse::<f64>() {
-                    Self::numeric_comparison(col, num_value, |a, b| a >= b)
-                } else if let Ok(str_col) = col.str() {
-                    // Fall back to string comparison
-                    Self::string_comparison(str_col, value, |a, b| a >= b)
-                } else {
-                    bail!("Column must be numeric or string type for >= comparison")
-                }
+            FilterExpr::Or(left, right) => {
+                Ok(left.to_expr(schema, strings_only)?.or(right.to_expr(schema, strings_only)?))
             }
-            ComparisonOp::LessOrEqual => {
-                // Try numeric comparison first
-                if let Ok(num_value) = value.parse::<f64>() {
-                    Self::numeric_comparison(col, num_value, |a, b| a <= b)
-                } else if let Ok(str_col) = col.str() {
-                    // Fall back to string comparison
-                    Self::string_comparison(str_col, value, |a, b| a <= b)
-                } else {
-                    bail!("Column must be numeric or string type for <= comparison")
-                }
+            FilterExpr::Not(inner) => Ok(inner.to_expr(schema, strings_only)?.not()),
+            FilterExpr::Sql(expr) => Ok(expr.clone()),
+        }
+    }
+
+    /// Whether the expression compares the `#row` pseudo column, which lazy sources
+    /// then have to number the rows for
+    pub fn uses_row_numbers(&self) -> bool {
+        match self {
+            FilterExpr::Comparison { column, .. } => column == ROW_COLUMN,
+            FilterExpr::And(left, right) | FilterExpr::Or(left, right) => {
+                left.uses_row_numbers() || right.uses_row_numbers()
             }
+            FilterExpr::Not(inner) => inner.uses_row_numbers(),
+            FilterExpr::Sql(_) => false,
         }
     }
 
-    fn numeric_comparison<F>(col: &Column, value: f64, op: F) -> Result<BooleanChunked>
-    where
-        F: Fn(f64, f64) -> bool,
-    {
-        // Try different numeric types
-        if let Ok(i64_col) = col.i64() {
-            Ok(i64_col
-                .into_iter()
-                .map(|opt_val| opt_val.map(|v| op(v as f64, value)))
-                .collect())
-        } else if let Ok(i32_col) = col.i32() {
-            Ok(i32_col
-                .into_iter()
-                .map(|opt_val| opt_val.map(|v| op(v as f64, value)))
-                .collect())
-        } else if let Ok(f64_col) = col.f64() {
-            Ok(f64_col
-                .into_iter()
-                .map(|opt_val| opt_val.map(|v| op(v, value)))
-                .collect())
-        } else if let Ok(f32_col) = col.f32() {
-            Ok(f32_col
-                .into_iter()
-                .map(|opt_val| opt_val.map(|v| op(v as f64, value)))
-                .collect())
+    /// Narrow a lazy frame with `schema` to the rows matching the expression, numbering
+    /// the rows for `#row` first when it is used (and not a real column)
+    pub fn filter_lazy(&self, lf: LazyFrame, schema: &Schema, strings_only: bool) -> Result<LazyFrame> {
+        let expr = self.to_expr(schema, strings_only)?;
+        Ok(if self.uses_row_numbers() && !schema.contains(ROW_COLUMN) {
+            lf.with_row_index(ROW_COLUMN, Some(1)).filter(expr).drop([ROW_COLUMN])
         } else {
-            bail!("Column is not numeric type")
+            lf.filter(expr)
+        })
+    }
+
+    /// Apply filter expression to DataFrame. `strings_only` limits global search to
+    /// string columns; the other options are applied by `with_options`.
+    pub fn apply(&self, df: &DataFrame, strings_only: bool) -> Result<DataFrame> {
+        let lf = self.filter_lazy(df.clone().lazy(), &df.schema(), strings_only)?;
+        collect(lf)
+    }
+}
+
+/// Run a lazy query, reporting just the first line of Polars errors rather than the
+/// query plan they end with
+pub(crate) fn collect(lf: LazyFrame) -> Result<DataFrame> {
+    lf.collect().map_err(|e| {
+        let message = e.to_string();
+        anyhow!("{}", message.lines().next().unwrap_or_default())
+    })
+}
+
+/// Lower-cased letters and digits of `text`, which is what fuzzy matching compares
+pub(crate) fn fuzzy_chars(text: &str) -> Vec<char> {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Whether `value` fuzzily contains `term` (as given by `fuzzy_chars`): the term's
+/// characters appear in order, with anything in between and one in four of them allowed
+/// to be missing, so `ic2602` finds "IC-2602" and "IC 260 2" as well as the typo "IC2620"
+pub(crate) fn fuzzy_match(value: &str, term: &[char]) -> bool {
+    if term.is_empty() {
+        return true;
+    }
+    // Longest common subsequence of term and value, one value character at a time
+    let mut lengths = vec![0usize; term.len() + 1];
+    for v in fuzzy_chars(value) {
+        let mut diagonal = 0;
+        for (j, t) in term.iter().enumerate() {
+            let above = lengths[j + 1];
+            lengths[j + 1] = if v == *t { diagonal + 1 } else { above.max(lengths[j]) };
+            diagonal = above;
         }
     }
+    lengths[term.len()] + term.len() / 4 >= term.len()
+}
 
-    fn string_comparison<F>(str_col: &StringChunked, value: &str, op: F) -> Result<BooleanChunked>
-    where
-        F: Fn(&str, &str) -> bool,
-    {
-        Ok(str_col
-            .into_iter()
-            .map(|opt_val| opt_val.map(|v| op(v, value)))
-            .collect())
+/// Column whose string length `len(name)` refers to
+fn length_of(column: &str) -> Option<&str> {
+    let (function, rest) = column.split_once('(')?;
+    let name = rest.strip_suffix(')')?.trim();
+    (function.trim().eq_ignore_ascii_case("len") && !name.is_empty()).then_some(name)
+}
+
+/// Error for a column that does not exist, suggesting the closest existing name
+/// (ignoring case, within one edit per three characters) to catch typos like `Pirce`
+fn unknown_column<'a>(column: &str, columns: impl Iterator<Item = &'a str>) -> anyhow::Error {
+    let target = column.to_lowercase();
+    let max_distance = (target.chars().count() / 3).max(1);
+    let closest = columns
+        .map(|name| (edit_distance(&target, &name.to_lowercase()), name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance);
+    match closest {
+        Some((_, name)) => anyhow!("Column '{}' not found, did you mean '{}'?", column, name),
+        None => anyhow!("Column '{}' not found", column),
+    }
+}
+
+/// Edit distance counting insertions, deletions, substitutions and swaps of adjacent
+/// characters (so `pirce` is one edit from `price`)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Boolean literal: true/false, t/f, yes/no or 1/0, in any case
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "t" | "yes" | "1" => Some(true),
+        "false" | "f" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether a column holds text: strings, or categoricals of them
+fn is_text(dtype: &DataType) -> bool {
+    matches!(dtype, DataType::String | DataType::Categorical(..))
+}
+
+fn is_date_or_time(dtype: &DataType) -> bool {
+    matches!(dtype, DataType::Date | DataType::Datetime(..) | DataType::Time)
+}
+
+/// Parse a date or date-time, filling in missing parts with the start of the period
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(datetime);
+        }
+    }
+
+    let date = match value.len() {
+        4 => NaiveDate::parse_from_str(&format!("{}-01-01", value), "%Y-%m-%d"),
+        7 => NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d"),
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d"),
+    };
+    date.ok().map(|d| d.and_time(NaiveTime::MIN))
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    let value = value.trim();
+    NaiveTime::parse_from_str(value, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parsed tree written out with every AND, OR and NOT grouped
+    fn shape(input: &str) -> String {
+        fn write(expr: &FilterExpr) -> String {
+            match expr {
+                FilterExpr::Comparison { column, op, value, .. } => format!("{} {:?} {}", column, op, value),
+                FilterExpr::And(left, right) => format!("({} AND {})", write(left), write(right)),
+                FilterExpr::Or(left, right) => format!("({} OR {})", write(left), write(right)),
+                FilterExpr::Not(inner) => format!("NOT {}", write(inner)),
+                FilterExpr::Sql(expr) => format!("sql:{:?}", expr),
+            }
+        }
+        write(&FilterExpr::parse(input).unwrap())
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(shape("a = 1 OR b = 2 AND c = 3"), "(a Equal 1 OR (b Equal 2 AND c Equal 3))");
+        assert_eq!(shape("a = 1 AND b = 2 OR c = 3"), "((a Equal 1 AND b Equal 2) OR c Equal 3)");
+    }
+
+    #[test]
+    fn same_operators_group_from_the_left() {
+        assert_eq!(shape("a = 1 OR b = 2 OR c = 3"), "((a Equal 1 OR b Equal 2) OR c Equal 3)");
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(shape("(a = 1 OR b = 2) AND c = 3"), "((a Equal 1 OR b Equal 2) AND c Equal 3)");
+        assert_eq!(
+            shape("((a = 1 OR (b = 2 AND c = 3)) AND d = 4)"),
+            "((a Equal 1 OR (b Equal 2 AND c Equal 3)) AND d Equal 4)"
+        );
+    }
+
+    #[test]
+    fn not_binds_tightest() {
+        assert_eq!(shape("NOT a = 1 AND b = 2"), "(NOT a Equal 1 AND b Equal 2)");
+        assert_eq!(shape("NOT (a = 1 OR b = 2)"), "NOT (a Equal 1 OR b Equal 2)");
+        assert_eq!(shape("not(a = 1) or b = 2"), "(NOT a Equal 1 OR b Equal 2)");
+    }
+
+    #[test]
+    fn quoted_values_keep_keywords_and_parentheses() {
+        assert_eq!(shape("name = \"Smith AND Co\" OR a = 1"), "(name Equal Smith AND Co OR a Equal 1)");
+        assert_eq!(shape("(name = 'x) OR (y' AND a = 1)"), "(name Equal x) OR (y AND a Equal 1)");
+    }
+
+    #[test]
+    fn only_one_pair_of_quotes_is_removed() {
+        assert_eq!(shape("name = '\"Smith\"'"), "name Equal \"Smith\"");
+        assert_eq!(shape("px BETWEEN \"'1'\" AND 2"), "(px GreaterOrEqual '1' AND px LessOrEqual 2)");
+        assert_eq!(shape("name = O'Brien'"), "name Equal O'Brien'");
+    }
+
+    #[test]
+    fn quoted_values_read_back_unchanged() {
+        for value in ["plain", "two words", "O'Brien", "say \"hi\"", "it's \"both\"", "C:\\ dir\\", "a\\\"b", "(x) OR y"] {
+            let filter = format!("name = {} AND a = 1", quote_value(value));
+            let Ok(FilterExpr::And(left, _)) = FilterExpr::parse(&filter) else {
+                panic!("'{}' did not parse", filter);
+            };
+            match *left {
+                FilterExpr::Comparison { value: parsed, .. } => assert_eq!(parsed, value, "{}", filter),
+                _ => panic!("'{}' did not parse to a comparison", filter),
+            }
+        }
+    }
+
+    #[test]
+    fn regex_parentheses_stay_in_the_value() {
+        assert_eq!(shape("sym ~ ^(IC|IF) AND a = 1"), "(sym Regex ^(IC|IF) AND a Equal 1)");
+    }
+
+    #[test]
+    fn between_keeps_its_and_inside_an_or() {
+        assert_eq!(
+            shape("a = 1 OR px BETWEEN 10 AND 20"),
+            "(a Equal 1 OR (px GreaterOrEqual 10 AND px LessOrEqual 20))"
+        );
+        assert_eq!(
+            shape("px BETWEEN 10 AND 20 OR b = 2 AND c = 3"),
+            "((px GreaterOrEqual 10 AND px LessOrEqual 20) OR (b Equal 2 AND c Equal 3))"
+        );
+    }
+
+    #[test]
+    fn unbalanced_parentheses_are_errors() {
+        assert!(FilterExpr::parse("(a = 1 OR b = 2").is_err());
+        assert!(FilterExpr::parse("a = 1 OR b = 2)").is_err());
+        assert!(FilterExpr::parse("((a = 1)").is_err());
+        assert!(FilterExpr::parse("()").is_err());
+    }
+
+    #[test]
+    fn dangling_operators_are_errors() {
+        assert!(FilterExpr::parse("a = 1 AND").is_err());
+        assert!(FilterExpr::parse("OR a = 1").is_err());
+        assert!(FilterExpr::parse("NOT").is_err());
     }
 }
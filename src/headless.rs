@@ -0,0 +1,47 @@
+use crate::data::source::filter_frame;
+use crate::data::DataSource;
+use crate::filter::FilterOptions;
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// A run without the TUI (`--output`): filter, keep some columns and write the rows out
+#[derive(Debug, Clone)]
+pub struct Headless {
+    /// Filter expression, in the syntax of the `/` prompt
+    pub filter: Option<String>,
+    /// Columns written, in this order; all of them when `None`
+    pub columns: Option<Vec<String>>,
+    /// File written, in the format its extension names (see `export::write`)
+    pub output: PathBuf,
+}
+
+impl Headless {
+    /// Filter `source` the way the `/` prompt does, keep the chosen columns and write
+    /// the rows to the output file, returning how many were written
+    pub fn run(&self, mut source: DataSource) -> Result<usize> {
+        let pattern = self.filter.as_deref().unwrap_or_default();
+        let options = FilterOptions::default();
+        let rows = if source.is_paged() {
+            // Paged sources run the filter server-side, then hand over all that match
+            source
+                .filter_paged(pattern, options)
+                .map_err(|e| anyhow::anyhow!("Filter error: {}", e))?;
+            source.page(0, source.len())?
+        } else {
+            filter_frame(source.dataframe(), pattern, options).map_err(|e| anyhow::anyhow!("Filter error: {}", e))?
+        };
+
+        let mut df = match &self.columns {
+            Some(columns) => {
+                if let Some(missing) = columns.iter().find(|column| rows.column(column).is_err()) {
+                    let names: Vec<&str> = rows.get_column_names_str();
+                    anyhow::bail!("Unknown column '{}', expected one of {}", missing, names.join(", "));
+                }
+                rows.select(columns)?
+            }
+            None => rows,
+        };
+        crate::export::write(&mut df, &self.output)?;
+        Ok(df.height())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::app::App;
+use crate::record_view::{popup_area, render_popup};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    Frame,
+};
+
+/// Keys and syntax listed by the help overlay, as (section, entries of (keys, description))
+const HELP: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Navigation",
+        &[
+            ("↑↓ / j k", "Scroll one row"),
+            ("←→ / h l", "Scroll one column"),
+            ("PgUp PgDn", "Scroll one page"),
+            ("Home End / g G", "Jump to the top / bottom"),
+            ("Tab Shift+Tab", "Switch between open files"),
+            ("Ctrl-w s", "Split the table into two panes, or back to one"),
+            ("Ctrl-w w", "Switch the focused pane (Ctrl-w q closes the other)"),
+            ("Mouse wheel", "Scroll rows, or columns with Shift"),
+            ("Click", "Sort by a header, or focus a cell"),
+        ],
+    ),
+    (
+        "Filtering",
+        &[
+            ("/", "Type a filter; Enter applies it on top of the applied ones"),
+            ("u", "Undo the last applied filter"),
+            ("Esc", "Drop picked rows, :dupes, all filters, then the SQL query; stop a running filter"),
+            ("I", "Toggle case-insensitive matching"),
+            ("Tab ↑↓", "Complete column names while typing a filter"),
+            ("Ctrl+F", "Toggle fuzzy global search while typing a filter"),
+            ("Ctrl+T", "Toggle global search of non-string columns"),
+            (":", "Run a SQL query on table df, or just a WHERE condition"),
+            (":dupes a,b", "Show only rows whose values in a and b repeat (all columns if none)"),
+        ],
+    ),
+    (
+        "Cell mode (Enter)",
+        &[
+            ("↑↓←→ / hjkl", "Move the focused cell"),
+            ("PgUp PgDn g G", "Move a page, or to the first / last row"),
+            ("0 $", "Focus the first / last column"),
+            ("Enter", "Show the focused row with one line per column"),
+            ("v", "Show the focused cell's complete value"),
+            ("S", "Statistics of the focused column over the filtered rows"),
+            ("F", "Distinct values of the focused column by frequency; Enter keeps one"),
+            ("R", "Rows and aggregates per time bucket of the focused datetime column"),
+            ("y Y", "Copy the focused cell / row (as TSV) to the clipboard"),
+            ("y c", "Copy the focused column's filtered values, one per line"),
+            ("Space", "Pick / drop the focused row for export, copy and :pipe"),
+            ("Shift+Space V", "Pick / drop every row since the last one toggled"),
+            ("= !", "Keep / exclude rows with the focused value"),
+            ("s", "Sort by the focused column: ascending, descending, off"),
+            ("p", "Pin the focused column to the left"),
+            ("H", "Hide the focused column (c shows it again)"),
+            ("Esc", "Leave cell mode"),
+        ],
+    ),
+    (
+        "Sorting",
+        &[
+            ("r", "Back to file order, keeping the filters"),
+            ("o", "Toggle natural order (file2 before file10)"),
+        ],
+    ),
+    (
+        "Column selection (c)",
+        &[
+            ("J K", "Move the column down / up in the display order"),
+            ("Space", "Show / hide the column"),
+            ("Shift+Space V", "Show / hide every column since the last one toggled"),
+            ("a", "Show all columns"),
+            ("d i", "Hide / invert the listed columns (all, or the search matches)"),
+            ("s", "Add the column to a multi-column sort"),
+            ("n", "Sort missing values first / last"),
+            ("p", "Pin the column to the left"),
+            ("/", "Fuzzy search the column names; Enter keeps the matches listed"),
+            ("Enter Esc", "Apply / cancel"),
+        ],
+    ),
+    (
+        "Export",
+        &[
+            ("e", "Write the rows and columns shown, or the picked rows, to a file"),
+            (":export f", "Format by extension: .csv, .parquet, .ndjson / .jsonl or .md"),
+            (":copy", "Copy the page as a Markdown table (:copy all for every row)"),
+            (":pipe cmd", "Send the rows shown as CSV to a shell command and show its output"),
+        ],
+    ),
+    (
+        "Display",
+        &[
+            ("n", "Toggle line numbers"),
+            ("z", "Toggle row striping and the focused row highlight"),
+            ("w", "Toggle wrapping long cells instead of cutting them off"),
+            ("m", "Toggle coloring numbers from their column's minimum to its maximum"),
+            ("T", "Cycle color themes"),
+            ("i", "Schema: every column's type, missing and distinct values, an example"),
+            ("d", "Describe: count, mean, std, min, quartiles and max of numeric columns"),
+            ("t", "Choose a sheet or table"),
+            ("E", "Reload with another text encoding"),
+            ("?", "Show this help"),
+            ("q", "Quit"),
+        ],
+    ),
+    (
+        "Filter syntax",
+        &[
+            ("text", "Any column contains the text"),
+            ("col:text  col !: text", "Column contains / does not contain"),
+            ("col = v  col != v", "Equals / differs"),
+            ("col > v  >=  <  <=", "Compares numbers, dates and times"),
+            ("col ^= v  col $= v", "Starts / ends with"),
+            ("col ~ re  col !~ re", "Matches / does not match a regex"),
+            ("col IS NULL", "Missing values (IS NOT NULL for present ones)"),
+            ("a AND b  a OR b  NOT a", "Combine conditions, with ( ) to group"),
+            ("len(col) > 10", "Compare the length of strings"),
+            ("#row <= 100", "Compare the row number"),
+            ("sql: expr", "Filter with a Polars SQL expression"),
+        ],
+    ),
+];
+
+/// Render the list of keys and filter syntax, in a scrollable popup over the table
+pub fn render_help(f: &mut Frame, app: &App, area: Rect) {
+    let keys_width = HELP
+        .iter()
+        .flat_map(|(_, entries)| entries.iter())
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (section, entries) in HELP {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::styled(
+            section.to_string(),
+            Style::default().fg(app.theme.frame).add_modifier(Modifier::BOLD),
+        ));
+        for (keys, description) in entries.iter() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<width$}  ", keys, width = keys_width), app.theme.key()),
+                Span::raw(description.to_string()),
+            ]));
+        }
+    }
+
+    let title = " Help | ↑↓ PgUp/PgDn scroll | Esc close ".to_string();
+    render_popup(f, app, popup_area(area), title, lines);
+}
@@ -0,0 +1,119 @@
+use crate::data::source::filter_frame;
+use crate::filter::FilterOptions;
+use anyhow::Result;
+use polars::prelude::*;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long typing has to pause before the filter is applied
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Runs a filter on a background thread, so the UI stays responsive while large
+/// frames are filtered; starting another one or cancelling drops its result
+pub struct FilterJob {
+    /// Bumped for every job, so results of superseded jobs are dropped
+    generation: u64,
+    /// When the latest job started, while it is still running
+    started: Option<Instant>,
+    sender: Sender<(u64, Result<DataFrame>)>,
+    results: Receiver<(u64, Result<DataFrame>)>,
+}
+
+impl FilterJob {
+    pub fn new() -> Self {
+        let (sender, results) = channel();
+        Self {
+            generation: 0,
+            started: None,
+            sender,
+            results,
+        }
+    }
+
+    /// Start filtering `df`, superseding the job still running
+    pub fn start(&mut self, df: &DataFrame, pattern: &str, options: FilterOptions) {
+        self.started = Some(Instant::now());
+        self.generation += 1;
+
+        let generation = self.generation;
+        let sender = self.sender.clone();
+        let df = df.clone();
+        let pattern = pattern.to_string();
+        thread::spawn(move || {
+            let result = filter_frame(&df, &pattern, options);
+            // The receiver is gone if the app quit meanwhile
+            let _ = sender.send((generation, result));
+        });
+    }
+
+    /// Forget the running job; the thread finishes on its own
+    pub fn cancel(&mut self) {
+        self.started = None;
+        self.generation += 1;
+    }
+
+    /// How long the running job has taken so far, `None` if none is running
+    pub fn running_for(&self) -> Option<Duration> {
+        self.started.map(|started| started.elapsed())
+    }
+
+    /// Result of the latest job, once it has finished
+    pub fn take_result(&mut self) -> Option<Result<DataFrame>> {
+        let mut latest = None;
+        while let Ok((generation, result)) = self.results.try_recv() {
+            if generation == self.generation {
+                self.started = None;
+                latest = Some(result);
+            }
+        }
+        latest
+    }
+}
+
+/// Applies the filter being typed on a background thread, so the table updates
+/// live without keystrokes waiting for large frames to be filtered
+pub struct LiveFilter {
+    /// When the edited pattern is due to be applied
+    due: Option<Instant>,
+    job: FilterJob,
+}
+
+impl LiveFilter {
+    pub fn new() -> Self {
+        Self {
+            due: None,
+            job: FilterJob::new(),
+        }
+    }
+
+    /// Note that the pattern changed; it is applied once typing pauses
+    pub fn schedule(&mut self) {
+        self.due = Some(Instant::now() + DEBOUNCE);
+    }
+
+    /// Forget scheduled and running jobs, e.g. because the filter was applied directly
+    pub fn cancel(&mut self) {
+        self.due = None;
+        self.job.cancel();
+    }
+
+    /// Whether a job is scheduled or running
+    pub fn is_pending(&self) -> bool {
+        self.due.is_some() || self.job.running_for().is_some()
+    }
+
+    /// Start filtering `df` in the background if the debounce time has passed
+    pub fn start_if_due(&mut self, df: &DataFrame, pattern: &str, options: FilterOptions) {
+        if self.due.is_none_or(|due| Instant::now() < due) {
+            return;
+        }
+        self.due = None;
+        self.job.start(df, pattern, options);
+    }
+
+    /// Result of the latest job, once it has finished
+    pub fn take_result(&mut self) -> Option<Result<DataFrame>> {
+        self.job.take_result()
+    }
+}
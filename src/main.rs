@@ -1,36 +1,93 @@
 mod app;
+mod cli;
 mod data;
 mod ui;
 mod filter;
 mod column_selection;
+mod table_selection;
+mod encoding_selection;
+mod workspace;
+mod watch;
+mod live_filter;
+mod column_stats;
+mod prompt;
+mod sort;
+mod record_view;
+mod theme;
+mod help;
+mod text;
+mod export;
+mod clipboard;
+mod pipe;
+mod headless;
+mod value_counts;
+mod schema_view;
+mod describe;
+mod resample;
 
 use anyhow::{Context, Result};
 use app::App;
+use cli::Cli;
+use data::source::DataSourceType;
+use data::{remote, BackgroundLoad, DataSource, LoadOptions, LoadProgress};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    Terminal,
+};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use workspace::Workspace;
+
+/// Rows scrolled by one step of the mouse wheel
+const MOUSE_SCROLL_ROWS: usize = 3;
 
 fn main() -> Result<()> {
     // Parse command line arguments
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        eprintln!("Usage: {} <file.parquet|file.csv|file.db>", args[0]);
-        eprintln!("\nSupported formats:");
-        eprintln!("  - Parquet (.parquet)");
-        eprintln!("  - CSV (.csv)");
-        eprintln!("  - SQLite (.db, .sqlite, .sqlite3)");
+        cli::print_usage(&args[0]);
         std::process::exit(1);
     }
+    let cli = match cli::parse(&args[1..]) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("Error: {}\n", e);
+            cli::print_usage(&args[0]);
+            std::process::exit(1);
+        }
+    };
 
-    let file_path = PathBuf::from(&args[1]);
-    if !file_path.exists() {
-        eprintln!("Error: File '{}' does not exist", file_path.display());
-        std::process::exit(1);
+    // Categoricals of different files (diff, concat, reloads) can only be combined
+    // when they share the global string cache
+    if cli.options.categorical {
+        polars::enable_string_cache();
+    }
+
+    for file_path in &cli.files {
+        let source = file_path.to_string_lossy();
+        let is_remote = remote::is_remote(&source)
+            || remote::is_cloud(&source)
+            || data::postgres::is_postgres_url(&source)
+            || data::mysql::is_mysql_url(&source);
+        if !file_path.exists() && !data::is_glob(file_path) && !is_remote {
+            eprintln!("Error: File '{}' does not exist", file_path.display());
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(headless) = &cli.headless {
+        let rows = run_headless(&cli, headless)?;
+        eprintln!("Wrote {} rows to {}", rows, headless.output.display());
+        return Ok(());
     }
 
     // Setup terminal
@@ -40,71 +97,645 @@ fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Show loading screen
+    // Create app and run
+    let result = run_app(&mut terminal, cli);
+
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    if let Err(e) = result {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Frames of the spinner on the loading screen, one per redraw
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How often the loading screen is redrawn, and Esc checked for
+const LOADING_REDRAW: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Draw the loading screen: a spinner with the time taken so far and the rows parsed,
+/// and a download gauge while a remote file is being fetched
+fn draw_loading_screen(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    file_path: &Path,
+    progress: &LoadProgress,
+    elapsed: std::time::Duration,
+) -> Result<()> {
     terminal.draw(|f| {
-        use ratatui::widgets::{Block, Borders, Paragraph};
-        use ratatui::layout::{Layout, Constraint, Direction};
+        use ratatui::widgets::{Block, Borders, Gauge, Paragraph};
         use ratatui::style::{Color, Style, Modifier};
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints([Constraint::Percentage(50), Constraint::Length(3), Constraint::Min(0)])
             .split(f.area());
 
-        let loading_text = format!("Loading file: {}\n\nPlease wait...", file_path.display());
+        let spinner = SPINNER[(elapsed.as_millis() / LOADING_REDRAW.as_millis()) as usize % SPINNER.len()];
+        let mut status = format!("{} {:.1} s", spinner, elapsed.as_secs_f64());
+        if progress.rows() > 0 {
+            status.push_str(&format!(" | {} rows read", progress.rows()));
+        }
+        let summary = progress.summary();
+        if let Some(summary) = &summary {
+            status.push_str(&format!(
+                " | {} rows, {} columns",
+                ui::format_count(summary.rows),
+                summary.columns.len()
+            ));
+        }
+        let loading_text = format!(
+            "Loading file: {}\n\n{}\n\nPress Esc to cancel",
+            file_path.display(),
+            status
+        );
         let paragraph = Paragraph::new(loading_text)
             .block(Block::default().title(" Loading ").borders(Borders::ALL))
             .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
 
         f.render_widget(paragraph, chunks[0]);
+
+        if let Some((downloaded, total)) = progress.download() {
+            let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+            let block = Block::default().title(" Downloading ").borders(Borders::ALL);
+            match total {
+                Some(total) if total > 0 => {
+                    let gauge = Gauge::default()
+                        .block(block)
+                        .gauge_style(Style::default().fg(Color::Green))
+                        .ratio((downloaded as f64 / total as f64).min(1.0))
+                        .label(format!("{:.1} / {:.1} MB", mb(downloaded), mb(total)));
+                    f.render_widget(gauge, chunks[1]);
+                }
+                _ => {
+                    let text = Paragraph::new(format!("{:.1} MB received", mb(downloaded))).block(block);
+                    f.render_widget(text, chunks[1]);
+                }
+            }
+        }
+
+        // The schema from the file's metadata, to check it is the right file meanwhile
+        if let Some(summary) = summary {
+            let lines: Vec<String> = summary
+                .columns
+                .iter()
+                .map(|(name, dtype)| format!("{}: {}", name, dtype))
+                .collect();
+            let schema = Paragraph::new(lines.join("\n"))
+                .block(Block::default().title(" Columns ").borders(Borders::ALL));
+            f.render_widget(schema, chunks[2]);
+        }
     })?;
+    Ok(())
+}
 
-    // Create app and run
-    let result = run_app(&mut terminal, file_path);
+/// What the loading screen handed over when it closed
+enum Loaded {
+    /// The whole source
+    Done(DataSource),
+    /// The first rows of a CSV file, with the rest still loading
+    Streaming(DataSource, BackgroundLoad),
+}
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+/// Animate the loading screen until `load` is done or, for streaming loads, has
+/// parsed its first rows. Returns `None` if Esc (or Ctrl+C) cancelled it; the worker
+/// is then left to finish on its own, and its result dropped.
+fn show_loading_screen(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    title: &Path,
+    load: BackgroundLoad,
+) -> Result<Option<Loaded>> {
+    loop {
+        draw_loading_screen(terminal, title, load.progress(), load.elapsed())?;
+        if let Some(source) = load.wait(LOADING_REDRAW) {
+            return source.map(|source| Some(Loaded::Done(source)));
+        }
+        let mut rows = load.progress().take_rows().into_iter();
+        if let Some(mut first) = rows.next() {
+            for batch in rows {
+                first.vstack_mut(&batch)?;
+            }
+            return Ok(Some(Loaded::Streaming(DataSource::preview(title, first), load)));
+        }
+        while event::poll(std::time::Duration::ZERO)? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.code == KeyCode::Esc || ctrl_c {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
 
-    if let Err(e) = result {
-        eprintln!("Error: {:?}", e);
-        std::process::exit(1);
+/// Wait behind the loading screen for a load that does not stream, see `show_loading_screen`
+fn finish_load(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    title: &Path,
+    load: BackgroundLoad,
+) -> Result<Option<DataSource>> {
+    Ok(match show_loading_screen(terminal, title, load)? {
+        Some(Loaded::Done(source)) => Some(source),
+        Some(Loaded::Streaming(..)) => unreachable!("only streaming loads hand over their first rows"),
+        None => None,
+    })
+}
+
+/// Start loading one file or URL on a worker thread. With `lazy`, local Parquet is
+/// read page by page; with `streaming`, CSV rows are handed out as they are parsed.
+fn start_load(file_path: &Path, load_options: &LoadOptions, lazy: bool, streaming: bool) -> BackgroundLoad {
+    let path = file_path.to_path_buf();
+    let load_options = load_options.clone();
+    BackgroundLoad::spawn(streaming, move |progress| load_file(&path, &load_options, lazy, progress))
+}
+
+/// Load one file or URL, see `start_load`
+fn load_file(path: &Path, load_options: &LoadOptions, lazy: bool, progress: &LoadProgress) -> Result<DataSource> {
+    let source = path.to_string_lossy().to_string();
+    let is_parquet = matches!(DataSourceType::from_path(path), Some(DataSourceType::Parquet));
+    if remote::is_remote(&source) {
+        DataSource::load_remote(&source, load_options, progress)
+    } else if lazy && is_parquet && !remote::is_cloud(&source) && load_options.rows.is_none() {
+        DataSource::scan_parquet(path)
+    } else {
+        DataSource::load_with_progress(path, load_options, progress)
     }
+    .with_context(|| format!("Failed to load data file '{}'", path.display()))
+}
 
-    Ok(())
+/// Load the files without the TUI and write out their filtered rows (`--output`),
+/// returning how many were written
+fn run_headless(cli: &Cli, headless: &headless::Headless) -> Result<usize> {
+    let progress = LoadProgress::default();
+    let source = if cli.concat {
+        let mut sources = Vec::with_capacity(cli.files.len());
+        for file_path in &cli.files {
+            sources.push((file_path.display().to_string(), load_file(file_path, &cli.options, cli.lazy, &progress)?));
+        }
+        DataSource::concat(&sources)?
+    } else {
+        load_file(&cli.files[0], &cli.options, cli.lazy, &progress)?
+    };
+    headless.run(source)
+}
+
+/// Load one file or URL in the background, showing the loading screen (and download
+/// progress for URLs). Returns `None` if the user cancelled.
+fn load_data_source(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    file_path: &Path,
+    load_options: &LoadOptions,
+    lazy: bool,
+) -> Result<Option<DataSource>> {
+    finish_load(terminal, file_path, start_load(file_path, load_options, lazy, false))
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, file_path: PathBuf) -> Result<()> {
-    let mut app = App::new(file_path).context("Failed to load data file")?;
+/// Split off the top line for the tab bar when it is shown
+fn split_tab_bar(area: Rect, show_tabs: bool) -> (Option<Rect>, Rect) {
+    if !show_tabs {
+        return (None, area);
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(area);
+    (Some(chunks[0]), chunks[1])
+}
+
+fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, cli: Cli) -> Result<()> {
+    let mut workspace = Workspace::new();
+    // Comparisons and concatenations are a snapshot, so they are never watched
+    let mut watcher = if cli.watch && !cli.diff && !cli.concat {
+        Some(watch::FileWatcher::new()?)
+    } else {
+        None
+    };
+
+    // Separately opened files are timed on their own, the other tabs take the total
+    let started = std::time::Instant::now();
+    if let Some((url, path)) = &cli.flight {
+        let title = PathBuf::from(format!("{}/{}", url.trim_end_matches('/'), path));
+        let (url, path) = (url.clone(), path.clone());
+        let load = BackgroundLoad::spawn(false, move |_| {
+            DataSource::load_flight(&url, &path).with_context(|| format!("Failed to fetch '{}' from {}", path, url))
+        });
+        let Some(data_source) = finish_load(terminal, &title, load)?
+        else {
+            return Ok(());
+        };
+        workspace.push(App::from_source(title, data_source));
+    } else if cli.diff {
+        // Both files are merged into a single comparison view
+        let Some(old) = load_data_source(terminal, &cli.files[0], &cli.options, cli.lazy)? else {
+            return Ok(());
+        };
+        let Some(new) = load_data_source(terminal, &cli.files[1], &cli.options, cli.lazy)? else {
+            return Ok(());
+        };
+        let diff = DataSource::diff(&old, &new, cli.key.as_deref())?;
+        let title = PathBuf::from(format!("{} ↔ {}", cli.files[0].display(), cli.files[1].display()));
+        workspace.push(App::from_source(title, diff));
+    } else if cli.concat {
+        let mut sources = Vec::with_capacity(cli.files.len());
+        for file_path in &cli.files {
+            let Some(data_source) = load_data_source(terminal, file_path, &cli.options, cli.lazy)? else {
+                return Ok(());
+            };
+            sources.push((file_path.display().to_string(), data_source));
+        }
+        let combined = DataSource::concat(&sources)?;
+        let title = PathBuf::from(format!("{} (+{} more)", cli.files[0].display(), cli.files.len() - 1));
+        workspace.push(App::from_source(title, combined));
+    } else {
+        for file_path in &cli.files {
+            // Large CSV files are viewed from their first rows while the rest loads
+            let file_started = std::time::Instant::now();
+            let load = start_load(file_path, &cli.options, cli.lazy, true);
+            let (data_source, rest) = match show_loading_screen(terminal, file_path, load)? {
+                Some(Loaded::Done(source)) => (source, None),
+                Some(Loaded::Streaming(preview, rest)) => (preview, Some(rest)),
+                None => return Ok(()),
+            };
+
+            // Only local files and dataset directories can be watched
+            if let Some(watcher) = watcher.as_mut() {
+                if file_path.exists() {
+                    watcher.watch(file_path, workspace.len())?;
+                }
+            }
+            let mut app = App::from_source(file_path.clone(), data_source);
+            app.load_time = Some(file_started.elapsed());
+            if let Some(rest) = rest {
+                app.keep_loading(rest);
+            }
+            workspace.push(app);
+        }
+    }
+
+    for tab in 0..workspace.len() {
+        let app = workspace.tab_mut(tab);
+        app.sort_options.natural = cli.natural_sort;
+        app.sort_options.nulls_last = cli.nulls_last;
+        app.cell_format = cli.cell_format.clone();
+        app.load_time.get_or_insert(started.elapsed());
+        app.open_view(cli.filter.as_deref(), cli.columns.as_deref(), cli.jump);
+    }
+    workspace.set_theme(cli.theme);
 
     loop {
+        // Only show the tab bar when more than one file is open
+        let show_tabs = workspace.len() > 1;
+        let size = terminal.size()?;
+        let (_, area) = split_tab_bar(Rect::new(0, 0, size.width, size.height), show_tabs);
+
+        for tab in 0..workspace.len() {
+            workspace.tab_mut(tab).update_loading();
+        }
+        let app = workspace.active_mut();
+        app.update_filter();
+        app.update_live_filter();
+        app.update_column_stats();
+        ui::measure_table(app, area);
+        app.update_jump();
+        app.clamp_cursor();
+
         terminal.draw(|f| {
-            if app.column_selection_mode {
-                column_selection::render_column_selection(f, &app, f.area());
+            let (tab_bar, area) = split_tab_bar(f.area(), show_tabs);
+            if let Some(tab_bar) = tab_bar {
+                ui::render_tabs(f, workspace.active(), &workspace.titles(), workspace.active_index(), tab_bar);
+            }
+
+            let app = workspace.active_mut();
+            if app.table_selection_mode {
+                table_selection::render_table_selection(f, app, area);
+            } else if app.encoding_selection_mode {
+                encoding_selection::render_encoding_selection(f, app, area);
+            } else if app.value_counts.is_some() {
+                value_counts::render_value_counts(f, app, area);
+            } else if app.resample.is_some() {
+                resample::render_resample(f, app, area);
+            } else if app.describe.is_some() {
+                describe::render_describe(f, app, area);
+            } else if app.schema_mode {
+                schema_view::render_schema_view(f, app, area);
+            } else if app.column_selection_mode {
+                column_selection::render_column_selection(f, app, area);
             } else {
-                ui::render(f, &app);
+                ui::render(f, app, area);
             }
         })?;
 
-        if app.should_quit {
+        if workspace.should_quit() {
             break;
         }
 
-        if let Event::Key(key) = event::read()? {
-            handle_key_event(&mut app, key.code, key.modifiers);
+        if let Some(watcher) = watcher.as_mut() {
+            for tab in watcher.changed_tabs() {
+                workspace.tab_mut(tab).reload();
+            }
+        }
+
+        // Wake up regularly to pick up file changes, filter results, column counts and
+        // rows of files still loading
+        let app = workspace.active();
+        let wake_up = if app.filter_running_for().is_some() || app.live_filter_pending() || app.column_stats_pending() {
+            Some(std::time::Duration::from_millis(50))
+        } else if watcher.is_some() || workspace.is_loading() {
+            Some(std::time::Duration::from_millis(200))
+        } else {
+            None
+        };
+        if let Some(timeout) = wake_up {
+            if !event::poll(timeout)? {
+                continue;
+            }
+        }
+
+        let event = event::read()?;
+        if let Event::Mouse(mouse) = event {
+            handle_mouse_event(workspace.active_mut(), mouse, area);
+        }
+        if let Event::Key(key) = event {
+            let app = workspace.active();
+            let in_normal_mode = !app.table_selection_mode
+                && !app.encoding_selection_mode
+                && app.value_counts.is_none()
+                && app.describe.is_none()
+                && app.resample.is_none()
+                && !app.schema_mode
+                && !app.column_selection_mode
+                && !app.filter_mode
+                && !app.command_mode
+                && !app.popup_open();
+            match key.code {
+                KeyCode::Tab if in_normal_mode => workspace.next_tab(),
+                KeyCode::BackTab if in_normal_mode => workspace.prev_tab(),
+                KeyCode::Char('T') if in_normal_mode => {
+                    let theme = workspace.active().theme.next();
+                    workspace.set_theme(theme);
+                }
+                _ => handle_key_event(workspace.active_mut(), key.code, key.modifiers),
+            }
+        }
+
+        if let Some(command) = workspace.active_mut().pipe_command.take() {
+            pipe_view(terminal, workspace.active_mut(), &command)?;
         }
     }
 
     Ok(())
 }
 
+/// Send the view to `command` as CSV for `:pipe`, suspending the viewer until it is done
+fn pipe_view(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App, command: &str) -> Result<()> {
+    let (input, rows) = match app.pipe_input() {
+        Ok(input) => input,
+        Err(e) => {
+            app.error_message = Some(format!("{:#}", e));
+            return Ok(());
+        }
+    };
+    match pipe::run(terminal, command, &input) {
+        Ok(status) if status.success() => app.message = Some(format!("Piped {} rows to `{}`", rows, command)),
+        Ok(status) => app.error_message = Some(format!("`{}` failed: {}", command, status)),
+        Err(e) => app.error_message = Some(format!("{:#}", e)),
+    }
+    Ok(())
+}
+
+/// Handle the mouse over the table: the wheel scrolls rows (with Shift, columns),
+/// clicking a header sorts by its column and clicking a row focuses its cell
+fn handle_mouse_event(app: &mut App, mouse: MouseEvent, area: Rect) {
+    if app.popup_open() {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => app.popup_scroll_down(MOUSE_SCROLL_ROWS),
+            MouseEventKind::ScrollUp => app.popup_scroll_up(MOUSE_SCROLL_ROWS),
+            _ => {}
+        }
+        return;
+    }
+    if app.table_selection_mode
+        || app.encoding_selection_mode
+        || app.value_counts.is_some()
+        || app.describe.is_some()
+        || app.resample.is_some()
+        || app.schema_mode
+        || app.column_selection_mode
+        || app.filter_mode
+        || app.command_mode
+    {
+        return;
+    }
+
+    let shift = mouse.modifiers.contains(KeyModifiers::SHIFT);
+    match mouse.kind {
+        MouseEventKind::ScrollDown if shift => app.scroll_right(),
+        MouseEventKind::ScrollUp if shift => app.scroll_left(),
+        MouseEventKind::ScrollRight => app.scroll_right(),
+        MouseEventKind::ScrollLeft => app.scroll_left(),
+        MouseEventKind::ScrollDown => (0..MOUSE_SCROLL_ROWS).for_each(|_| app.scroll_down_one()),
+        MouseEventKind::ScrollUp => (0..MOUSE_SCROLL_ROWS).for_each(|_| app.scroll_up_one()),
+        MouseEventKind::Down(MouseButton::Left) => match ui::hit_table(app, area, mouse.column, mouse.row) {
+            Some(ui::TableHit::Header(column)) => app.cycle_sort_column(column),
+            Some(ui::TableHit::Cell(row, column)) => app.click_cell(row, column),
+            Some(ui::TableHit::OtherPane) => app.switch_pane(),
+            None => {}
+        },
+        _ => {}
+    }
+}
+
+/// Handle a key in cell navigation mode, returning whether it was used
+fn handle_cell_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) -> bool {
+    match key {
+        KeyCode::Down | KeyCode::Char('j') => app.cursor_down(),
+        KeyCode::Up | KeyCode::Char('k') => app.cursor_up(),
+        KeyCode::Left | KeyCode::Char('h') => app.cursor_left(),
+        KeyCode::Right | KeyCode::Char('l') => app.cursor_right(),
+        KeyCode::PageDown => app.cursor_page_down(),
+        KeyCode::PageUp => app.cursor_page_up(),
+        KeyCode::Home | KeyCode::Char('g') => app.cursor_first_row(),
+        KeyCode::End | KeyCode::Char('G') => app.cursor_last_row(),
+        KeyCode::Char('0') => app.cursor_first_column(),
+        KeyCode::Char('$') => app.cursor_last_column(),
+        KeyCode::Char('=') => app.filter_by_cell(false),
+        KeyCode::Char('!') => app.filter_by_cell(true),
+        KeyCode::Char('s') => app.cycle_sort(),
+        KeyCode::Enter => app.open_record_view(),
+        KeyCode::Char('v') => app.open_value_popup(),
+        KeyCode::Char('S') => app.open_column_profile(),
+        KeyCode::Char('F') => app.open_value_counts(),
+        KeyCode::Char('R') => app.open_resample(),
+        KeyCode::Char('y') => {
+            app.copy_cell();
+            app.yank_prefix = true;
+        }
+        KeyCode::Char('Y') => app.copy_row(),
+        // Terminals without extended key reporting send Shift+Space as Space, so V does the same
+        KeyCode::Char(' ') if modifiers.contains(KeyModifiers::SHIFT) => app.toggle_row_range_at_cursor(),
+        KeyCode::Char('V') => app.toggle_row_range_at_cursor(),
+        KeyCode::Char(' ') => app.toggle_row_at_cursor(),
+        KeyCode::Char('p') => app.toggle_pin_at_cursor(),
+        KeyCode::Char('H') => app.hide_column_at_cursor(),
+        KeyCode::Esc => app.exit_cell_mode(),
+        _ => return false,
+    }
+    true
+}
+
 fn handle_key_event(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
-    if app.column_selection_mode {
+    app.message = None;
+    let after_yank = std::mem::take(&mut app.yank_prefix);
+    // Esc stops a filter still running, whatever else is open
+    if key == KeyCode::Esc && app.filter_running_for().is_some() {
+        app.cancel_filter();
+        return;
+    }
+    if app.table_selection_mode {
+        // Table (sheet) selection mode
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => {
+                app.table_selection_up();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.table_selection_down();
+            }
+            KeyCode::Enter => {
+                app.select_table_at_cursor();
+            }
+            KeyCode::Esc => {
+                app.exit_table_selection_mode();
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q') => {
+                app.quit();
+            }
+            _ => {}
+        }
+    } else if app.encoding_selection_mode {
+        // Encoding selection mode
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => {
+                app.encoding_selection_up();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.encoding_selection_down();
+            }
+            KeyCode::Enter => {
+                app.select_encoding_at_cursor();
+            }
+            KeyCode::Esc => {
+                app.exit_encoding_selection_mode();
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q') => {
+                app.quit();
+            }
+            _ => {}
+        }
+    } else if let Some(counts) = app.value_counts.as_mut() {
+        // Frequency screen
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => counts.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => counts.move_cursor(1),
+            KeyCode::PageUp => counts.move_cursor(-(app.page_size as isize)),
+            KeyCode::PageDown => counts.move_cursor(app.page_size as isize),
+            KeyCode::Home | KeyCode::Char('g') => counts.move_cursor(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => counts.move_cursor(isize::MAX),
+            KeyCode::Enter => app.filter_by_value_count(false),
+            KeyCode::Char('!') => app.filter_by_value_count(true),
+            KeyCode::Esc => app.value_counts = None,
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => {}
+        }
+    } else if let Some(resample) = app.resample.as_mut() {
+        // Resample screen
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => resample.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => resample.move_cursor(1),
+            KeyCode::PageUp => resample.move_cursor(-(app.page_size as isize)),
+            KeyCode::PageDown => resample.move_cursor(app.page_size as isize),
+            KeyCode::Home | KeyCode::Char('g') => resample.move_cursor(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => resample.move_cursor(isize::MAX),
+            KeyCode::Left | KeyCode::Char('h') => resample.scroll_values(-1),
+            KeyCode::Right | KeyCode::Char('l') => resample.scroll_values(1),
+            KeyCode::Char('t') | KeyCode::Char('a') => {
+                let regrouped = if key == KeyCode::Char('t') {
+                    resample.next_interval()
+                } else {
+                    resample.next_aggregate()
+                };
+                if let Err(e) = regrouped {
+                    app.error_message = Some(format!("Failed to resample: {:#}", e));
+                    app.resample = None;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('R') => app.resample = None,
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => {}
+        }
+    } else if let Some(describe) = app.describe.as_mut() {
+        // Describe screen
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => describe.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => describe.move_cursor(1),
+            KeyCode::PageUp => describe.move_cursor(-(app.page_size as isize)),
+            KeyCode::PageDown => describe.move_cursor(app.page_size as isize),
+            KeyCode::Home | KeyCode::Char('g') => describe.move_cursor(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => describe.move_cursor(isize::MAX),
+            KeyCode::Left | KeyCode::Char('h') => describe.scroll_statistics(-1),
+            KeyCode::Right | KeyCode::Char('l') => describe.scroll_statistics(1),
+            KeyCode::Esc | KeyCode::Char('d') => app.describe = None,
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => {}
+        }
+    } else if app.schema_mode {
+        // Schema overview
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => app.schema_move(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.schema_move(1),
+            KeyCode::PageUp => app.schema_move(-(app.page_size as isize)),
+            KeyCode::PageDown => app.schema_move(app.page_size as isize),
+            KeyCode::Home | KeyCode::Char('g') => app.schema_move(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => app.schema_move(isize::MAX),
+            KeyCode::Enter => app.go_to_schema_column(),
+            KeyCode::Esc | KeyCode::Char('i') => app.exit_schema_mode(),
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => {}
+        }
+    } else if app.column_selection_mode && app.column_search_mode {
+        // Typing the column search narrows the list as it goes
+        match key {
+            KeyCode::Char(c) => {
+                app.column_search.insert(c);
+                app.column_search_edited();
+            }
+            KeyCode::Backspace => {
+                app.column_search.backspace();
+                app.column_search_edited();
+            }
+            KeyCode::Delete => {
+                app.column_search.delete();
+                app.column_search_edited();
+            }
+            KeyCode::Left => app.column_search.left(),
+            KeyCode::Right => app.column_search.right(),
+            KeyCode::Home => app.column_search.home(),
+            KeyCode::End => app.column_search.end(),
+            KeyCode::Up => app.column_selection_up(),
+            KeyCode::Down => app.column_selection_down(),
+            KeyCode::Enter => app.finish_column_search(),
+            KeyCode::Esc => app.clear_column_search(),
+            _ => {}
+        }
+    } else if app.column_selection_mode {
         // Column selection mode
         match key {
             KeyCode::Up | KeyCode::Char('k') => {
@@ -113,23 +744,90 @@ fn handle_key_event(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
             KeyCode::Down | KeyCode::Char('j') => {
                 app.column_selection_down();
             }
+            KeyCode::Char('K') => {
+                app.move_column_at_cursor(false);
+            }
+            KeyCode::Char('J') => {
+                app.move_column_at_cursor(true);
+            }
+            // Terminals without extended key reporting send Shift+Space as Space, so V does the same
+            KeyCode::Char(' ') if modifiers.contains(KeyModifiers::SHIFT) => {
+                app.toggle_column_range_at_cursor();
+            }
+            KeyCode::Char('V') => {
+                app.toggle_column_range_at_cursor();
+            }
             KeyCode::Char(' ') => {
                 app.toggle_column_at_cursor();
             }
+            KeyCode::Char('d') => {
+                app.select_no_columns();
+            }
+            KeyCode::Char('i') => {
+                app.invert_column_selection();
+            }
             KeyCode::Char('a') | KeyCode::Char('A') => {
                 // Reset to show all columns
                 app.reset_column_selection();
             }
+            KeyCode::Char('s') => {
+                app.cycle_sort_at_column_cursor();
+            }
+            KeyCode::Char('n') => {
+                app.toggle_nulls_last();
+            }
+            KeyCode::Char('p') => {
+                app.toggle_pin_at_column_cursor();
+            }
+            KeyCode::Char('/') => {
+                app.start_column_search();
+            }
             KeyCode::Enter => {
                 app.exit_column_selection_mode();
             }
+            // Esc drops a search before leaving
+            KeyCode::Esc if !app.column_search.text.is_empty() => {
+                app.clear_column_search();
+            }
             KeyCode::Esc => {
                 app.exit_column_selection_mode();
             }
             _ => {}
         }
+    } else if app.command_mode {
+        match key {
+            KeyCode::Char(c) => app.command.insert(c),
+            KeyCode::Backspace => app.command.backspace(),
+            KeyCode::Delete => app.command.delete(),
+            KeyCode::Left => app.command.left(),
+            KeyCode::Right => app.command.right(),
+            KeyCode::Home => app.command.home(),
+            KeyCode::End => app.command.end(),
+            KeyCode::Enter => app.run_command(),
+            KeyCode::Esc => app.exit_command_mode(),
+            _ => {}
+        }
+    } else if app.popup_open() {
+        // Record view of the focused row, or full value of the focused cell
+        match key {
+            KeyCode::Down | KeyCode::Char('j') => app.popup_scroll_down(1),
+            KeyCode::Up | KeyCode::Char('k') => app.popup_scroll_up(1),
+            KeyCode::PageDown => app.popup_scroll_down(app.page_size),
+            KeyCode::PageUp => app.popup_scroll_up(app.page_size),
+            KeyCode::Home | KeyCode::Char('g') => app.popup_scroll_up(usize::MAX),
+            KeyCode::End | KeyCode::Char('G') => app.popup_scroll_down(usize::MAX),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('v') | KeyCode::Char('?') => app.close_popup(),
+            KeyCode::Char('q') | KeyCode::Char('Q') => app.quit(),
+            _ => {}
+        }
     } else if app.filter_mode {
         match key {
+            KeyCode::Char('f') if modifiers.contains(KeyModifiers::CONTROL) => {
+                app.toggle_fuzzy_search();
+            }
+            KeyCode::Char('t') if modifiers.contains(KeyModifiers::CONTROL) => {
+                app.toggle_strings_only();
+            }
             KeyCode::Char(c) => {
                 app.push_filter_char(c);
             }
@@ -151,9 +849,17 @@ fn handle_key_event(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
             KeyCode::End => {
                 app.filter_cursor_end();
             }
+            KeyCode::Tab => {
+                app.complete_filter_column();
+            }
+            KeyCode::Up => {
+                app.completion_up();
+            }
+            KeyCode::Down => {
+                app.completion_down();
+            }
             KeyCode::Enter => {
-                app.apply_filter();
-                app.exit_filter_mode();
+                app.push_filter();
             }
             KeyCode::Esc => {
                 app.exit_filter_mode();
@@ -161,18 +867,56 @@ fn handle_key_event(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
             _ => {}
         }
     } else {
+        // After Ctrl-w, the key picks what to do with the panes
+        if app.pane_prefix {
+            app.pane_prefix = false;
+            match key {
+                KeyCode::Char('s') => app.toggle_split(),
+                KeyCode::Char('w') | KeyCode::Char('j') | KeyCode::Char('k') | KeyCode::Up | KeyCode::Down => {
+                    app.switch_pane()
+                }
+                KeyCode::Char('q') | KeyCode::Char('o') => app.close_split(),
+                _ => {}
+            }
+            return;
+        }
+        if key == KeyCode::Char('w') && modifiers.contains(KeyModifiers::CONTROL) {
+            app.pane_prefix = true;
+            return;
+        }
+
+        // `yc` copies the column of the cell `y` just copied
+        if after_yank && key == KeyCode::Char('c') {
+            app.copy_column();
+            return;
+        }
+
+        // Cell navigation keys; everything else works as in normal mode
+        if app.cell_mode && handle_cell_key(app, key, modifiers) {
+            return;
+        }
+
         match key {
             KeyCode::Char('q') | KeyCode::Char('Q') => {
                 app.quit();
             }
+            KeyCode::Enter => {
+                app.enter_cell_mode();
+            }
             KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
                 app.quit();
             }
             KeyCode::Char('/') => {
                 app.enter_filter_mode();
             }
+            KeyCode::Char(':') => {
+                app.enter_command_mode();
+            }
             KeyCode::Esc => {
-                app.clear_filter();
+                app.clear_view();
+            }
+            KeyCode::Char('u') => {
+                app.pop_filter();
             }
             KeyCode::Down | KeyCode::Char('j') => {
                 app.scroll_down_one();
@@ -201,9 +945,45 @@ fn handle_key_event(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
             KeyCode::Char('n') | KeyCode::Char('N') => {
                 app.toggle_line_numbers();
             }
+            KeyCode::Char('z') => {
+                app.toggle_row_shading();
+            }
+            KeyCode::Char('w') => {
+                app.toggle_wrap_cells();
+            }
+            KeyCode::Char('m') => {
+                app.toggle_heatmap();
+            }
             KeyCode::Char('c') | KeyCode::Char('C') => {
                 app.enter_column_selection_mode();
             }
+            KeyCode::Char('t') => {
+                app.enter_table_selection_mode();
+            }
+            KeyCode::Char('e') => {
+                app.start_export();
+            }
+            KeyCode::Char('E') => {
+                app.enter_encoding_selection_mode();
+            }
+            KeyCode::Char('i') => {
+                app.enter_schema_mode();
+            }
+            KeyCode::Char('d') => {
+                app.open_describe();
+            }
+            KeyCode::Char('I') => {
+                app.toggle_ignore_case();
+            }
+            KeyCode::Char('o') => {
+                app.toggle_natural_sort();
+            }
+            KeyCode::Char('r') => {
+                app.restore_file_order();
+            }
+            KeyCode::Char('?') => {
+                app.open_help();
+            }
             _ => {}
         }
     }
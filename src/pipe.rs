@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::io::{self, Write};
+use std::process::{Command, ExitStatus, Stdio};
+
+/// Run `command` through the shell with `input` on its stdin. The terminal is handed
+/// over to it, and taken back once it has exited and Enter is pressed, so its output
+/// can be read.
+pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, command: &str, input: &[u8]) -> Result<ExitStatus> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+    terminal.show_cursor()?;
+
+    let status = run_in_shell(command, input);
+    if let Ok(status) = &status {
+        print!("\n[{}, press Enter to return]", status);
+        io::stdout().flush()?;
+        io::stdin().read_line(&mut String::new())?;
+    }
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    status
+}
+
+fn run_in_shell(command: &str, input: &[u8]) -> Result<ExitStatus> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run `{}`", command))?;
+
+    // Commands that stop reading early close the pipe, which is not an error
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    match stdin.write_all(input) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e).context("Failed to write to the command"),
+        _ => drop(stdin),
+    }
+    child.wait().with_context(|| format!("Failed to run `{}`", command))
+}
@@ -0,0 +1,61 @@
+/// Single-line text input with a cursor, used by the `:` command prompt
+#[derive(Debug, Default, Clone)]
+pub struct Prompt {
+    pub text: String,
+    /// Cursor position as a byte index into `text`, always on a char boundary
+    pub cursor: usize,
+}
+
+impl Prompt {
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Delete the character before the cursor
+    pub fn backspace(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    /// Delete the character at the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// Text with a `│` marking the cursor
+    pub fn display(&self) -> String {
+        let mut text = self.text.clone();
+        text.insert(self.cursor, '│');
+        text
+    }
+}
@@ -0,0 +1,116 @@
+use crate::app::App;
+use crate::text::{self, wrap};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Render the focused row transposed, in a popup over the table
+pub fn render_record_view(f: &mut Frame, app: &App, area: Rect) {
+    let Some(record) = &app.record else {
+        return;
+    };
+    let popup = popup_area(area);
+    let inner_width = popup.width.saturating_sub(2) as usize;
+
+    // Column names are padded so the values line up, and wrapped values stay indented
+    let name_width = record.iter().map(|(name, _)| text::width(name)).max().unwrap_or(0);
+    let value_width = inner_width.saturating_sub(name_width + 2);
+    let mut lines = Vec::new();
+    for (name, value) in record {
+        for (i, part) in wrap(value, value_width).into_iter().enumerate() {
+            let label = if i == 0 { name.as_str() } else { "" };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{}  ", text::pad(label, name_width)),
+                    Style::default().fg(app.theme.frame).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(part),
+            ]));
+        }
+    }
+
+    let title = format!(
+        " Row {} ({} columns) | ↑↓ PgUp/PgDn scroll | Esc close ",
+        app.cursor_row + 1,
+        record.len()
+    );
+    render_popup(f, app, popup, title, lines);
+}
+
+/// Render the complete value of the focused cell, word-wrapped, in a popup over the table
+pub fn render_value_popup(f: &mut Frame, app: &App, area: Rect) {
+    let Some((column, value)) = &app.value_popup else {
+        return;
+    };
+    let popup = popup_area(area);
+    let lines = wrap(value, popup.width.saturating_sub(2) as usize)
+        .into_iter()
+        .map(Line::from)
+        .collect();
+
+    let title = format!(
+        " {} (row {}, {} chars) | ↑↓ PgUp/PgDn scroll | Esc close ",
+        column,
+        app.cursor_row + 1,
+        value.chars().count()
+    );
+    render_popup(f, app, popup, title, lines);
+}
+
+/// Render the statistics of the focused column, one line per figure, in a popup over the table
+pub fn render_column_profile(f: &mut Frame, app: &App, area: Rect) {
+    let Some((column, stats)) = &app.column_profile else {
+        return;
+    };
+    let label_width = stats.iter().map(|(label, _)| text::width(label)).max().unwrap_or(0);
+    let lines = stats
+        .iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{}  ", text::pad(label, label_width)),
+                    Style::default().fg(app.theme.frame).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(value.as_str()),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        " Statistics of {} ({} filtered rows) | ↑↓ PgUp/PgDn scroll | Esc close ",
+        column,
+        crate::ui::format_count(app.total_rows())
+    );
+    render_popup(f, app, popup_area(area), title, lines);
+}
+
+/// Popups cover the table, leaving a margin so it is clear they are on top
+pub fn popup_area(area: Rect) -> Rect {
+    Rect {
+        x: area.x + 2,
+        y: area.y + 1,
+        width: area.width.saturating_sub(4),
+        height: area.height.saturating_sub(2),
+    }
+}
+
+/// Draw `lines` scrolled by the app's popup scroll, and note how far they can scroll
+pub fn render_popup(f: &mut Frame, app: &App, popup: Rect, title: String, lines: Vec<Line>) {
+    let max_scroll = lines.len().saturating_sub(popup.height.saturating_sub(2) as usize);
+    app.popup_max_scroll.set(max_scroll);
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default().fg(app.theme.text));
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((app.popup_scroll.min(max_scroll) as u16, 0));
+
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
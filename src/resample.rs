@@ -0,0 +1,317 @@
+use crate::app::App;
+use crate::ui::format_count;
+use anyhow::Result;
+use polars::prelude::*;
+use std::cell::Cell;
+
+/// Bucket sizes the resample screen cycles through with `t`, in Polars duration syntax
+pub const INTERVALS: [&str; 4] = ["1m", "5m", "1h", "1d"];
+
+/// Aggregates of the numeric columns the resample screen cycles through with `a`
+pub const AGGREGATES: [&str; 6] = ["mean", "sum", "min", "max", "first", "last"];
+
+/// Name of the count column, which cannot clash with the columns next to it
+const COUNT_COLUMN: &str = "__count";
+
+/// Values of a text column tried as datetimes before all of them are converted
+const DATETIME_SAMPLE: usize = 100;
+
+/// Decimal places of means and sums when `--precision` is not given
+const DEFAULT_PRECISION: usize = 4;
+
+/// Filtered rows grouped into time buckets of a date or datetime column, with the rows
+/// counted and the numeric columns aggregated per bucket, for the resample screen (`R`)
+pub struct Resample {
+    /// Date or datetime column the rows are bucketed by
+    pub column: String,
+    /// Numeric columns aggregated per bucket
+    pub values: Vec<String>,
+    /// Index into `INTERVALS`
+    pub interval: usize,
+    /// Index into `AGGREGATES`
+    pub aggregate: usize,
+    /// Rows bucketed, kept to bucket them again at another interval
+    rows: DataFrame,
+    /// Filtered rows of a paged source the bucketed ones are the first of, when more
+    pub sampled_from: Option<usize>,
+    /// One row per bucket, in time order: the bucket start, the count and the aggregates
+    buckets: DataFrame,
+    /// Bucket under the cursor
+    pub cursor: usize,
+    /// First of `values` shown, for terminals too narrow for all of them
+    pub first_value: usize,
+    /// First bucket shown, kept while drawn so the table only scrolls when the cursor leaves it
+    offset: Cell<usize>,
+}
+
+impl Resample {
+    /// Bucket `df` by `column` at the first interval, aggregating its numeric columns
+    /// among `columns`. Columns of text are read as datetimes, as CSV files store them.
+    pub fn of(df: &DataFrame, column: &str, columns: &[String]) -> Result<Self> {
+        let dtype = df.column(column)?.dtype().clone();
+        let mut rows = df.clone();
+        if dtype == DataType::String {
+            let parse = |strict| {
+                let options = StrptimeOptions {
+                    strict,
+                    ..Default::default()
+                };
+                col(column).str().to_datetime(None, None, options, lit("raise"))
+            };
+            // Searching every value for a format is slow, so a few values are tried first
+            let sample = df.column(column)?.drop_nulls().head(Some(DATETIME_SAMPLE));
+            let parses = DataFrame::new(vec![sample]).and_then(|sample| sample.lazy().select([parse(true)]).collect());
+            if parses.is_err() || df.column(column)?.null_count() == df.height() {
+                anyhow::bail!("Column '{}' holds no dates or times; focus one to resample by it", column);
+            }
+            rows = rows.lazy().with_column(parse(false)).collect()?;
+        } else if !dtype.is_temporal() || dtype == DataType::Time {
+            anyhow::bail!("Column '{}' holds no dates or times; focus one to resample by it", column);
+        }
+
+        let schema = rows.schema();
+        let values = columns
+            .iter()
+            .filter(|name| name.as_str() != column && schema.get(name).is_some_and(|dtype| dtype.is_numeric()))
+            .cloned()
+            .collect();
+        let mut resample = Self {
+            column: column.to_string(),
+            values,
+            interval: 0,
+            aggregate: 0,
+            rows,
+            sampled_from: None,
+            buckets: DataFrame::empty(),
+            cursor: 0,
+            first_value: 0,
+            offset: Cell::new(0),
+        };
+        resample.bucket()?;
+        Ok(resample)
+    }
+
+    /// Group the rows again with the current interval and aggregate
+    fn bucket(&mut self) -> Result<()> {
+        let mut aggregates = vec![len().alias(COUNT_COLUMN)];
+        for value in &self.values {
+            let values = col(value.as_str());
+            aggregates.push(match AGGREGATES[self.aggregate] {
+                "mean" => values.cast(DataType::Float64).mean(),
+                "sum" => values.sum(),
+                "min" => values.min(),
+                "max" => values.max(),
+                "first" => values.first(),
+                _ => values.last(),
+            });
+        }
+        self.buckets = self
+            .rows
+            .clone()
+            .lazy()
+            .filter(col(self.column.as_str()).is_not_null())
+            .group_by([col(self.column.as_str()).dt().truncate(lit(INTERVALS[self.interval]))])
+            .agg(aggregates)
+            .sort([self.column.as_str()], SortMultipleOptions::default())
+            .collect()?;
+        self.cursor = self.cursor.min(self.len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// Switch to the next bucket size, going back to the first bucket
+    pub fn next_interval(&mut self) -> Result<()> {
+        self.interval = (self.interval + 1) % INTERVALS.len();
+        self.cursor = 0;
+        self.offset.set(0);
+        self.bucket()
+    }
+
+    /// Switch to the next aggregate of the numeric columns
+    pub fn next_aggregate(&mut self) -> Result<()> {
+        self.aggregate = (self.aggregate + 1) % AGGREGATES.len();
+        self.bucket()
+    }
+
+    /// Number of buckets
+    pub fn len(&self) -> usize {
+        self.buckets.height()
+    }
+
+    /// Rows in bucket `i`
+    fn count(&self, i: usize) -> usize {
+        self.buckets
+            .column(COUNT_COLUMN)
+            .ok()
+            .and_then(|counts| counts.get(i).ok())
+            .and_then(|count| count.extract::<usize>())
+            .unwrap_or(0)
+    }
+
+    /// Move the cursor by `delta` buckets, stopping at either end
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Scroll the aggregated columns shown by `delta`, keeping at least the last one in view
+    pub fn scroll_values(&mut self, delta: isize) {
+        self.first_value = self.first_value.saturating_add_signed(delta).min(self.values.len().saturating_sub(1));
+    }
+}
+
+/// Render the resample screen
+pub fn render_resample(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::{
+        layout::{Constraint, Direction, Layout},
+        style::{Modifier, Style},
+        text::{Line, Span},
+        widgets::{Block, Borders, Cell as TableCell, Paragraph, Row, Sparkline, Table, TableState},
+    };
+
+    let Some(resample) = &app.resample else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),  // Header
+            Constraint::Length(5),  // Sparkline of the counts
+            Constraint::Min(0),     // Bucket table
+            Constraint::Length(3),  // Footer
+        ])
+        .split(area);
+
+    // Header
+    let header_block = Block::default()
+        .title(format!(" Resample by {} ", resample.column))
+        .borders(Borders::ALL)
+        .style(Style::default().fg(app.theme.frame));
+
+    let header_text = vec![Line::from(vec![
+        Span::styled("↑↓ PgUp/PgDn g/G", app.theme.key()),
+        Span::raw(" navigate | "),
+        Span::styled("t", app.theme.key()),
+        Span::raw(format!(" bucket size ({}) | ", INTERVALS[resample.interval])),
+        Span::styled("a", app.theme.key()),
+        Span::raw(format!(" aggregate ({}) | ", AGGREGATES[resample.aggregate])),
+        Span::styled("←→", app.theme.key()),
+        Span::raw(" scroll columns | "),
+        Span::styled("Esc", app.theme.key()),
+        Span::raw(" back"),
+    ])];
+
+    let header = Paragraph::new(header_text).block(header_block);
+    f.render_widget(header, chunks[0]);
+
+    // Only the buckets on screen are formatted, as fine buckets over a long range are many
+    let height = chunks[2].height.saturating_sub(4).max(1) as usize;
+    let mut offset = resample.offset.get().min(resample.cursor);
+    if resample.cursor >= offset + height {
+        offset = resample.cursor + 1 - height;
+    }
+    resample.offset.set(offset);
+
+    // The counts from the first bucket listed, one bar each, as far as the width goes
+    let spark_width = chunks[1].width.saturating_sub(2) as usize;
+    let counts: Vec<u64> = (offset..resample.len().min(offset + spark_width))
+        .map(|i| resample.count(i) as u64)
+        .collect();
+    let sparkline = Sparkline::default()
+        .block(Block::default().title(" Rows per bucket, from the first listed ").borders(Borders::ALL))
+        .data(&counts)
+        .style(Style::default().fg(app.theme.good));
+    f.render_widget(sparkline, chunks[1]);
+
+    let fractions = app.cell_format.with_default_precision(DEFAULT_PRECISION);
+    let text = |name: &str, i: usize| {
+        let value = resample.buckets.column(name).and_then(|values| values.get(i)).unwrap_or(AnyValue::Null);
+        let format = if matches!(value, AnyValue::Float32(_) | AnyValue::Float64(_)) { &fractions } else { &app.cell_format };
+        format.format(&value).unwrap_or_else(|| format.null.clone())
+    };
+    let shown_rows = offset..resample.len().min(offset + height);
+    let bucket_texts: Vec<String> = shown_rows.clone().map(|i| text(&resample.column, i)).collect();
+    let count_texts: Vec<String> = shown_rows.clone().map(|i| format_count(resample.count(i))).collect();
+
+    // Figures are right-aligned so their digits line up, and only as many columns as fit
+    // are shown, as squeezed figures would be cut
+    const SPACING: usize = 2;
+    let available = chunks[2].width.saturating_sub(2) as usize;
+    let bucket_width = bucket_texts.iter().map(|t| crate::text::width(t)).max().unwrap_or(0).max(6);
+    let count_width = count_texts.iter().map(|t| t.len()).max().unwrap_or(0).max(4);
+    let mut used = bucket_width + SPACING + count_width;
+    let mut values: Vec<(&String, Vec<String>, usize)> = Vec::new();
+    for name in resample.values.iter().skip(resample.first_value) {
+        let figures: Vec<String> = shown_rows.clone().map(|i| text(name, i)).collect();
+        let width = figures
+            .iter()
+            .map(|t| crate::text::width(t))
+            .max()
+            .unwrap_or(0)
+            .max(crate::text::width(name));
+        if !values.is_empty() && used + SPACING + width > available {
+            break;
+        }
+        used += SPACING + width;
+        values.push((name, figures, width));
+    }
+
+    let right = |text: &str, width: usize| format!("{:>width$}", text, width = width);
+    let mut header_cells = vec![TableCell::from("Bucket"), TableCell::from(right("rows", count_width))];
+    header_cells.extend(values.iter().map(|(name, _, width)| TableCell::from(right(name, *width))));
+    let rows: Vec<Row> = shown_rows
+        .clone()
+        .enumerate()
+        .map(|(row, i)| {
+            let mut cells = vec![
+                TableCell::from(bucket_texts[row].clone()),
+                TableCell::from(right(&count_texts[row], count_width)),
+            ];
+            cells.extend(values.iter().map(|(_, figures, width)| TableCell::from(right(&figures[row], *width))));
+            let style = if i == resample.cursor { app.theme.cursor_entry() } else { Style::default() };
+            Row::new(cells).style(style)
+        })
+        .collect();
+
+    let mut widths = vec![Constraint::Length(bucket_width as u16), Constraint::Length(count_width as u16)];
+    widths.extend(values.iter().map(|(_, _, width)| Constraint::Length(*width as u16)));
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(header_cells)
+                .style(Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD))
+                .bottom_margin(1),
+        )
+        .column_spacing(SPACING as u16)
+        .block(Block::default().title(format!(" {} per bucket ", AGGREGATES[resample.aggregate])).borders(Borders::ALL))
+        .style(Style::default().fg(app.theme.text));
+    let mut state = TableState::default().with_selected(Some(resample.cursor.saturating_sub(offset)));
+    f.render_stateful_widget(table, chunks[2], &mut state);
+
+    // Footer
+    let footer_block = Block::default()
+        .title(" Info ")
+        .borders(Borders::ALL);
+
+    let rows = match resample.sampled_from {
+        Some(total) => format!("the first {} of {}", format_count(resample.rows.height()), format_count(total)),
+        None => format_count(resample.rows.height()),
+    };
+    let mut footer_text = format!(
+        "{} buckets of {} in {} filtered rows | bucket {} of {}",
+        format_count(resample.len()),
+        INTERVALS[resample.interval],
+        rows,
+        format_count(resample.cursor + 1),
+        format_count(resample.len())
+    );
+    if values.len() < resample.values.len() {
+        footer_text.push_str(&format!(
+            " | columns {} to {} of {}",
+            resample.first_value + 1,
+            resample.first_value + values.len(),
+            resample.values.len()
+        ));
+    }
+
+    let footer = Paragraph::new(footer_text).block(footer_block);
+    f.render_widget(footer, chunks[3]);
+}
@@ -0,0 +1,123 @@
+use crate::app::App;
+use crate::data::format::type_label;
+use crate::ui::format_count;
+
+/// Render the schema overview: every column with its type, missing and distinct values
+/// and an example value
+pub fn render_schema_view(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::{
+        layout::{Constraint, Direction, Layout},
+        style::{Modifier, Style},
+        text::{Line, Span},
+        widgets::{Block, Borders, Paragraph, Row, Table, TableState},
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),  // Header
+            Constraint::Min(0),     // Column table
+            Constraint::Length(3),  // Footer
+        ])
+        .split(area);
+
+    // Header
+    let header_block = Block::default()
+        .title(" Schema ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(app.theme.frame));
+
+    let header_text = vec![Line::from(vec![
+        Span::styled("↑↓ PgUp/PgDn g/G", app.theme.key()),
+        Span::raw(" navigate | "),
+        Span::styled("Enter", app.theme.key()),
+        Span::raw(" go to column | "),
+        Span::styled("Esc", app.theme.key()),
+        Span::raw(" back"),
+    ])];
+
+    let header = Paragraph::new(header_text).block(header_block);
+    f.render_widget(header, chunks[0]);
+
+    // One row per column, in display order; hidden columns are dimmed
+    let columns = app.all_columns();
+    // Types given with `--dtype` are marked, as they were not inferred from the values,
+    // unless a query has since turned the column into another
+    let mut types = app.column_types();
+    for (column, label) in types.iter_mut() {
+        if app.forced_type(column).is_some_and(|dtype| type_label(dtype) == *label) {
+            label.push_str(" (--dtype)");
+        }
+    }
+    let rows: Vec<Row> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            // Counts appear as the background job gets to the column
+            let (nulls, distinct) = match app.column_stats(column) {
+                Some(stats) => (
+                    format_count(stats.nulls),
+                    stats.distinct.map_or("?".to_string(), format_count),
+                ),
+                None if app.column_stats_pending() => ("…".to_string(), "…".to_string()),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            let style = if i == app.schema_cursor {
+                app.theme.cursor_entry()
+            } else if app.is_column_selected(column) {
+                Style::default()
+            } else {
+                Style::default().fg(app.theme.muted)
+            };
+            Row::new(vec![
+                column.clone(),
+                types.get(column).cloned().unwrap_or_default(),
+                nulls,
+                distinct,
+                app.schema_example(column).unwrap_or_default().to_string(),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let name_width = columns.iter().map(|c| crate::text::width(c)).max().unwrap_or(0).max(6) as u16;
+    let type_width = types.values().map(|t| crate::text::width(t)).max().unwrap_or(0).max(4) as u16;
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(name_width),
+            Constraint::Length(type_width),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Min(10),
+        ],
+    )
+    .header(
+        Row::new(["Column", "Type", "Missing", "Distinct", "Example"])
+            .style(Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD))
+            .bottom_margin(1),
+    )
+    .block(Block::default().title(" Columns ").borders(Borders::ALL))
+    .style(Style::default().fg(app.theme.text));
+
+    // The table scrolls from where it was drawn last, just enough to keep the cursor in view
+    let mut state = TableState::default()
+        .with_offset(app.schema_list_offset.get())
+        .with_selected(Some(app.schema_cursor));
+    f.render_stateful_widget(table, chunks[1], &mut state);
+    app.schema_list_offset.set(state.offset());
+
+    // Footer
+    let footer_block = Block::default()
+        .title(" Info ")
+        .borders(Borders::ALL);
+
+    let footer_text = format!(
+        "{} columns | {} filtered rows | counts are of the filtered rows",
+        columns.len(),
+        format_count(app.total_rows())
+    );
+
+    let footer = Paragraph::new(footer_text).block(footer_block);
+    f.render_widget(footer, chunks[2]);
+}
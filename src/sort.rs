@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+
+/// Direction of a sorted column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    /// Marker shown next to the column name in the table header
+    pub fn indicator(&self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "▲",
+            SortOrder::Descending => "▼",
+        }
+    }
+
+    /// SQL keyword for server-side sorting
+    pub fn sql(&self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "ASC",
+            SortOrder::Descending => "DESC",
+        }
+    }
+}
+
+/// How sorted columns compare their values
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SortOptions {
+    /// Compare runs of digits inside strings by their numeric value, so `file2` comes before `file10`
+    pub natural: bool,
+    /// Put missing values after all others instead of before them, in either direction
+    pub nulls_last: bool,
+}
+
+/// Column holding each row's position in the unsorted frame while sorting
+const POSITION_COLUMN: &str = "__position";
+
+/// Sort `df` by the keys of `spec` in priority order, later keys breaking ties in earlier
+/// ones. Rows with equal keys keep their order.
+///
+/// Also returns the position each sorted row had in `df`, which `restore_order` uses to
+/// undo the sort.
+pub fn sort_frame(
+    df: &DataFrame,
+    spec: &[(String, SortOrder)],
+    options: SortOptions,
+) -> Result<(DataFrame, IdxCa)> {
+    let columns: Vec<&str> = spec.iter().map(|(column, _)| column.as_str()).collect();
+    let sort_options = SortMultipleOptions::default()
+        .with_order_descending_multi(spec.iter().map(|(_, order)| *order == SortOrder::Descending))
+        .with_nulls_last(options.nulls_last)
+        .with_maintain_order(true);
+    let context = || format!("Failed to sort by {}", columns.join(", "));
+
+    // Sort keys and positions are extra columns, dropped again afterwards
+    let mut keyed = df.with_row_index(POSITION_COLUMN.into(), None)?;
+    let mut keys = Vec::with_capacity(columns.len());
+    for (i, column) in columns.iter().enumerate() {
+        let series = df.column(column).with_context(context)?;
+        if !options.natural || !matches!(series.dtype(), DataType::String | DataType::Categorical(..)) {
+            keys.push(column.to_string());
+            continue;
+        }
+        let key_name = format!("__natural_key_{}", i);
+        let key: StringChunked = series
+            .cast(&DataType::String)?
+            .str()?
+            .into_iter()
+            .map(|value| value.map(natural_key))
+            .collect();
+        keyed.with_column(key.with_name(key_name.as_str().into()))?;
+        keys.push(key_name);
+    }
+    let sorted = keyed.sort(keys, sort_options).with_context(context)?;
+    let positions = sorted.column(POSITION_COLUMN)?.idx()?.clone();
+    Ok((sorted.select(df.get_column_names_str())?, positions))
+}
+
+/// Put the rows of a frame sorted by `sort_frame` back in their unsorted order
+pub fn restore_order(sorted: &DataFrame, positions: &IdxCa) -> Result<DataFrame> {
+    let unsorted = positions.arg_sort(polars::prelude::SortOptions::default());
+    sorted.take(&unsorted).context("Failed to restore the row order")
+}
+
+/// Key that sorts lexically in natural order: every run of digits becomes its digit count,
+/// zero-padded, followed by the digits without leading zeros
+fn natural_key(value: &str) -> String {
+    let mut key = String::with_capacity(value.len() + 8);
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !c.is_ascii_digit() {
+            key.push(c);
+            continue;
+        }
+        let mut digits = String::from(c);
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+        let digits = digits.trim_start_matches('0');
+        key.push_str(&format!("{:04}{}", digits.len(), digits));
+    }
+    key
+}
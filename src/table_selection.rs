@@ -0,0 +1,82 @@
+use crate::app::App;
+
+/// Render table (sheet) selection UI
+pub fn render_table_selection(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::{
+        layout::{Constraint, Direction, Layout},
+        style::Style,
+        text::{Line, Span},
+        widgets::{Block, Borders, List, ListItem, Paragraph},
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),  // Header
+            Constraint::Min(0),     // Table list
+            Constraint::Length(3),  // Footer
+        ])
+        .split(area);
+
+    // Header
+    let header_block = Block::default()
+        .title(format!(" {} Selection ", app.table_label()))
+        .borders(Borders::ALL)
+        .style(Style::default().fg(app.theme.frame));
+
+    let header_text = vec![Line::from(vec![
+        Span::styled("↑↓", app.theme.key()),
+        Span::raw(" navigate | "),
+        Span::styled("Enter", app.theme.key()),
+        Span::raw(" open | "),
+        Span::styled("Esc", app.theme.key()),
+        Span::raw(" keep current"),
+    ])];
+
+    let header = Paragraph::new(header_text).block(header_block);
+    f.render_widget(header, chunks[0]);
+
+    // Table list
+    let current = app.current_table();
+    let items: Vec<ListItem> = app
+        .all_tables()
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let is_current = current == Some(name.as_str());
+            let marker = if is_current { "●" } else { " " };
+            let content = format!("{} {}", marker, name);
+
+            let style = if i == app.table_selection_cursor {
+                app.theme.cursor_entry()
+            } else if is_current {
+                Style::default().fg(app.theme.good)
+            } else {
+                Style::default()
+            };
+
+            ListItem::new(content).style(style)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().title(format!(" {}s ", app.table_label())).borders(Borders::ALL))
+        .style(Style::default().fg(app.theme.text));
+
+    f.render_widget(list, chunks[1]);
+
+    // Footer
+    let footer_block = Block::default()
+        .title(" Info ")
+        .borders(Borders::ALL);
+
+    let footer_text = format!(
+        "{} {}s | current: {}",
+        app.all_tables().len(),
+        app.table_label().to_lowercase(),
+        current.unwrap_or("-")
+    );
+
+    let footer = Paragraph::new(footer_text).block(footer_block);
+    f.render_widget(footer, chunks[2]);
+}
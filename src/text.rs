@@ -0,0 +1,73 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Terminal columns the text takes up; CJK characters and most emoji take two
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Cut the text off so it takes at most `max_width` columns, ending it with `...`
+/// if anything was cut. Whole graphemes are kept, so accents and emoji stay intact.
+pub fn truncate(text: &str, max_width: usize) -> String {
+    if width(text) <= max_width {
+        return text.to_string();
+    }
+    let (kept, _) = split_at_width(text, max_width.saturating_sub(3));
+    format!("{}...", kept)
+}
+
+/// Pad the text with spaces to `target` columns
+pub fn pad(text: &str, target: usize) -> String {
+    format!("{}{}", text, " ".repeat(target.saturating_sub(width(text))))
+}
+
+/// Split the text after its longest start that fits in `max_width` columns
+pub fn split_at_width(text: &str, max_width: usize) -> (&str, &str) {
+    let mut used = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > max_width {
+            return text.split_at(i);
+        }
+    }
+    (text, "")
+}
+
+/// Break `text` into lines of at most `max_width` columns, at spaces where possible.
+/// Line breaks in the text are kept; words longer than a line are split.
+pub fn wrap(text: &str, max_width: usize) -> Vec<String> {
+    let max_width = max_width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for mut word in paragraph.split_inclusive(' ') {
+            loop {
+                // A trailing space may hang past the edge
+                if line_width + width(word.trim_end()) <= max_width {
+                    line.push_str(word);
+                    line_width += width(word);
+                    break;
+                }
+                if line_width > 0 {
+                    lines.push(std::mem::take(&mut line).trim_end().to_string());
+                    line_width = 0;
+                    continue;
+                }
+                // At least one grapheme goes on each line, even if it is wider
+                let (mut head, mut rest) = split_at_width(word, max_width);
+                if head.is_empty() {
+                    let first = word.graphemes(true).next().map_or(word.len(), str::len);
+                    (head, rest) = word.split_at(first);
+                }
+                lines.push(head.to_string());
+                word = rest;
+                if word.is_empty() {
+                    break;
+                }
+            }
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
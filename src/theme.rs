@@ -0,0 +1,123 @@
+use ratatui::style::{Color, Modifier, Style};
+
+/// Colors the UI is drawn with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Name used with `--theme`
+    pub name: &'static str,
+    /// Key names in hints, column headers and highlighted labels
+    pub accent: Color,
+    /// Frames of the header and of the selection screens, pinned column headers
+    pub frame: Color,
+    /// Ready status, selected entries, added rows and the filter input
+    pub good: Color,
+    /// Errors and removed rows
+    pub bad: Color,
+    /// Line numbers and unselected entries
+    pub muted: Color,
+    /// Regular text
+    pub text: Color,
+    /// Background of the entry under the cursor in lists
+    pub selection: Color,
+    /// Text matched by the filter
+    pub matched: Color,
+    /// Text and background of the applied filter chips
+    pub chip: (Color, Color),
+    /// Background of every other table row
+    pub stripe: Color,
+    /// Background of the focused row in cell mode
+    pub cursor_row: Color,
+    /// Backgrounds of the smallest and largest values in the heatmap
+    pub heat: ((u8, u8, u8), (u8, u8, u8)),
+}
+
+/// The original yellow-on-default scheme, for dark terminals
+pub const DARK: Theme = Theme {
+    name: "dark",
+    accent: Color::Yellow,
+    frame: Color::Cyan,
+    good: Color::Green,
+    bad: Color::Red,
+    muted: Color::DarkGray,
+    text: Color::White,
+    selection: Color::DarkGray,
+    matched: Color::Magenta,
+    chip: (Color::Black, Color::Yellow),
+    stripe: Color::Indexed(235),
+    cursor_row: Color::Indexed(238),
+    heat: ((0x1d, 0x3b, 0x6e), (0x8c, 0x1c, 0x1c)),
+};
+
+/// Dark text and saturated accents that stay readable on light terminals
+pub const LIGHT: Theme = Theme {
+    name: "light",
+    accent: Color::Blue,
+    frame: Color::Magenta,
+    good: Color::Green,
+    bad: Color::Red,
+    muted: Color::DarkGray,
+    text: Color::Black,
+    selection: Color::Gray,
+    matched: Color::Red,
+    chip: (Color::White, Color::Blue),
+    stripe: Color::Indexed(255),
+    cursor_row: Color::Indexed(252),
+    heat: ((0xc6, 0xdb, 0xef), (0xfc, 0xae, 0x91)),
+};
+
+/// Solarized accents, readable on both its dark and light backgrounds
+pub const SOLARIZED: Theme = Theme {
+    name: "solarized",
+    accent: Color::Rgb(0xb5, 0x89, 0x00),
+    frame: Color::Rgb(0x26, 0x8b, 0xd2),
+    good: Color::Rgb(0x85, 0x99, 0x00),
+    bad: Color::Rgb(0xdc, 0x32, 0x2f),
+    muted: Color::Rgb(0x58, 0x6e, 0x75),
+    text: Color::Rgb(0x83, 0x94, 0x96),
+    selection: Color::Rgb(0x07, 0x36, 0x42),
+    matched: Color::Rgb(0xd3, 0x36, 0x82),
+    chip: (Color::Rgb(0xfd, 0xf6, 0xe3), Color::Rgb(0x6c, 0x71, 0xc4)),
+    stripe: Color::Rgb(0x07, 0x36, 0x42),
+    cursor_row: Color::Rgb(0x0e, 0x4b, 0x5a),
+    heat: ((0x0b, 0x3d, 0x5c), (0x6e, 0x1f, 0x1d)),
+};
+
+/// Built-in themes, in the order the cycle key steps through them
+pub const THEMES: [Theme; 3] = [DARK, LIGHT, SOLARIZED];
+
+impl Default for Theme {
+    fn default() -> Self {
+        DARK
+    }
+}
+
+impl Theme {
+    /// Built-in theme with the given name
+    pub fn by_name(name: &str) -> Option<Theme> {
+        THEMES.iter().copied().find(|theme| theme.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The theme after this one in `THEMES`
+    pub fn next(&self) -> Theme {
+        let i = THEMES.iter().position(|theme| theme == self).unwrap_or(0);
+        THEMES[(i + 1) % THEMES.len()]
+    }
+
+    /// Key names in hints and other emphasized labels
+    pub fn key(&self) -> Style {
+        Style::default().fg(self.accent).add_modifier(Modifier::BOLD)
+    }
+
+    /// Heatmap background for a value `level` of the way from its column's minimum (0.0)
+    /// to its maximum (1.0)
+    pub fn heat(&self, level: f64) -> Color {
+        let ((r0, g0, b0), (r1, g1, b1)) = self.heat;
+        let mix = |low: u8, high: u8| (low as f64 + (high as f64 - low as f64) * level).round() as u8;
+        Color::Rgb(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+    }
+
+    /// The entry under the cursor in lists
+    pub fn cursor_entry(&self) -> Style {
+        self.key().bg(self.selection)
+    }
+}
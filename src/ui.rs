@@ -1,15 +1,27 @@
-use crate::app::App;
+use crate::app::{App, COLUMN_SPACING};
+use crate::data::diff::{CHANGE_MARKER, STATUS_COLUMN};
+use crate::data::subset::RowSubset;
+use crate::data::TableData;
+use crate::text::{self, wrap};
+use crate::filter::FilterExpr;
+use regex::{Regex, RegexBuilder};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    layout::{Constraint, Direction, Layout, Margin, Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
+        Table, Tabs, Wrap,
+    },
     Frame,
 };
 
-/// Render the UI
-pub fn render(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
+/// Width of the line number column
+const LINE_NUMBER_WIDTH: u16 = 6;
+
+/// Split the screen into header, table, footer and status bar
+fn layout(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Header
@@ -17,32 +29,289 @@ pub fn render(f: &mut Frame, app: &App) {
             Constraint::Length(3), // Footer/Filter
             Constraint::Length(1), // Status bar
         ])
-        .split(f.area());
+        .split(area)
+}
+
+/// The focused pane of the table, and the other one when split
+fn pane_areas(app: &App, table_area: Rect) -> (Rect, Option<Rect>) {
+    let Some(split) = &app.split else {
+        return (table_area, None);
+    };
+    let halves = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+        .split(table_area);
+    if split.focus_bottom {
+        (halves[1], Some(halves[0]))
+    } else {
+        (halves[0], Some(halves[1]))
+    }
+}
+
+/// Measure every pane of the table; see `measure_pane`
+pub fn measure_table(app: &mut App, area: Rect) {
+    let (focused, other) = pane_areas(app, layout(area)[1]);
+    measure_pane(app, focused);
+    if let Some(other) = other {
+        app.switch_pane();
+        measure_pane(app, other);
+        app.switch_pane();
+    }
+}
+
+/// Fit the page to the rows the pane has room for, then measure the columns of the
+/// page and the room for them, so the app knows which columns fit on screen
+fn measure_pane(app: &mut App, table_area: Rect) {
+    // Borders, the header and the blank line below it
+    let room = table_area.height.saturating_sub(4) as usize;
+    app.set_page_size(room);
+    measure_columns(app, table_area);
+    // The focused cell was scrolled to by header widths, and may not fit once its
+    // column and those before it are measured by their cells
+    while app.cursor_right_of_screen() {
+        app.column_offset += 1;
+        measure_columns(app, table_area);
+    }
+
+    // Wrapped rows take several lines, so fewer of them fit
+    if app.wrap_cells {
+        let table_data = app.current_page();
+        let mut used = 0;
+        let fitting = row_heights(app, &table_data)
+            .into_iter()
+            .take_while(|height| {
+                used += height;
+                used <= room
+            })
+            .count()
+            .max(1);
+        if fitting < table_data.num_rows() {
+            app.set_page_size(fitting);
+            measure_columns(app, table_area);
+        }
+    }
+}
+
+/// Measure the columns of the current page and the room the table has for them.
+/// Columns off screen, whose cells are not formatted, are measured by their header.
+fn measure_columns(app: &mut App, table_area: Rect) {
+    let line_numbers = if app.show_line_numbers { LINE_NUMBER_WIDTH + COLUMN_SPACING } else { 0 };
+    app.table_width = table_area.width.saturating_sub(2 + line_numbers); // Borders
+
+    let table_data = app.current_page();
+    let widths = table_data
+        .headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            let header_width = text_width(&app.header_label(header)) + text_width(&type_suffix(&table_data.types[i]));
+            if !table_data.formatted[i] {
+                return header_width;
+            }
+            // As wide as the header or the widest cell on this page
+            table_data
+                .rows
+                .iter()
+                .map(|row| match &row[i] {
+                    Some(cell) => cell_lines(app, cell).iter().map(|line| text_width(line)).max().unwrap_or(0),
+                    None => text_width(&app.cell_format.null),
+                })
+                .max()
+                .unwrap_or(0)
+                .max(header_width)
+        })
+        .collect();
+    app.column_widths = widths;
+}
+
+/// Part of the table under a mouse position
+pub enum TableHit {
+    /// The header of the `index`th page column
+    Header(usize),
+    /// A row, and the page column unless the row number was hit
+    Cell(usize, Option<usize>),
+    /// The pane without focus, when split
+    OtherPane,
+}
+
+/// Find what of the table is at the screen position `(x, y)`, using the column
+/// widths from `measure_table`
+pub fn hit_table(app: &App, area: Rect, x: u16, y: u16) -> Option<TableHit> {
+    let (table_area, other) = pane_areas(app, layout(area)[1]);
+    if other.is_some_and(|other| other.contains(Position::new(x, y))) {
+        return Some(TableHit::OtherPane);
+    }
+    let inner = Block::default().borders(Borders::ALL).inner(table_area);
+    if !inner.contains(Position::new(x, y)) {
+        return None;
+    }
+
+    let mut left = inner.x;
+    let mut column = None;
+    if app.show_line_numbers {
+        left += LINE_NUMBER_WIDTH + COLUMN_SPACING;
+    }
+    if x >= left {
+        for i in app.screen_columns() {
+            let width = app.column_widths.get(i).copied().unwrap_or(0);
+            if x < left + width + COLUMN_SPACING {
+                column = Some(i);
+                break;
+            }
+            left += width + COLUMN_SPACING;
+        }
+    }
+
+    // The header is followed by a blank line, then rows of one line, or more when wrapped
+    match y - inner.y {
+        0 => column.map(TableHit::Header),
+        1 => None,
+        offset => {
+            let mut line = offset as usize - 2;
+            for (i, height) in row_heights(app, &app.current_page()).into_iter().enumerate() {
+                if line < height {
+                    return Some(TableHit::Cell(app.scroll_offset + i, column));
+                }
+                line -= height;
+            }
+            None
+        }
+    }
+}
+
+/// Render the UI
+pub fn render(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = layout(area);
 
     render_header(f, app, chunks[0]);
-    render_table(f, app, chunks[1]);
+    let (focused, other) = pane_areas(app, chunks[1]);
+    render_table(f, app, focused, true);
+    if let Some(other) = other {
+        app.switch_pane();
+        render_table(f, app, other, false);
+        app.switch_pane();
+    }
     render_footer(f, app, chunks[2]);
     render_status(f, app, chunks[3]);
+
+    if app.filter_mode {
+        render_completions(f, app, chunks[1], chunks[2]);
+    }
+    if app.help_open {
+        crate::help::render_help(f, app, chunks[1]);
+    } else if app.record.is_some() {
+        crate::record_view::render_record_view(f, app, chunks[1]);
+    } else if app.value_popup.is_some() {
+        crate::record_view::render_value_popup(f, app, chunks[1]);
+    } else if app.column_profile.is_some() {
+        crate::record_view::render_column_profile(f, app, chunks[1]);
+    }
+}
+
+/// Popup of column names matching the word being typed, just above the filter input
+fn render_completions(f: &mut Frame, app: &App, table_area: Rect, footer_area: Rect) {
+    const MAX_SHOWN: usize = 8;
+
+    let completions = app.filter_completions();
+    if completions.is_empty() {
+        return;
+    }
+
+    // Scroll the list so the highlighted entry stays visible
+    let first = app.completion_index.saturating_sub(MAX_SHOWN - 1);
+    let items: Vec<ListItem> = completions
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(MAX_SHOWN)
+        .map(|(i, name)| {
+            let style = if i == app.completion_index {
+                app.theme.cursor_entry()
+            } else {
+                Style::default()
+            };
+            ListItem::new(name.as_str()).style(style)
+        })
+        .collect();
+
+    let longest = completions.iter().map(|c| text::width(c)).max().unwrap_or(0);
+    let width = (longest as u16 + 4).max(30).min(footer_area.width);
+    let height = (items.len() as u16 + 2).min(table_area.height);
+    let area = Rect {
+        x: footer_area.x + 1,
+        y: footer_area.y.saturating_sub(height),
+        width,
+        height,
+    };
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(format!(" Columns ({}) Tab: complete ", completions.len()))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(app.theme.frame)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(list, area);
+}
+
+/// Render the tab bar listing open files
+pub fn render_tabs(f: &mut Frame, app: &App, titles: &[String], active: usize, area: Rect) {
+    let tabs = Tabs::new(titles.iter().map(|t| format!(" {} ", t)))
+        .select(active)
+        .style(Style::default().fg(app.theme.muted))
+        .highlight_style(Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD | Modifier::REVERSED))
+        .divider("|");
+    f.render_widget(tabs, area);
 }
 
 fn render_header(f: &mut Frame, app: &App, area: Rect) {
-    let title = format!(" {} ", app.file_path.display());
-    let block = Block::default()
+    let title = match (app.current_table(), app.encoding_name()) {
+        (Some(table), _) => format!(" {} [{}] ", app.file_path.display(), table),
+        (None, Some(encoding)) => format!(" {} [{}] ", app.file_path.display(), encoding),
+        (None, None) => format!(" {} ", app.file_path.display()),
+    };
+    let mut block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .style(Style::default().fg(Color::Cyan));
+        .style(Style::default().fg(app.theme.frame));
+    // Files loaded only in part say so, as the rows shown are not all there are
+    if let Some(partial) = app.partial() {
+        let rows = format_count(partial.rows);
+        let mut banner = match partial.subset {
+            RowSubset::Head(_) => format!(" Partial view: first {}", rows),
+            RowSubset::Tail(_) => format!(" Partial view: last {}", rows),
+            RowSubset::Sample(_) => format!(" Partial view: random {}", rows),
+        };
+        if let Some(total_rows) = partial.total_rows {
+            let about = if partial.exact { "" } else { "~" };
+            banner.push_str(&format!(" of {}{}", about, format_count(total_rows)));
+        }
+        banner.push_str(" rows");
+        if let Some(budget) = partial.budget {
+            banner.push_str(&format!(", over --max-memory {}", format_bytes(budget as usize)));
+        }
+        banner.push(' ');
+        let style = Style::default().fg(app.theme.bad).add_modifier(Modifier::BOLD | Modifier::REVERSED);
+        block = block.title(Line::styled(banner, style).right_aligned());
+    }
 
     let text = vec![
         Line::from(vec![
-            Span::styled("/", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled("/", app.theme.key()),
             Span::raw(" filter | "),
-            Span::styled("c", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(":", app.theme.key()),
+            Span::raw(" sql | "),
+            Span::styled("c", app.theme.key()),
             Span::raw(" columns | "),
-            Span::styled("n", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled("t", app.theme.key()),
+            Span::raw(" tables | "),
+            Span::styled("n", app.theme.key()),
             Span::raw(" line# | "),
-            Span::styled("↑↓←→", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled("↑↓←→", app.theme.key()),
             Span::raw(" navigate | "),
-            Span::styled("q", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled("?", app.theme.key()),
+            Span::raw(" help | "),
+            Span::styled("q", app.theme.key()),
             Span::raw(" quit"),
         ]),
     ];
@@ -51,7 +320,62 @@ fn render_header(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
-fn render_table(f: &mut Frame, app: &App, area: Rect) {
+/// Cells wider than this many columns are cut off with `...`
+const MAX_CELL_WIDTH: usize = 50;
+
+/// Most lines a wrapped cell takes up
+const MAX_WRAPPED_LINES: usize = 8;
+
+/// Lines a cell is shown on: cut off at `MAX_CELL_WIDTH` columns, or wrapped at
+/// that width onto at most `MAX_WRAPPED_LINES` lines when wrapping is on
+fn cell_lines(app: &App, cell: &str) -> Vec<String> {
+    if !app.wrap_cells {
+        return vec![text::truncate(cell, MAX_CELL_WIDTH)];
+    }
+    let mut lines = wrap(cell, MAX_CELL_WIDTH);
+    if lines.len() > MAX_WRAPPED_LINES {
+        lines.truncate(MAX_WRAPPED_LINES);
+        let last = &mut lines[MAX_WRAPPED_LINES - 1];
+        *last = format!("{}...", text::split_at_width(last, MAX_CELL_WIDTH - 3).0);
+    }
+    lines
+}
+
+/// Height of each row of the page, in lines
+fn row_heights(app: &App, table_data: &TableData) -> Vec<usize> {
+    let screen_columns = app.screen_columns();
+    table_data
+        .rows
+        .iter()
+        .map(|row| {
+            screen_columns
+                .iter()
+                .filter_map(|&i| row.get(i)?.as_deref())
+                .map(|cell| cell_lines(app, cell).len())
+                .max()
+                .unwrap_or(1)
+        })
+        .collect()
+}
+
+/// Data type shown after a column header, like ` (f64)`
+fn type_suffix(dtype: &str) -> String {
+    if dtype.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", dtype)
+    }
+}
+
+/// Columns the text takes up on screen
+fn text_width(text: &str) -> u16 {
+    text::width(text) as u16
+}
+
+/// Draw the rows and columns of the app's position; only the `focused` pane shows
+/// the cell cursor, and when split its frame is highlighted
+fn render_table(f: &mut Frame, app: &App, area: Rect, focused: bool) {
+    let cell_mode = app.cell_mode && focused;
     let table_data = app.current_page();
 
     if table_data.num_columns() == 0 {
@@ -63,102 +387,367 @@ fn render_table(f: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
-    // Build headers with optional line number column
+    // Build headers with optional line number column, and the data type suffix of each
     let mut all_headers = Vec::new();
+    let mut header_types = Vec::new();
     if app.show_line_numbers {
         all_headers.push("#".to_string());
+        header_types.push(String::new());
     }
 
-    // Apply column offset for horizontal scrolling
-    let visible_data_headers: Vec<String> = table_data.headers
+    // Pinned columns, then the others from the column offset for horizontal scrolling
+    let screen_columns = app.screen_columns();
+    let line_number_columns = usize::from(app.show_line_numbers);
+    let pinned = app.pinned_count();
+    let visible_data_headers: Vec<String> = screen_columns
         .iter()
-        .skip(app.column_offset)
-        .take(10) // Show up to 10 columns at a time
-        .cloned()
+        .map(|&i| app.header_label(&table_data.headers[i]))
         .collect();
 
     all_headers.extend(visible_data_headers);
+    header_types.extend(screen_columns.iter().map(|&i| type_suffix(&table_data.types[i])));
 
+    // In cell mode the focused column's header and row number are highlighted
+    let focused_header = cell_mode
+        .then(|| screen_columns.iter().position(|&i| i == app.cursor_column))
+        .flatten()
+        .map(|i| i + line_number_columns);
     let header_cells = all_headers
         .iter()
-        .map(|h| {
-            Cell::from(h.as_str())
-                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .enumerate()
+        .map(|(i, h)| {
+            let is_pinned = (line_number_columns..line_number_columns + pinned).contains(&i);
+            let color = if is_pinned { app.theme.frame } else { app.theme.accent };
+            let style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+            let line = Line::from(vec![
+                Span::styled(h.as_str(), style),
+                Span::styled(header_types[i].as_str(), Style::default().fg(app.theme.muted)),
+            ]);
+            if Some(i) == focused_header {
+                Cell::from(line).style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                Cell::from(line)
+            }
         });
 
     let header = Row::new(header_cells)
         .height(1)
         .bottom_margin(1);
 
+    // In comparisons, rows are colored by their diff status
+    let status_index = if app.is_diff() {
+        table_data.headers.iter().position(|h| h == STATUS_COLUMN)
+    } else {
+        None
+    };
+
+    let highlighters = match_highlighters(app, &table_data.headers);
+
+    let heights = row_heights(app, &table_data);
     let rows = table_data.rows.iter().enumerate().map(|(idx, row)| {
         let mut cells = Vec::new();
+        let status = status_index.map(|i| row[i].as_deref().unwrap_or_default());
+
+        let row_index = app.scroll_offset + idx;
 
         // Add line number if enabled
         if app.show_line_numbers {
-            let line_num = app.scroll_offset + idx + 1;
-            cells.push(Cell::from(format!("{}", line_num))
-                .style(Style::default().fg(Color::DarkGray)));
+            let line_num = row_index + 1;
+            let style = if cell_mode && row_index == app.cursor_row {
+                app.theme.key()
+            } else if app.is_row_selected(row_index) {
+                Style::default().fg(app.theme.text)
+            } else {
+                Style::default().fg(app.theme.muted)
+            };
+            cells.push(Cell::from(format!("{}", line_num)).style(style));
         }
 
         // Add data cells
-        let data_cells: Vec<Cell> = row
+        let data_cells: Vec<Cell> = screen_columns
             .iter()
-            .skip(app.column_offset)
-            .take(10)
-            .map(|cell| {
-                // Truncate long cells
-                let display = if cell.len() > 50 {
-                    format!("{}...", &cell[..47])
-                } else {
-                    cell.clone()
+            .map(|&column_index| {
+                // Missing values show as the dimmed placeholder
+                let (display, changed) = match &row[column_index] {
+                    Some(cell) => (
+                        Text::from(
+                            cell_lines(app, cell)
+                                .into_iter()
+                                .map(|line| highlight_matches(app, line, highlighters[column_index].as_ref()))
+                                .collect::<Vec<_>>(),
+                        ),
+                        status.is_some() && cell.contains(CHANGE_MARKER),
+                    ),
+                    None => (
+                        Text::styled(
+                            app.cell_format.null.clone(),
+                            Style::default().fg(app.theme.muted).add_modifier(Modifier::DIM),
+                        ),
+                        false,
+                    ),
                 };
-                Cell::from(display)
+                let heat = app
+                    .heat_level(&table_data.headers[column_index], row_index)
+                    .map(|level| Style::default().bg(app.theme.heat(level)));
+                if cell_mode && row_index == app.cursor_row && column_index == app.cursor_column {
+                    Cell::from(display).style(Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD))
+                } else if changed {
+                    Cell::from(display).style(app.theme.key())
+                } else if let Some(heat) = heat {
+                    Cell::from(display).style(heat)
+                } else {
+                    Cell::from(display)
+                }
             })
             .collect();
 
         cells.extend(data_cells);
-        Row::new(cells).height(1)
+        let mut row_style = match status {
+            Some(s) if s.contains("added") => Style::default().fg(app.theme.good),
+            Some(s) if s.contains("removed") => Style::default().fg(app.theme.bad),
+            _ => Style::default(),
+        };
+        if app.shade_rows {
+            if cell_mode && row_index == app.cursor_row {
+                row_style = row_style.bg(app.theme.cursor_row);
+            } else if row_index % 2 == 1 {
+                row_style = row_style.bg(app.theme.stripe);
+            }
+        }
+        if app.is_row_selected(row_index) {
+            row_style = row_style.bg(app.theme.selection);
+        }
+        Row::new(cells).height(heights[idx] as u16).style(row_style)
     });
 
-    // Calculate column widths dynamically
-    let num_visible_cols = all_headers.len();
-    let widths: Vec<Constraint> = if app.show_line_numbers && num_visible_cols > 1 {
-        // Give line number column fixed width, split rest evenly
-        let mut w = vec![Constraint::Length(6)]; // Line number column
-        let remaining_cols = num_visible_cols - 1;
-        if remaining_cols > 0 {
-            w.extend(vec![Constraint::Percentage(100 / remaining_cols as u16); remaining_cols]);
-        }
-        w
-    } else if num_visible_cols > 0 {
-        vec![Constraint::Percentage(100 / num_visible_cols as u16); num_visible_cols]
-    } else {
-        vec![Constraint::Percentage(100)]
-    };
+    // Columns are as wide as measured by `measure_table`
+    let mut widths = Vec::with_capacity(all_headers.len());
+    if app.show_line_numbers {
+        widths.push(Constraint::Length(LINE_NUMBER_WIDTH));
+    }
+    widths.extend(
+        screen_columns
+            .iter()
+            .map(|&i| Constraint::Length(app.column_widths.get(i).copied().unwrap_or(0))),
+    );
 
+    // Files still loading also count the rows read so far, which the view catches up with
+    let loading = app
+        .loading_rows()
+        .map(|rows| format!(", {} read so far", rows))
+        .unwrap_or_default();
     let table = Table::new(rows, widths)
+        .column_spacing(COLUMN_SPACING)
         .header(header)
         .block(
             Block::default()
                 .title(format!(
-                    " Data (rows {}-{} of {}) ",
+                    " Data (rows {}-{} of {}{}) ",
                     app.scroll_offset + 1,
                     (app.scroll_offset + table_data.num_rows()).min(table_data.total_rows),
-                    table_data.total_rows
+                    table_data.total_rows,
+                    loading
                 ))
                 .borders(Borders::ALL)
+                .border_style(if focused && app.split.is_some() {
+                    Style::default().fg(app.theme.frame)
+                } else {
+                    Style::default()
+                })
         )
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(app.theme.text));
 
     f.render_widget(table, area);
+    render_scrollbars(f, app, area, screen_columns.len() - pinned);
+}
+
+/// Draw where the page is among all rows on the right border, and where the scrolled
+/// columns are among the unpinned ones on the bottom border, when not all of them fit
+fn render_scrollbars(f: &mut Frame, app: &App, area: Rect, scrolled_on_screen: usize) {
+    let thumb = Style::default().fg(app.theme.accent);
+    let track = Style::default().fg(app.theme.muted);
+
+    let total_rows = app.total_rows();
+    if total_rows > app.page_size {
+        let mut state = ScrollbarState::new(total_rows - app.page_size + 1)
+            .viewport_content_length(app.page_size)
+            .position(app.scroll_offset);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(Some("│"))
+            .thumb_style(thumb)
+            .track_style(track);
+        f.render_stateful_widget(scrollbar, area.inner(Margin::new(0, 1)), &mut state);
+    }
+
+    let scrolled_columns = app.visible_columns().len() - app.pinned_count();
+    if scrolled_columns > scrolled_on_screen {
+        let mut state = ScrollbarState::new(scrolled_columns - scrolled_on_screen + 1)
+            .viewport_content_length(scrolled_on_screen)
+            .position(app.column_offset);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(Some("─"))
+            .thumb_symbol("■")
+            .thumb_style(thumb)
+            .track_style(track);
+        f.render_stateful_widget(scrollbar, area.inner(Margin::new(1, 0)), &mut state);
+    }
+}
+
+/// How string filters currently match, shown in the footer
+fn case_label(app: &App) -> &'static str {
+    if app.filter_options.ignore_case {
+        "case-insensitive"
+    } else {
+        "case-sensitive"
+    }
+}
+
+/// How global search currently matches, shown while typing a filter
+fn search_label(app: &App) -> &'static str {
+    if app.filter_options.fuzzy {
+        "fuzzy search"
+    } else {
+        "exact search"
+    }
+}
+
+/// Which columns global search looks at
+fn search_columns_label(app: &App) -> &'static str {
+    if app.filter_options.strings_only {
+        "string columns"
+    } else {
+        "all columns"
+    }
+}
+
+/// For each column, a regex finding the text that `:` conditions and global search in the
+/// current filter look for, so the table can show why a row matched
+fn match_highlighters(app: &App, headers: &[String]) -> Vec<Option<Regex>> {
+    let Ok(expr) = FilterExpr::parse(&app.filter_expression()) else {
+        return vec![None; headers.len()];
+    };
+    let terms = expr.contains_terms();
+
+    headers
+        .iter()
+        .map(|header| {
+            let patterns: Vec<String> = terms
+                .iter()
+                .filter(|(column, _)| column.is_none_or(|c| c == header))
+                .map(|(_, term)| regex::escape(term))
+                .collect();
+            if patterns.is_empty() {
+                return None;
+            }
+            RegexBuilder::new(&patterns.join("|"))
+                .case_insensitive(app.filter_options.ignore_case)
+                .build()
+                .ok()
+        })
+        .collect()
+}
+
+/// Format a row count with thousands separators, e.g. 56,789
+pub fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// How many rows the filter being typed would keep, computed by the live filter.
+/// Database sources only filter on Enter, so they have no preview.
+fn match_preview(app: &App) -> Option<String> {
+    if app.is_paged() || app.filter_pattern.trim().is_empty() {
+        return None;
+    }
+    if app.live_filter_pending() {
+        return Some("counting…".to_string());
+    }
+    Some(match app.match_preview {
+        Some(matched) => format!(
+            "would match {} of {} rows",
+            format_count(matched),
+            format_count(app.unfiltered_rows())
+        ),
+        None => "not a valid filter yet".to_string(),
+    })
+}
+
+/// The applied filters as chips, oldest first
+fn filter_chips(app: &App) -> Vec<Span<'static>> {
+    let style = Style::default().fg(app.theme.chip.0).bg(app.theme.chip.1);
+    let mut spans = Vec::new();
+    for (i, filter) in app.filters.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(format!(" {} ", filter), style));
+    }
+    spans
+}
+
+/// Split a cell's text into spans, with the matches of `highlighter` colored
+fn highlight_matches(app: &App, text: String, highlighter: Option<&Regex>) -> Line<'static> {
+    let Some(highlighter) = highlighter else {
+        return Line::from(text);
+    };
+
+    let style = Style::default().fg(app.theme.matched).add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for found in highlighter.find_iter(&text) {
+        if found.start() > last {
+            spans.push(Span::raw(text[last..found.start()].to_string()));
+        }
+        spans.push(Span::styled(found.as_str().to_string(), style));
+        last = found.end();
+    }
+    if last < text.len() {
+        spans.push(Span::raw(text[last..].to_string()));
+    }
+    Line::from(spans)
 }
 
 fn render_footer(f: &mut Frame, app: &App, area: Rect) {
-    if app.filter_mode {
+    if app.command_mode {
+        let block = Block::default()
+            .title(" SQL Query (Enter: run, Esc: cancel) ")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(app.theme.good));
+
+        let text = vec![
+            Line::from(format!(":{}", app.command.display())),
+            Line::from(vec![
+                Span::styled("Examples: ", Style::default().fg(app.theme.accent)),
+                Span::raw("SELECT * FROM df WHERE price > 5000 ORDER BY ts | price > 5000 AND volume > 0"),
+            ]),
+        ];
+        let paragraph = Paragraph::new(text).block(block);
+        f.render_widget(paragraph, area);
+    } else if app.filter_mode {
+        let mut title = format!(
+            " Advanced Filter (Enter: apply, Esc: cancel) [{}, {} (Ctrl+F), {} (Ctrl+T)] ",
+            case_label(app),
+            search_label(app),
+            search_columns_label(app)
+        );
+        if let Some(preview) = match_preview(app) {
+            title.push_str(&format!("— {} ", preview));
+        }
         let block = Block::default()
-            .title(" Advanced Filter (Enter: apply, Esc: cancel) ")
+            .title(title)
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Green));
+            .style(Style::default().fg(app.theme.good));
 
         // Insert cursor indicator in the pattern
         let mut display_text = app.filter_pattern.clone();
@@ -166,26 +755,47 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect) {
             display_text.insert(app.filter_cursor, '│'); // Use │ as cursor
         }
 
+        // The new filter narrows the applied ones, shown in front of it
+        let mut input = filter_chips(app);
+        if !input.is_empty() {
+            input.push(Span::raw(" AND "));
+        }
+        input.push(Span::raw(display_text));
+
         let text = vec![
-            Line::from(display_text),
+            Line::from(input),
             Line::from(vec![
-                Span::styled("Operators: ", Style::default().fg(Color::Cyan)),
-                Span::raw("= != > < >= <= :contains"),
+                Span::styled("Operators: ", Style::default().fg(app.theme.frame)),
+                Span::raw("= != > < >= <= :contains !: ^= $= ~regex !~"),
             ]),
             Line::from(vec![
-                Span::styled("Examples: ", Style::default().fg(Color::Yellow)),
+                Span::styled("Examples: ", Style::default().fg(app.theme.accent)),
                 Span::raw("Price > 5000 | InstrumentID = IC2602 AND Price > 5000"),
             ]),
         ];
         let paragraph = Paragraph::new(text).block(block);
         f.render_widget(paragraph, area);
-    } else if !app.filter_pattern.is_empty() {
+    } else if !app.filters.is_empty() {
         let block = Block::default()
-            .title(" Active Filter (press Esc to clear) ")
+            .title(format!(
+                " Active Filters (u: undo last, Esc: clear all) [{}, I toggles, {}] ",
+                case_label(app),
+                search_label(app)
+            ))
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Yellow));
+            .style(Style::default().fg(app.theme.accent));
 
-        let paragraph = Paragraph::new(app.filter_pattern.clone()).block(block);
+        let paragraph = Paragraph::new(Line::from(filter_chips(app)))
+            .block(block)
+            .wrap(Wrap { trim: false });
+        f.render_widget(paragraph, area);
+    } else if let Some(query) = &app.sql_query {
+        let block = Block::default()
+            .title(" SQL Query (press Esc to clear) ")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(app.theme.accent));
+
+        let paragraph = Paragraph::new(query.clone()).block(block);
         f.render_widget(paragraph, area);
     } else {
         let block = Block::default()
@@ -193,28 +803,125 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect) {
             .borders(Borders::ALL);
 
         let text = format!(
-            "Total rows: {} | Columns: {} (showing {}-{})",
+            "Total rows: {} | Columns: {} ({}) | Filter: {} | Sort: {}",
             app.original_total_rows(),
-            app.current_page().headers.len(),
-            app.column_offset + 1,
-            (app.column_offset + 10).min(app.current_page().headers.len())
+            app.visible_columns().len(),
+            columns_shown(app),
+            case_label(app),
+            if app.sort_options.natural { "natural" } else { "lexical" }
         );
         let paragraph = Paragraph::new(text).block(block);
         f.render_widget(paragraph, area);
     }
 }
 
+/// Which columns are on screen, e.g. "showing 3-12" or "2 pinned, showing 5-12"
+fn columns_shown(app: &App) -> String {
+    let screen = app.screen_columns();
+    let pinned = app.pinned_count();
+    let scrolled = match (screen.get(pinned), screen.last()) {
+        (Some(first), Some(last)) => format!("showing {}-{}", first + 1, last + 1),
+        _ => "none scrolled".to_string(),
+    };
+    if pinned > 0 {
+        format!("{} pinned, {}", pinned, scrolled)
+    } else {
+        scrolled
+    }
+}
+
+/// Size in B, KB, MB or GB, e.g. `12.3 MB`
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Milliseconds below a second, seconds otherwise, e.g. `45 ms` or `1.23 s`
+fn format_duration(duration: std::time::Duration) -> String {
+    if duration.as_millis() < 1000 {
+        format!("{} ms", duration.as_millis())
+    } else {
+        format!("{:.2} s", duration.as_secs_f64())
+    }
+}
+
+/// How many rows are picked, as a suffix of the cell position, e.g. ` | 3 picked`
+fn picked_rows(app: &App) -> String {
+    match app.selected_rows().len() {
+        0 => String::new(),
+        picked => format!(" | {} picked", picked),
+    }
+}
+
+/// Sum, mean and count of the focused numeric column, as a suffix of the cell position,
+/// e.g. ` | sum 1,234 · mean 61.7000 · count 20`
+fn column_totals(app: &App) -> String {
+    match app.cursor_column_totals() {
+        Some(totals) => format!(" | sum {} · mean {} · count {}", totals.sum, totals.mean, format_count(totals.count)),
+        None => String::new(),
+    }
+}
+
 fn render_status(f: &mut Frame, app: &App, area: Rect) {
-    let status = if let Some(err) = &app.error_message {
+    let status = if let Some(elapsed) = app.filter_running_for() {
+        Span::styled(
+            format!(" Filtering… {} | Esc cancels ", format_duration(elapsed)),
+            Style::default().fg(app.theme.frame).add_modifier(Modifier::BOLD)
+        )
+    } else if let Some(err) = &app.error_message {
         Span::styled(
             format!(" ERROR: {} ", err),
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            Style::default().fg(app.theme.bad).add_modifier(Modifier::BOLD)
         )
-    } else {
+    } else if let Some(message) = &app.message {
+        Span::styled(format!(" {} ", message), Style::default().fg(app.theme.good).add_modifier(Modifier::BOLD))
+    } else if app.cell_mode {
         Span::styled(
-            " Ready ",
-            Style::default().fg(Color::Green)
+            format!(
+                " Cell: row {}, {}{}{} | PgUp/PgDn g/G 0/$ jump | Enter row details | v full value | S stats | F values | R resample | y/Y copy | Space/V pick rows | p pin | H hide | = keep value | ! exclude value | s sort | Esc exit ",
+                app.cursor_row + 1,
+                app.cursor_column_name().unwrap_or_default(),
+                picked_rows(app),
+                column_totals(app)
+            ),
+            Style::default().fg(app.theme.frame)
         )
+    } else {
+        let mut parts = vec!["Ready".to_string()];
+        let picked = app.selected_rows().len();
+        if picked > 0 {
+            parts.push(format!("{} rows picked (Esc drops them)", picked));
+        }
+        match app.duplicates.as_deref() {
+            Some([]) => parts.push("duplicate rows only (Esc shows all)".to_string()),
+            Some(columns) => parts.push(format!("duplicates by {} only (Esc shows all)", columns.join(", "))),
+            None => {}
+        }
+        if let Some(size) = app.memory_size() {
+            parts.push(format!("{} in memory", format_bytes(size)));
+        }
+        if app.is_loading() {
+            parts.push("loading…".to_string());
+        } else if let Some(time) = app.load_time {
+            parts.push(format!("loaded in {}", format_duration(time)));
+        }
+        if let Some(time) = app.filter_time {
+            parts.push(format!("filtered in {}", format_duration(time)));
+        }
+        if let Some(time) = &app.reloaded_at {
+            parts.push(format!("reloaded at {}", time));
+        }
+        Span::styled(format!(" {} ", parts.join(" | ")), Style::default().fg(app.theme.good))
     };
 
     let paragraph = Paragraph::new(Line::from(vec![status]));
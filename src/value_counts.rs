@@ -0,0 +1,190 @@
+use crate::app::App;
+use crate::data::paged::COUNT_COLUMN;
+use crate::data::DataSource;
+use crate::ui::format_count;
+use anyhow::Result;
+use polars::prelude::*;
+use std::cell::Cell;
+
+/// Width of the bar drawn for a value found in every row; bars show each value's share
+const BAR_WIDTH: usize = 20;
+
+/// Most distinct values fetched from a database table or lazily read Parquet file,
+/// which counts them itself; the rarer ones are left out
+pub const PAGED_VALUES: usize = 10_000;
+
+/// Distinct values of a column with how often they occur among the filtered rows, most
+/// frequent first, for the frequency screen (`F`)
+pub struct ValueCounts {
+    pub column: String,
+    /// The distinct values, missing ones included, and their counts
+    counts: DataFrame,
+    /// Rows counted
+    pub rows: usize,
+    /// Whether only the `PAGED_VALUES` most frequent values were fetched
+    pub truncated: bool,
+    /// Entry under the cursor
+    pub cursor: usize,
+    /// First entry shown, kept while drawn so the list only scrolls when the cursor leaves it
+    offset: Cell<usize>,
+}
+
+impl ValueCounts {
+    /// Count the values of `column` of `df`; equally frequent values are in value order
+    pub fn of(df: &DataFrame, column: &str) -> Result<Self> {
+        let counts = df
+            .clone()
+            .lazy()
+            .group_by([col(column)])
+            .agg([len().alias(COUNT_COLUMN)])
+            .sort(
+                [COUNT_COLUMN, column],
+                SortMultipleOptions::default().with_order_descending_multi([true, false]),
+            )
+            .collect()?;
+        Ok(Self::new(column, counts, df.height(), false))
+    }
+
+    /// Count the values of `column` of a paged source where it is stored, as fetching
+    /// its rows to count them is what reading it page by page avoids
+    pub fn of_paged(source: &DataSource, column: &str) -> Result<Self> {
+        // One more than shown tells whether there were more
+        let mut counts = source.value_counts_paged(column, PAGED_VALUES + 1)?;
+        let truncated = counts.height() > PAGED_VALUES;
+        counts = counts.head(Some(PAGED_VALUES));
+        Ok(Self::new(column, counts, source.len(), truncated))
+    }
+
+    fn new(column: &str, counts: DataFrame, rows: usize, truncated: bool) -> Self {
+        Self {
+            column: column.to_string(),
+            counts,
+            rows,
+            truncated,
+            cursor: 0,
+            offset: Cell::new(0),
+        }
+    }
+
+    /// Number of distinct values
+    pub fn len(&self) -> usize {
+        self.counts.height()
+    }
+
+    /// Value and count of entry `i`
+    pub fn get(&self, i: usize) -> Result<(AnyValue<'static>, usize)> {
+        let value = self.counts.column(&self.column)?.get(i)?.into_static();
+        let count = self.counts.column(COUNT_COLUMN)?.get(i)?.extract::<usize>().unwrap_or(0);
+        Ok((value, count))
+    }
+
+    /// Move the cursor by `delta` entries, stopping at either end
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+}
+
+/// Render the frequency screen
+pub fn render_value_counts(f: &mut ratatui::Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::{
+        layout::{Constraint, Direction, Layout},
+        style::Style,
+        text::{Line, Span},
+        widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    };
+
+    let Some(counts) = &app.value_counts else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),  // Header
+            Constraint::Min(0),     // Value list
+            Constraint::Length(3),  // Footer
+        ])
+        .split(area);
+
+    // Header
+    let header_block = Block::default()
+        .title(format!(" Values of {} ", counts.column))
+        .borders(Borders::ALL)
+        .style(Style::default().fg(app.theme.frame));
+
+    let header_text = vec![Line::from(vec![
+        Span::styled("↑↓ PgUp/PgDn g/G", app.theme.key()),
+        Span::raw(" navigate | "),
+        Span::styled("Enter", app.theme.key()),
+        Span::raw(" keep rows with the value | "),
+        Span::styled("!", app.theme.key()),
+        Span::raw(" exclude them | "),
+        Span::styled("Esc", app.theme.key()),
+        Span::raw(" back"),
+    ])];
+
+    let header = Paragraph::new(header_text).block(header_block);
+    f.render_widget(header, chunks[0]);
+
+    // Only the entries on screen are built, as a column can have millions of values
+    let height = chunks[1].height.saturating_sub(2).max(1) as usize;
+    let mut offset = counts.offset.get().min(counts.cursor);
+    if counts.cursor >= offset + height {
+        offset = counts.cursor + 1 - height;
+    }
+    counts.offset.set(offset);
+
+    let shown: Vec<(String, usize)> = (offset..counts.len().min(offset + height))
+        .filter_map(|i| counts.get(i).ok())
+        .map(|(value, count)| (app.cell_format.format(&value).unwrap_or_else(|| app.cell_format.null.clone()), count))
+        .collect();
+    let value_width = shown.iter().map(|(value, _)| crate::text::width(value)).max().unwrap_or(0);
+    let count_width = shown.iter().map(|(_, count)| format_count(*count).len()).max().unwrap_or(0);
+    let items: Vec<ListItem> = shown
+        .iter()
+        .enumerate()
+        .map(|(i, (value, count))| {
+            let share = *count as f64 * 100.0 / counts.rows.max(1) as f64;
+            let bar = "█".repeat((count * BAR_WIDTH).div_ceil(counts.rows.max(1)));
+            let line = Line::from(vec![
+                Span::raw(format!(
+                    "{}  {:>count_width$}  {:>5.1}%  ",
+                    crate::text::pad(value, value_width),
+                    format_count(*count),
+                    share,
+                )),
+                Span::styled(bar, Style::default().fg(app.theme.good)),
+            ]);
+            let style = if offset + i == counts.cursor {
+                app.theme.cursor_entry()
+            } else {
+                Style::default()
+            };
+            ListItem::new(line).style(style)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().title(" Values by frequency ").borders(Borders::ALL))
+        .style(Style::default().fg(app.theme.text));
+    let mut state = ListState::default().with_selected(Some(counts.cursor - offset));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    // Footer
+    let footer_block = Block::default()
+        .title(" Info ")
+        .borders(Borders::ALL);
+
+    let values = if counts.truncated { "most frequent values" } else { "distinct values" };
+    let footer_text = format!(
+        "{} {} in {} filtered rows | value {} of {}",
+        format_count(counts.len()),
+        values,
+        format_count(counts.rows),
+        format_count(counts.cursor + 1),
+        format_count(counts.len())
+    );
+
+    let footer = Paragraph::new(footer_text).block(footer_block);
+    f.render_widget(footer, chunks[2]);
+}
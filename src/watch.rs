@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+/// How long a file has to stay quiet after a change before it is reloaded,
+/// so a file that is still being written is not read half-way through
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Watches the files behind open tabs and reports which tabs need reloading
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    /// Watched path and the tab it belongs to
+    targets: Vec<(PathBuf, usize)>,
+    /// Tabs with unprocessed changes and when the last change was seen
+    pending: Vec<(usize, Instant)>,
+}
+
+impl FileWatcher {
+    pub fn new() -> Result<Self> {
+        let (tx, events) = channel();
+        let watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+        Ok(Self {
+            watcher,
+            events,
+            targets: Vec::new(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Start watching the file (or dataset directory) shown in `tab`.
+    /// Files are watched through their parent directory so that editors and
+    /// tools that replace the file by renaming a new one over it are noticed too.
+    pub fn watch(&mut self, path: &Path, tab: usize) -> Result<()> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Cannot watch '{}'", path.display()))?;
+
+        if path.is_dir() {
+            self.watcher.watch(&path, RecursiveMode::Recursive)?;
+        } else {
+            let parent = path.parent().context("File has no parent directory")?;
+            self.watcher.watch(parent, RecursiveMode::NonRecursive)?;
+        }
+        self.targets.push((path, tab));
+        Ok(())
+    }
+
+    /// Tabs whose files changed and have since settled
+    pub fn changed_tabs(&mut self) -> Vec<usize> {
+        while let Ok(event) = self.events.try_recv() {
+            let Ok(event) = event else { continue };
+            if event.kind.is_access() {
+                continue;
+            }
+            for (target, tab) in &self.targets {
+                if event.paths.iter().any(|p| p.starts_with(target)) {
+                    self.pending.retain(|(t, _)| t != tab);
+                    self.pending.push((*tab, Instant::now()));
+                }
+            }
+        }
+
+        let (ready, waiting): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|(_, seen)| seen.elapsed() >= SETTLE_TIME);
+        self.pending = waiting;
+        ready.into_iter().map(|(tab, _)| tab).collect()
+    }
+}
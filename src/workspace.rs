@@ -0,0 +1,95 @@
+use crate::app::App;
+use crate::theme::Theme;
+
+/// A set of open files, each shown in its own tab with independent App state
+pub struct Workspace {
+    /// One App per open file
+    tabs: Vec<App>,
+    /// Index of the tab currently shown
+    active: usize,
+}
+
+impl Workspace {
+    /// Create an empty workspace
+    pub fn new() -> Self {
+        Self {
+            tabs: Vec::new(),
+            active: 0,
+        }
+    }
+
+    /// Add an already loaded App as a new tab
+    pub fn push(&mut self, app: App) {
+        self.tabs.push(app);
+    }
+
+    /// Get the active tab
+    pub fn active(&self) -> &App {
+        &self.tabs[self.active]
+    }
+
+    /// Get the active tab mutably
+    pub fn active_mut(&mut self) -> &mut App {
+        &mut self.tabs[self.active]
+    }
+
+    /// Get the tab at the given index mutably
+    pub fn tab_mut(&mut self, index: usize) -> &mut App {
+        &mut self.tabs[index]
+    }
+
+    /// Index of the active tab
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    /// Number of open tabs
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    /// Switch to the next tab (wrapping around)
+    pub fn next_tab(&mut self) {
+        if !self.tabs.is_empty() {
+            self.active = (self.active + 1) % self.tabs.len();
+        }
+    }
+
+    /// Switch to the previous tab (wrapping around)
+    pub fn prev_tab(&mut self) {
+        if !self.tabs.is_empty() {
+            self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+        }
+    }
+
+    /// Draw every tab with the given colors
+    pub fn set_theme(&mut self, theme: Theme) {
+        for app in &mut self.tabs {
+            app.theme = theme;
+        }
+    }
+
+    /// Tab titles: the file name of each tab
+    pub fn titles(&self) -> Vec<String> {
+        self.tabs
+            .iter()
+            .map(|app| {
+                app.file_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| app.file_path.display().to_string())
+            })
+            .collect()
+    }
+
+    /// Whether the application should exit
+    pub fn should_quit(&self) -> bool {
+        self.tabs.iter().any(|app| app.should_quit)
+    }
+
+    /// Whether any tab is still loading rows in the background
+    pub fn is_loading(&self) -> bool {
+        self.tabs.iter().any(|app| app.is_loading())
+    }
+
+}